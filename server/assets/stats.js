@@ -89,7 +89,7 @@
         if (extractor === null) {
             extractor = (cols) => {
                 return {
-                    label: cols[0].innerHTML,
+                    label: cols[0].textContent,
                     value: Number(cols[1].innerHTML),
                 };
             };
@@ -297,40 +297,46 @@
              });
     }
 
-    makeTreeMap(
-        d3.hierarchy({
-            children: extractData('duties'),
-        }).sum(d => d.value),
-        'dutiesChart',
-    );
-    makeTreeMap(
-        d3.hierarchy(
-            d3.group(
-                extractData(
-                    'hosts',
-                    (cols) => {
-                        return {
-                            label: cols[1].innerHTML,
-                            world: cols[0].innerHTML,
-                            value: Number(cols[2].innerHTML),
-                        };
-                    },
-                ),
-                d => d.world,
-            )
-        ).sum(d => d.value),
-        'hostsChart',
-        {
-            drawLabels: false,
-            grouped: true,
-        },
-    );
-    makeBarPlot(
-        extractData('hours'),
-        'hoursChart',
-    );
-    makeBarPlot(
-        extractData('days'),
-        'daysChart',
-    );
+    function hasChart(graphId) {
+        return document.getElementById(graphId) !== null;
+    }
+
+    if (hasChart('dutiesChart')) {
+        makeTreeMap(
+            d3.hierarchy({
+                children: extractData('duties'),
+            }).sum(d => d.value),
+            'dutiesChart',
+        );
+    }
+    if (hasChart('hostsChart')) {
+        makeTreeMap(
+            d3.hierarchy(
+                d3.group(
+                    extractData(
+                        'hosts',
+                        (cols) => {
+                            return {
+                                label: cols[1].innerHTML,
+                                world: cols[0].innerHTML,
+                                value: Number(cols[2].innerHTML),
+                            };
+                        },
+                    ),
+                    d => d.world,
+                )
+            ).sum(d => d.value),
+            'hostsChart',
+            {
+                drawLabels: false,
+                grouped: true,
+            },
+        );
+    }
+    for (let name of ['volume', 'hours', 'days', 'objectives', 'conditions']) {
+        let data = hasChart(`${name}Chart`) ? extractData(name) : [];
+        if (data.length > 0) {
+            makeBarPlot(data, `${name}Chart`);
+        }
+    }
 })();
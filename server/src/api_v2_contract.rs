@@ -4,16 +4,22 @@ use mongodb::bson::doc;
 
 use crate::listing::{
     ConditionFlags, DutyCategory, DutyType, JobFlags, LootRuleFlags, ObjectiveFlags,
+    PartyFinderSlot, SearchAreaFlags,
 };
 use crate::listing_container::QueriedListing;
+use crate::store::UpsertOutcome;
 use crate::web::v2::contracts::{
-    CollectionEnvelope, ErrorEnvelope, ListingDetail, ListingMemberResponse, ListingSlot,
-    ListingSummary, Pagination,
+    CollectionEnvelope, CursorEnvelope, CursorPagination, DutyCount, DutyNameForm, ErrorEnvelope,
+    ExclusionCode, HistoricalListing, HostCount, HourCount, ListingDetail, ListingMemberResponse,
+    ListingSlot, ListingSummary, MemberEnvelope, Pagination, PlayerCount, Statistics,
+    WeekdayCount,
 };
 use crate::web::v2::filters::ListingsQuery;
 use crate::web::v2::id_inventory;
+use crate::web::v2::lookups;
+use crate::web::v2::stream;
 use crate::web::v2::listings::{
-    collection_response_from_documents,
+    collection_filter, collection_response_from_documents,
     collection_response_from_raw_documents_for_tests, member_route_for_tests,
     project_listing_detail, project_listing_summaries, project_listing_summary,
     resolve_listing_detail,
@@ -254,8 +260,44 @@ fn docs_include_minimal_contract_examples() {
         data: sample_detail(),
     };
 
+    let history = CursorEnvelope {
+        data: vec![HistoricalListing {
+            summary: sample_summary(),
+            created_at: "2026-04-23T12:20:00Z".into(),
+        }],
+        pagination: CursorPagination {
+            per_page: 20,
+            next_cursor: None,
+        },
+    };
+
     let collection_json = serde_json::to_string_pretty(&collection).unwrap();
     let detail_json = serde_json::to_string_pretty(&detail).unwrap();
+    let history_json = serde_json::to_string_pretty(&history).unwrap();
+    let statistics = MemberEnvelope {
+        data: Statistics {
+            listings: 3,
+            duties: vec![DutyCount {
+                duty_type_id: 2,
+                category_id: 32,
+                duty_id: 1010,
+                count: 3,
+            }],
+            hosts: vec![HostCount {
+                created_world_id: 1167,
+                count: 3,
+                top_hosts: vec![PlayerCount {
+                    player_name: Some("Alice".into()),
+                    home_world_id: Some(1167),
+                    count: 2,
+                }],
+                other_count: 1,
+            }],
+            hours: vec![HourCount { hour: 9, count: 3 }],
+            weekdays: vec![WeekdayCount { weekday: 1, count: 3 }],
+        },
+    };
+    let statistics_json = serde_json::to_string_pretty(&statistics).unwrap();
 
     assert!(
         readme.contains("See [`docs/api-v2.md`](docs/api-v2.md)"),
@@ -263,8 +305,74 @@ fn docs_include_minimal_contract_examples() {
     );
     assert!(api_v2_doc.contains("`GET /api/v2/listings`"));
     assert!(api_v2_doc.contains("`GET /api/v2/listings/{id}`"));
+    assert!(api_v2_doc.contains("`GET /api/v2/history/listings`"));
+    assert!(api_v2_doc.contains("`GET /api/v2/stats` and `GET /api/v2/stats/7days`"));
+    assert!(api_v2_doc.contains("`GET /api/v2/stats/duties/{duty_type_id}/{category_id}/{duty_id}`"));
     assert!(api_v2_doc.contains("`GET /api/v2/listings?datacenter=Aether,Primal`"));
     assert!(api_v2_doc.contains("`GET /api/v2/listings?region=North-America,Japan`"));
+    assert!(api_v2_doc.contains("`GET /api/v2/listings?sort=-updated_at,time_left_seconds`"));
+    assert!(api_v2_doc.contains("Without `sort`, listings are ordered by `updated_at` descending"));
+    assert!(api_v2_doc.contains("`GET /api/v2/listings?beginners_welcome=true&is_cross_world=true&slots_open_gte=2`"));
+    for key in crate::web::v2::filters::SortKey::ALL {
+        assert!(
+            api_v2_doc.contains(&format!("`{}`", key.name())),
+            "docs/api-v2.md is missing sort field {}",
+            key.name()
+        );
+    }
+    assert!(api_v2_doc.contains("## `GET /api/v2/listings/fit`"));
+    assert!(api_v2_doc.contains("`GET /api/v2/listings/fit?party_job_ids=19,24,40`"));
+    assert!(api_v2_doc.contains("## `POST /api/v2/listings/eligible`"));
+    for code in [
+        ExclusionCode::OtherWorld,
+        ExclusionCode::OtherDataCenter,
+        ExclusionCode::DutyClearRequired,
+        ExclusionCode::DutyNotClearedRequired,
+        ExclusionCode::WeeklyRewardUnclaimedRequired,
+        ExclusionCode::ItemLevelTooLow,
+        ExclusionCode::JobAlreadyInParty,
+        ExclusionCode::NoOpenSlotForJob,
+    ] {
+        let code = serde_json::to_value(code).unwrap();
+        assert!(
+            api_v2_doc.contains(&format!("| `{}` |", code.as_str().unwrap())),
+            "docs/api-v2.md is missing exclusion code {code}"
+        );
+    }
+    assert!(api_v2_doc.contains("`GET /api/v2/lookups/{kind}`"));
+    assert!(api_v2_doc.contains("`GET /api/v2/lookups/duties/search`"));
+    for form in [
+        DutyNameForm::En,
+        DutyNameForm::Ja,
+        DutyNameForm::JaRomaji,
+        DutyNameForm::De,
+        DutyNameForm::Fr,
+        DutyNameForm::Zh,
+        DutyNameForm::ZhPinyin,
+        DutyNameForm::ZhInitials,
+    ] {
+        let form = serde_json::to_value(form).unwrap();
+        assert!(
+            api_v2_doc.contains(&format!("| `{}` |", form.as_str().unwrap())),
+            "docs/api-v2.md is missing duty name form {form}"
+        );
+    }
+    for kind in lookups::LOOKUP_KINDS {
+        assert!(
+            api_v2_doc.contains(&format!("| `{kind}` |")),
+            "docs/api-v2.md is missing lookup kind {kind}"
+        );
+    }
+
+    let healer = lookups::roles()
+        .into_iter()
+        .filter(|role| role.id == id_inventory::ROLE_ID_HEALER)
+        .collect::<Vec<_>>();
+    let lookup_json = serde_json::to_string_pretty(&lookups::lookup_collection(healer)).unwrap();
+    assert!(
+        normalized_api_v2_doc.contains(&strip_whitespace(&lookup_json)),
+        "docs/api-v2.md lookup example drifted from contract"
+    );
 
     assert!(
         normalized_api_v2_doc.contains(&strip_whitespace(&collection_json)),
@@ -274,6 +382,14 @@ fn docs_include_minimal_contract_examples() {
         normalized_api_v2_doc.contains(&strip_whitespace(&detail_json)),
         "docs/api-v2.md detail example drifted from contract"
     );
+    assert!(
+        normalized_api_v2_doc.contains(&strip_whitespace(&history_json)),
+        "docs/api-v2.md history example drifted from contract"
+    );
+    assert!(
+        normalized_api_v2_doc.contains(&strip_whitespace(&statistics_json)),
+        "docs/api-v2.md statistics example drifted from contract"
+    );
 }
 
 #[test]
@@ -459,6 +575,92 @@ async fn malformed_filters_return_400() {
                 "region must be a comma-separated list of names",
             ),
         ),
+        (
+            "/api/v2/listings?min_item_level_gte=-1",
+            ErrorEnvelope::invalid_query(
+                "min_item_level_gte",
+                "min_item_level_gte must be an integer between 0 and 65535",
+            ),
+        ),
+        (
+            "/api/v2/listings?min_item_level_lte=65536",
+            ErrorEnvelope::invalid_query(
+                "min_item_level_lte",
+                "min_item_level_lte must be an integer between 0 and 65535",
+            ),
+        ),
+        (
+            "/api/v2/listings?duty_type_id=normal",
+            ErrorEnvelope::invalid_query("duty_type_id", "duty_type_id must be an unsigned integer"),
+        ),
+        (
+            "/api/v2/listings?objective_ids=1,",
+            ErrorEnvelope::invalid_query(
+                "objective_ids",
+                "objective_ids must be a comma-separated list of unsigned integers",
+            ),
+        ),
+        (
+            "/api/v2/listings?condition_ids=",
+            ErrorEnvelope::invalid_query(
+                "condition_ids",
+                "condition_ids must be a comma-separated list of unsigned integers",
+            ),
+        ),
+        (
+            "/api/v2/listings?loot_rule_id=1.5",
+            ErrorEnvelope::invalid_query("loot_rule_id", "loot_rule_id must be an unsigned integer"),
+        ),
+        (
+            "/api/v2/listings?beginners_welcome=1",
+            ErrorEnvelope::invalid_query("beginners_welcome", "beginners_welcome must be true or false"),
+        ),
+        (
+            "/api/v2/listings?one_player_per_job=yes",
+            ErrorEnvelope::invalid_query("one_player_per_job", "one_player_per_job must be true or false"),
+        ),
+        (
+            "/api/v2/listings?is_cross_world=",
+            ErrorEnvelope::invalid_query("is_cross_world", "is_cross_world must be true or false"),
+        ),
+        (
+            "/api/v2/listings?slots_open_gte=-2",
+            ErrorEnvelope::invalid_query("slots_open_gte", "slots_open_gte must be an unsigned integer"),
+        ),
+        (
+            "/api/v2/listings?open_role_ids=healer",
+            ErrorEnvelope::invalid_query(
+                "open_role_ids",
+                "open_role_ids must be a comma-separated list of unsigned integers",
+            ),
+        ),
+        (
+            "/api/v2/listings?open_job_ids=24,,28",
+            ErrorEnvelope::invalid_query(
+                "open_job_ids",
+                "open_job_ids must be a comma-separated list of unsigned integers",
+            ),
+        ),
+        (
+            "/api/v2/listings?sort=",
+            ErrorEnvelope::invalid_query("sort", "sort must be a comma-separated list of distinct fields from updated_at, time_left_seconds, min_item_level, slots_filled, slots_available, category_id, each optionally prefixed with -"),
+        ),
+        (
+            "/api/v2/listings?sort=id",
+            ErrorEnvelope::invalid_query("sort", "sort must be a comma-separated list of distinct fields from updated_at, time_left_seconds, min_item_level, slots_filled, slots_available, category_id, each optionally prefixed with -"),
+        ),
+        (
+            "/api/v2/listings?sort=-updated_at,",
+            ErrorEnvelope::invalid_query("sort", "sort must be a comma-separated list of distinct fields from updated_at, time_left_seconds, min_item_level, slots_filled, slots_available, category_id, each optionally prefixed with -"),
+        ),
+        (
+            "/api/v2/listings?sort=updated_at,-updated_at",
+            ErrorEnvelope::invalid_query("sort", "sort must be a comma-separated list of distinct fields from updated_at, time_left_seconds, min_item_level, slots_filled, slots_available, category_id, each optionally prefixed with -"),
+        ),
+        (
+            "/api/v2/listings?sort=--min_item_level",
+            ErrorEnvelope::invalid_query("sort", "sort must be a comma-separated list of distinct fields from updated_at, time_left_seconds, min_item_level, slots_filled, slots_available, category_id, each optionally prefixed with -"),
+        ),
     ];
 
     for (path, expected_error) in cases {
@@ -513,34 +715,778 @@ async fn legacy_label_filters_are_rejected() {
 }
 
 #[tokio::test]
-async fn lookup_routes_are_absent_from_the_full_router() {
-    let lookup_paths = [
-        "/api/v2/lookups",
-        "/api/v2/lookups/",
-        "/api/v2/lookups/worlds",
-        "/api/v2/lookups/worlds/1167",
-        "/api/v2/lookups/duties",
-        "/api/v2/lookups/duties/55",
-        "/api/v2/lookups/jobs",
-        "/api/v2/lookups/categories",
-    ];
+async fn lookup_routes_are_served_by_the_full_router() {
     let router = crate::web::router(crate::web::state_for_router_tests().await);
 
-    for path in lookup_paths {
+    for kind in lookups::LOOKUP_KINDS {
+        let path = format!("/api/v2/lookups/{kind}");
         let response = warp::test::request()
             .method("GET")
-            .path(path)
+            .path(&path)
             .reply(&router)
             .await;
 
+        assert_eq!(response.status(), StatusCode::OK, "path: {path}");
+        let body = serde_json::from_slice::<serde_json::Value>(response.body()).unwrap();
+        let total = body["data"].as_array().unwrap().len();
+        assert!(total > 0, "path: {path}");
         assert_eq!(
-            response.status(),
-            StatusCode::METHOD_NOT_ALLOWED,
-            "path {path} should be rejected consistently by the full router"
+            body["pagination"],
+            json!({ "total": total, "page": 1, "per_page": total, "total_pages": 1 }),
+            "path: {path}",
         );
     }
 }
 
+#[tokio::test]
+async fn duty_search_ranks_pinyin_initials_through_the_full_router() {
+    let router = crate::web::router(crate::web::state_for_router_tests().await);
+
+    let (status, body) = get_json(&router, "/api/v2/lookups/duties/search?q=hxls&limit=3").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"][0]["duty_id"], 788);
+    assert_eq!(body["data"][0]["matched_on"], "zh_initials");
+    assert_eq!(body["data"][0]["name"]["zh"], "幻想龙诗绝境战");
+    assert!(body["data"].as_array().unwrap().len() <= 3);
+
+    let (status, body) = get_json(&router, "/api/v2/lookups/duties/search?q=%20").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["details"]["field"], "q");
+}
+
+#[tokio::test]
+async fn unknown_lookup_returns_404_error_envelope() {
+    let response = warp::test::request()
+        .method("GET")
+        .path("/api/v2/lookups/servers")
+        .reply(&lookups::routes())
+        .await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = serde_json::from_slice::<serde_json::Value>(response.body()).unwrap();
+    assert_eq!(body["error"]["code"], "not_found");
+    assert_eq!(body["error"]["details"]["lookup"], "servers");
+    assert_eq!(
+        body["error"]["details"]["supported"],
+        json!(lookups::LOOKUP_KINDS),
+    );
+}
+
+#[test]
+fn lookup_tables_match_id_inventory() {
+    let world_ids = lookups::worlds().iter().map(|world| world.id).collect::<Vec<_>>();
+    assert_eq!(world_ids, id_inventory::world_ids());
+
+    let world = lookups::worlds().into_iter().find(|world| world.id == 73).unwrap();
+    assert_eq!(world.datacenter, "Aether");
+    assert_eq!(world.region, "North-America");
+
+    let mut datacenter_world_ids = lookups::datacenters()
+        .into_iter()
+        .flat_map(|datacenter| datacenter.world_ids)
+        .collect::<Vec<_>>();
+    datacenter_world_ids.sort_unstable();
+    assert_eq!(datacenter_world_ids, id_inventory::world_ids());
+
+    let mut region_datacenters = lookups::regions()
+        .into_iter()
+        .flat_map(|region| region.datacenters)
+        .collect::<Vec<_>>();
+    region_datacenters.sort_unstable();
+    assert_eq!(
+        region_datacenters,
+        lookups::datacenters()
+            .into_iter()
+            .map(|datacenter| datacenter.name)
+            .collect::<Vec<_>>()
+    );
+
+    let label_ids = |labels: Vec<crate::web::v2::contracts::LabelLookup>| {
+        labels.into_iter().map(|label| label.id).collect::<Vec<_>>()
+    };
+    assert_eq!(label_ids(lookups::categories()), id_inventory::CATEGORY_IDS);
+    assert_eq!(label_ids(lookups::duty_types()), id_inventory::DUTY_TYPE_IDS);
+    assert_eq!(label_ids(lookups::roles()), id_inventory::ROLE_IDS);
+    assert_eq!(label_ids(lookups::objectives()), id_inventory::OBJECTIVE_IDS);
+    assert_eq!(label_ids(lookups::conditions()), id_inventory::CONDITION_IDS);
+    assert_eq!(label_ids(lookups::loot_rules()), id_inventory::LOOT_RULE_IDS);
+
+    let jobs = lookups::jobs();
+    assert_eq!(jobs.len(), crate::ffxiv::JOBS.len());
+    assert_eq!(
+        jobs.iter()
+            .filter(|job| job.accepted_in_slots)
+            .map(|job| job.id)
+            .collect::<Vec<_>>(),
+        id_inventory::job_ids()
+    );
+    for job in &jobs {
+        assert_eq!(job.role_id, id_inventory::role_id_for_job_id(job.id), "job {}", job.id);
+    }
+    let beastmaster = jobs.iter().find(|job| job.id == 43).unwrap();
+    assert_eq!(beastmaster.code, "BST");
+    assert!(beastmaster.accepted_in_slots);
+
+    let duties = lookups::duties();
+    assert_eq!(
+        duties.len(),
+        crate::ffxiv::DUTIES.len()
+            + crate::ffxiv::ROULETTES.len()
+            + crate::ffxiv::TREASURE_MAPS.len()
+            + crate::ffxiv::TERRITORY_NAMES.len()
+    );
+    for duty in &duties {
+        assert!(id_inventory::DUTY_TYPE_IDS.contains(&duty.duty_type_id));
+        assert!(duty
+            .category_id
+            .is_none_or(|category_id| id_inventory::CATEGORY_IDS.contains(&category_id)));
+    }
+    let duty = duties
+        .iter()
+        .find(|duty| duty.duty_type_id == id_inventory::duty_type_id(DutyType::Normal) && duty.duty_id == 55)
+        .unwrap();
+    assert_eq!(duty.name.en, crate::ffxiv::DUTIES[&55].name.en);
+    assert_eq!(duty.name.zh, crate::ffxiv::DUTIES[&55].name.zh);
+}
+
+#[test]
+fn lookup_entries_carry_every_language() {
+    let category = lookups::categories()
+        .into_iter()
+        .find(|category| category.id == id_inventory::category_id(DutyCategory::HighEndDuty))
+        .unwrap();
+    let expected = DutyCategory::HighEndDuty.pf_category().name();
+
+    assert_eq!(
+        serde_json::to_value(&category.name).unwrap(),
+        json!({
+            "en": expected.en,
+            "ja": expected.ja,
+            "de": expected.de,
+            "fr": expected.fr,
+            "zh": expected.zh,
+        })
+    );
+
+    for label in lookups::roles()
+        .into_iter()
+        .chain(lookups::duty_types())
+        .chain(lookups::objectives())
+        .chain(lookups::conditions())
+        .chain(lookups::loot_rules())
+    {
+        for text in [&label.name.en, &label.name.ja, &label.name.de, &label.name.fr, &label.name.zh] {
+            assert!(!text.is_empty(), "label {} has an empty translation", label.id);
+        }
+    }
+}
+
+async fn next_stream_chunk(body: &mut warp::hyper::Body) -> String {
+    use warp::hyper::body::HttpBody;
+
+    let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), body.data())
+        .await
+        .expect("stream should yield an event")
+        .expect("stream should stay open")
+        .unwrap();
+    String::from_utf8(chunk.to_vec()).unwrap()
+}
+
+fn stream_listing(json: &str) -> crate::listing::PartyFinderListing {
+    serde_json::from_str(json).unwrap()
+}
+
+#[tokio::test]
+async fn listing_stream_pushes_matching_created_and_updated_events() {
+    let (events, route) = stream::stream_route_for_tests();
+    let response = warp::test::request()
+        .method("GET")
+        .path("/api/v2/listings/stream?category_id=64&created_world_id=73")
+        .filter(&route)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut body = response.into_body();
+
+    let mut other_category = stream_listing(CROSS_WORLD_FIXTURE_JSON);
+    other_category.category = DutyCategory::Raid;
+    events.publish_upsert(other_category, UpsertOutcome::Inserted, Utc::now());
+    events.publish_upsert(stream_listing(ACTIVE_FIXTURE_JSON), UpsertOutcome::Inserted, Utc::now());
+    events.publish_upsert(stream_listing(ACTIVE_FIXTURE_JSON), UpsertOutcome::Updated, Utc::now());
+
+    let created = next_stream_chunk(&mut body).await;
+    assert!(created.contains("event:created\n"), "{created}");
+    assert!(created.contains(&format!("id:{WIDE_LISTING_ID_STR}\n")), "{created}");
+    assert!(!created.contains(WIDE_CROSS_WORLD_LISTING_ID_STR), "{created}");
+
+    let data = created
+        .lines()
+        .find_map(|line| line.strip_prefix("data:"))
+        .unwrap();
+    let summary = serde_json::from_str::<ListingSummary>(data).unwrap();
+    assert_eq!(summary.id, WIDE_LISTING_ID_STR);
+    assert_eq!(summary.category_id, 64);
+    assert_eq!(summary.time_left_seconds, 1200);
+
+    let updated = next_stream_chunk(&mut body).await;
+    assert!(updated.contains("event:updated\n"), "{updated}");
+}
+
+#[tokio::test]
+async fn listing_stream_updates_keep_the_first_created_at() {
+    let events = stream::ListingEvents::new();
+    let mut receiver = events.subscribe();
+
+    let uploaded_at = Utc::now() - Duration::minutes(2);
+    events.publish_upsert(stream_listing(ACTIVE_FIXTURE_JSON), UpsertOutcome::Inserted, uploaded_at);
+    events.publish_upsert(stream_listing(ACTIVE_FIXTURE_JSON), UpsertOutcome::Updated, Utc::now());
+
+    let created = receiver.recv().await.unwrap();
+    let updated = receiver.recv().await.unwrap();
+    assert_eq!(updated.kind, stream::ListingEventKind::Updated);
+    assert_eq!(updated.listing.created_at, created.listing.created_at);
+    assert!(updated.listing.updated_at > uploaded_at);
+}
+
+#[test]
+fn listing_events_count_active_public_listings_by_data_centre() {
+    let events = stream::ListingEvents::new();
+    let uploaded_at = Utc::now();
+    let listing = stream_listing(ACTIVE_FIXTURE_JSON);
+    let data_centre = listing.data_centre_name().unwrap_or("unknown");
+    let mut private = stream_listing(CROSS_WORLD_FIXTURE_JSON);
+    private.search_area = crate::listing::SearchAreaFlags::PRIVATE;
+    events.publish_upsert(listing, UpsertOutcome::Inserted, uploaded_at);
+    events.publish_upsert(private, UpsertOutcome::Inserted, uploaded_at);
+
+    let counts = events.active_by_data_centre(uploaded_at);
+    assert_eq!(counts.into_iter().collect::<Vec<_>>(), [(data_centre, 1)]);
+    assert!(events.active_by_data_centre(uploaded_at + Duration::minutes(5)).is_empty());
+}
+
+#[tokio::test]
+async fn listing_stream_emits_expired_once_active_window_ends() {
+    let (events, route) = stream::stream_route_for_tests();
+    let response = warp::test::request()
+        .method("GET")
+        .path("/api/v2/listings/stream")
+        .filter(&route)
+        .await
+        .unwrap();
+    let mut body = response.into_body();
+
+    let uploaded_at = Utc::now();
+    events.publish_upsert(stream_listing(ACTIVE_FIXTURE_JSON), UpsertOutcome::Inserted, uploaded_at);
+    assert!(next_stream_chunk(&mut body).await.contains("event:created\n"));
+
+    assert_eq!(events.expire_stale(uploaded_at + Duration::minutes(4)), 0);
+    assert_eq!(events.expire_stale(uploaded_at + Duration::minutes(5)), 1);
+    assert_eq!(events.expire_stale(uploaded_at + Duration::minutes(6)), 0);
+
+    let expired = next_stream_chunk(&mut body).await;
+    assert!(expired.contains("event:expired\n"), "{expired}");
+    assert!(expired.contains("\"time_left_seconds\":0"), "{expired}");
+}
+
+#[tokio::test]
+async fn listing_stream_hides_private_listings() {
+    let (events, route) = stream::stream_route_for_tests();
+    let response = warp::test::request()
+        .method("GET")
+        .path("/api/v2/listings/stream")
+        .filter(&route)
+        .await
+        .unwrap();
+    let mut body = response.into_body();
+
+    let mut private = stream_listing(CROSS_WORLD_FIXTURE_JSON);
+    private.search_area = crate::listing::SearchAreaFlags::PRIVATE;
+    events.publish_upsert(private, UpsertOutcome::Inserted, Utc::now());
+    events.publish_upsert(stream_listing(ACTIVE_FIXTURE_JSON), UpsertOutcome::Inserted, Utc::now());
+
+    let created = next_stream_chunk(&mut body).await;
+    assert!(created.contains(&format!("id:{WIDE_LISTING_ID_STR}\n")), "{created}");
+}
+
+#[tokio::test]
+async fn listing_stream_rejects_malformed_filters() {
+    let router = crate::web::router(crate::web::state_for_router_tests().await);
+    let response = warp::test::request()
+        .method("GET")
+        .path("/api/v2/listings/stream?category_id=abc")
+        .reply(&router)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(response.body()).unwrap(),
+        serde_json::to_value(ErrorEnvelope::invalid_query(
+            "category_id",
+            "category_id must be an unsigned integer",
+        ))
+        .unwrap(),
+    );
+}
+
+fn contributable_listing(id: u64, content_id_lower: u32) -> crate::listing::PartyFinderListing {
+    // uploads must come from a real world id; the fixtures use 73 for the read-path tests
+    let mut listing = stream_listing(ACTIVE_FIXTURE_JSON);
+    listing.id = id;
+    listing.content_id_lower = content_id_lower;
+    listing.created_world = 1042;
+    listing.home_world = 1042;
+    listing.current_world = 1042;
+    listing
+}
+
+async fn get_json(
+    router: &warp::filters::BoxedFilter<(impl warp::Reply + 'static,)>,
+    path: &str,
+) -> (StatusCode, serde_json::Value) {
+    let response = warp::test::request().method("GET").path(path).reply(router).await;
+    let body = serde_json::from_slice(response.body()).expect("v2 routes always return JSON");
+    (response.status(), body)
+}
+
+fn data_ids(body: &serde_json::Value) -> Vec<&str> {
+    body["data"]
+        .as_array()
+        .expect("collection data must be an array")
+        .iter()
+        .map(|listing| listing["id"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn contributed_listings_round_trip_through_v2_routes() {
+    let router = crate::web::router(crate::web::state_for_router_tests().await);
+
+    let first = contributable_listing(WIDE_LISTING_ID, 456);
+    let mut second = contributable_listing(WIDE_LISTING_ID + 1, 457);
+    second.created_world = 1043;
+    second.home_world = 1043;
+    let mut private = contributable_listing(WIDE_LISTING_ID + 2, 458);
+    private.search_area |= crate::listing::SearchAreaFlags::PRIVATE;
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/contribute/multiple")
+        .json(&vec![&first, &second, &private])
+        .reply(&router)
+        .await;
+    let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(body["inserted"], 3);
+    assert_eq!(body["results"][0]["id"], WIDE_LISTING_ID_STR);
+    assert_eq!(body["results"][0]["outcome"], "inserted");
+
+    let (status, body) = get_json(&router, "/api/v2/listings").await;
+    assert_eq!(status, StatusCode::OK);
+    let mut ids = data_ids(&body);
+    ids.sort_unstable();
+    assert_eq!(ids, vec![WIDE_LISTING_ID_STR, WIDE_CROSS_WORLD_LISTING_ID_STR]);
+    assert_eq!(body["pagination"]["total"], 2);
+
+    let (_, body) = get_json(&router, "/api/v2/listings?created_world_id=1043").await;
+    assert_eq!(data_ids(&body), vec![WIDE_CROSS_WORLD_LISTING_ID_STR]);
+
+    let (_, body) = get_json(&router, "/api/v2/listings?datacenter=%E9%99%86%E8%A1%8C%E9%B8%9F").await;
+    assert_eq!(data_ids(&body), vec![WIDE_LISTING_ID_STR]);
+
+    let mut refreshed = contributable_listing(WIDE_LISTING_ID, 456);
+    refreshed.min_item_level = 600;
+    for expected in ["updated", "unchanged"] {
+        let response = warp::test::request()
+            .method("POST")
+            .path("/contribute")
+            .json(&refreshed)
+            .reply(&router)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["results"][0]["outcome"], expected);
+    }
+
+    let (status, body) = get_json(&router, &format!("/api/v2/listings/{WIDE_LISTING_ID}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["id"], WIDE_LISTING_ID_STR);
+    assert_eq!(body["data"]["created_world_id"], 1042);
+    assert_eq!(body["data"]["min_item_level"], 600);
+    let time_left = body["data"]["time_left_seconds"].as_u64().unwrap();
+    assert!(time_left > 1190 && time_left <= 1200, "time_left_seconds was {time_left}");
+
+    let (status, body) = get_json(&router, &format!("/api/v2/listings/{}", WIDE_LISTING_ID + 2)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "not_found");
+}
+
+#[tokio::test]
+async fn party_fit_returns_listings_that_seat_everyone_with_the_assignment() {
+    let router = crate::web::router(crate::web::state_for_router_tests().await);
+
+    let mut fits = contributable_listing(WIDE_LISTING_ID, 456);
+    fits.slots = vec![
+        PartyFinderSlot { accepting: JobFlags::PALADIN | JobFlags::WHITE_MAGE },
+        PartyFinderSlot { accepting: JobFlags::PALADIN },
+    ];
+    fits.slots_available = 2;
+    fits.jobs_present = vec![0, 0];
+    // the healer seat is already taken
+    let mut full = contributable_listing(WIDE_LISTING_ID + 1, 457);
+    full.slots = vec![
+        PartyFinderSlot { accepting: JobFlags::WHITE_MAGE },
+        PartyFinderSlot { accepting: JobFlags::PALADIN },
+    ];
+    full.slots_available = 2;
+    full.jobs_present = vec![24, 0];
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/contribute/multiple")
+        .json(&vec![&fits, &full])
+        .reply(&router)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (status, body) = get_json(&router, "/api/v2/listings/fit?party_job_ids=19,24").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(data_ids(&body), vec![WIDE_LISTING_ID_STR]);
+    assert_eq!(
+        body["data"][0]["assignments"],
+        json!([
+            { "job_id": 19, "slot_index": 1 },
+            { "job_id": 24, "slot_index": 0 },
+        ])
+    );
+    assert_eq!(body["pagination"]["total"], 1);
+
+    let (_, body) = get_json(&router, "/api/v2/listings/fit?party_job_ids=19").await;
+    assert_eq!(body["pagination"]["total"], 2);
+
+    let (_, body) = get_json(&router, "/api/v2/listings/fit?party_job_ids=19&created_world_id=73").await;
+    assert_eq!(body["pagination"]["total"], 0);
+
+    let (status, body) = get_json(&router, "/api/v2/listings/fit?party_job_ids=19,999999").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["pagination"]["total"], 0);
+
+    for (path, field, message) in [
+        ("/api/v2/listings/fit", "party_job_ids", "party_job_ids is required"),
+        (
+            "/api/v2/listings/fit?party_job_ids=PLD",
+            "party_job_ids",
+            "party_job_ids must be a comma-separated list of unsigned integers",
+        ),
+        (
+            "/api/v2/listings/fit?party_job_ids=19&jobs=PLD",
+            "jobs",
+            "jobs is not supported in v2; use job_ids",
+        ),
+    ] {
+        let (status, body) = get_json(&router, path).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "path: {path}");
+        assert_eq!(
+            body,
+            serde_json::to_value(ErrorEnvelope::invalid_query(field, message)).unwrap(),
+            "path: {path}",
+        );
+    }
+}
+
+async fn post_json(
+    router: &warp::filters::BoxedFilter<(impl warp::Reply + 'static,)>,
+    path: &str,
+    body: &serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let response = warp::test::request().method("POST").path(path).json(body).reply(router).await;
+    let body = serde_json::from_slice(response.body()).expect("v2 routes always return JSON");
+    (response.status(), body)
+}
+
+#[tokio::test]
+async fn eligibility_explains_every_excluded_listing() {
+    let router = crate::web::router(crate::web::state_for_router_tests().await);
+
+    let tank_or_healer = PartyFinderSlot { accepting: JobFlags::PALADIN | JobFlags::WHITE_MAGE };
+    let listing = |offset: u64| {
+        let mut listing = contributable_listing(WIDE_LISTING_ID + offset, 456 + offset as u32);
+        listing.slots = vec![tank_or_healer.clone(), tank_or_healer.clone()];
+        listing.slots_available = 2;
+        listing.jobs_present = vec![0, 0];
+        listing.min_item_level = 660;
+        listing
+    };
+    let open = listing(0);
+    let mut other_data_center = listing(1);
+    other_data_center.created_world = 1043;
+    let mut clear_required = listing(2);
+    clear_required.conditions = ConditionFlags::DUTY_COMPLETE;
+    let mut white_mage_present = listing(3);
+    white_mage_present.search_area |= SearchAreaFlags::ONE_PLAYER_PER_JOB;
+    white_mage_present.jobs_present = vec![24, 0];
+    white_mage_present.min_item_level = 600;
+    let mut world_local = listing(4);
+    world_local.search_area = SearchAreaFlags::empty();
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/contribute/multiple")
+        .json(&vec![&open, &other_data_center, &clear_required, &white_mage_present, &world_local])
+        .reply(&router)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // a White Mage at 700 and a Paladin at 650 from 幻影群岛, which shares 陆行鸟 with 1042
+    let profile = json!({
+        "home_world_id": 1044,
+        "jobs": [
+            { "job_id": 24, "item_level": 700 },
+            { "job_id": 19, "item_level": 650 },
+        ],
+        "duty_cleared": false,
+    });
+    let (status, body) = post_json(&router, "/api/v2/listings/eligible", &profile).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let id = |offset: u64| (WIDE_LISTING_ID + offset).to_string();
+    assert_eq!(data_ids(&body), [id(0), id(3), id(1), id(2), id(4)]);
+    assert_eq!(body["pagination"]["total"], 5);
+
+    let verdicts = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|listing| {
+            let codes = listing["exclusions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|exclusion| (exclusion["code"].as_str().unwrap(), exclusion["job_id"].as_u64()))
+                .collect::<Vec<_>>();
+            (listing["eligible"].as_bool().unwrap(), listing["eligible_job_ids"].clone(), codes)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        verdicts,
+        [
+            (true, json!([24]), vec![("item_level_too_low", Some(19))]),
+            (true, json!([19]), vec![("job_already_in_party", Some(24))]),
+            (
+                false,
+                json!([24]),
+                vec![("other_data_center", None), ("item_level_too_low", Some(19))],
+            ),
+            (
+                false,
+                json!([24]),
+                vec![("duty_clear_required", None), ("item_level_too_low", Some(19))],
+            ),
+            (
+                false,
+                json!([24]),
+                vec![("other_world", None), ("item_level_too_low", Some(19))],
+            ),
+        ]
+    );
+    assert_eq!(
+        body["data"][0]["exclusions"][0]["message"],
+        "the job's item level is below the listing's minimum"
+    );
+
+    let (_, body) = post_json(&router, "/api/v2/listings/eligible?created_world_id=1043", &profile).await;
+    assert_eq!(data_ids(&body), [id(1)]);
+
+    let invalid_profiles = [
+        (json!([]), "body"),
+        (json!({ "home_world_id": 999999, "jobs": [{ "job_id": 24, "item_level": 700 }] }), "home_world_id"),
+        (json!({ "home_world_id": 1044, "jobs": [] }), "jobs"),
+        (json!({ "home_world_id": 1044, "jobs": [{ "job_id": 999, "item_level": 700 }] }), "jobs[0].job_id"),
+        (
+            json!({
+                "home_world_id": 1044,
+                "jobs": [{ "job_id": 24, "item_level": 700 }, { "job_id": 24, "item_level": 690 }],
+            }),
+            "jobs[1].job_id",
+        ),
+        (json!({ "home_world_id": 1044, "jobs": [], "world": "Gilgamesh" }), "body"),
+    ];
+    for (profile, field) in invalid_profiles {
+        let (status, body) = post_json(&router, "/api/v2/listings/eligible", &profile).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "profile: {profile}");
+        assert_eq!(body["error"]["code"], "invalid_body", "profile: {profile}");
+        assert_eq!(body["error"]["details"]["field"], field, "profile: {profile}");
+    }
+}
+
+#[tokio::test]
+async fn listing_history_pages_by_cursor_through_the_router() {
+    let router = crate::web::router(crate::web::state_for_router_tests().await);
+
+    let listings: Vec<_> = (0..3)
+        .map(|offset| contributable_listing(WIDE_LISTING_ID + offset, 456 + offset as u32))
+        .collect();
+    for listing in &listings {
+        let response = warp::test::request()
+            .method("POST")
+            .path("/contribute")
+            .json(listing)
+            .reply(&router)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let now = Utc::now();
+    let range = format!(
+        "from={}&to={}",
+        (now - Duration::hours(1)).format("%Y-%m-%dT%H:%M:%SZ"),
+        (now + Duration::hours(1)).format("%Y-%m-%dT%H:%M:%SZ"),
+    );
+
+    let mut seen = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let path = match &cursor {
+            Some(cursor) => format!("/api/v2/history/listings?{range}&per_page=2&cursor={cursor}"),
+            None => format!("/api/v2/history/listings?{range}&per_page=2"),
+        };
+        let (status, body) = get_json(&router, &path).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["pagination"]["per_page"], 2);
+        assert!(body["data"][0]["created_at"].is_string());
+        seen.extend(data_ids(&body).into_iter().map(str::to_owned));
+
+        match body["pagination"]["next_cursor"].as_str() {
+            Some(next) => cursor = Some(next.to_owned()),
+            None => break,
+        }
+    }
+    let expected: Vec<_> = listings.iter().rev().map(|listing| listing.id.to_string()).collect();
+    assert_eq!(seen, expected, "newest updated_at first, every row exactly once");
+
+    let (_, body) = get_json(
+        &router,
+        &format!("/api/v2/history/listings?{range}&created_world_id=1043"),
+    )
+    .await;
+    assert!(data_ids(&body).is_empty());
+
+    let (status, body) = get_json(&router, "/api/v2/history/listings?per_page=2").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_query");
+    assert_eq!(body["error"]["details"]["field"], "from");
+}
+
+#[tokio::test]
+async fn subscriptions_deliver_signed_matching_listings_to_a_local_stand_in() {
+    use warp::{hyper::body::Bytes, Filter};
+
+    // a local webhook receiver that fails the first attempt
+    let (sender, mut received) = tokio::sync::mpsc::unbounded_channel();
+    let attempts = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+    let receiver = warp::post()
+        .and(warp::header::<String>(crate::webhooks::SIGNATURE_HEADER))
+        .and(warp::body::bytes())
+        .map(move |signature: String, body: Bytes| {
+            sender.send((signature, body)).unwrap();
+            match attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                0 => StatusCode::SERVICE_UNAVAILABLE,
+                _ => StatusCode::NO_CONTENT,
+            }
+        });
+    let (addr, server) = warp::serve(receiver).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::task::spawn(server);
+
+    let state = crate::web::state_for_router_tests_with_webhooks(
+        crate::config::Admin {
+            token: Some("admin-token".into()),
+        },
+        crate::config::Webhooks {
+            max_attempts: 3,
+            retry_base_ms: 1,
+            retry_max_ms: 4,
+            ..Default::default()
+        },
+    )
+    .await;
+    let router = crate::web::router(state);
+    let request = |method: &str, path: &str| {
+        warp::test::request()
+            .method(method)
+            .path(path)
+            .header("authorization", "Bearer admin-token")
+    };
+
+    let (status, body) = post_json(&router, "/api/v2/subscriptions", &json!({})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"]["code"], "unauthorized");
+
+    let secret = "0123456789abcdef";
+    let response = request("POST", "/api/v2/subscriptions")
+        .json(&json!({
+            "url": format!("http://{addr}/hook"),
+            "secret": secret,
+            "filters": { "created_world_id": 1043 },
+        }))
+        .reply(&router)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    let id = body["data"]["id"].as_str().unwrap().to_owned();
+    assert_eq!(body["data"]["filters"]["created_world_id"], "1043");
+    assert_eq!(body["data"]["enabled"], true);
+    assert!(body["data"].get("secret").is_none());
+
+    let mut other_world = contributable_listing(WIDE_LISTING_ID, 456);
+    other_world.created_world = 1042;
+    let mut matching = contributable_listing(WIDE_LISTING_ID + 1, 457);
+    matching.created_world = 1043;
+    matching.home_world = 1043;
+    let response = warp::test::request()
+        .method("POST")
+        .path("/contribute/multiple")
+        .json(&vec![&other_world, &matching])
+        .reply(&router)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let timeout = std::time::Duration::from_secs(5);
+    let (_, first) = tokio::time::timeout(timeout, received.recv()).await.unwrap().unwrap();
+    let (signature, retried) = tokio::time::timeout(timeout, received.recv()).await.unwrap().unwrap();
+    assert_eq!(first, retried);
+    assert_eq!(signature, crate::webhooks::signature(secret, &retried));
+    let payload: crate::web::v2::contracts::WebhookPayload = serde_json::from_slice(&retried).unwrap();
+    assert_eq!(payload.event, "listing.created");
+    assert_eq!(payload.subscription_id, id);
+    assert_eq!(payload.listing.id, WIDE_CROSS_WORLD_LISTING_ID_STR);
+
+    // the delivery is logged once the retry has been answered
+    let deliveries_path = format!("/api/v2/subscriptions/{id}/deliveries");
+    let mut deliveries = serde_json::Value::Null;
+    for _ in 0..100 {
+        let response = request("GET", &deliveries_path).reply(&router).await;
+        deliveries = serde_json::from_slice(response.body()).unwrap();
+        if deliveries["pagination"]["total"] == 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(deliveries["data"][0]["id"], payload.delivery_id);
+    assert_eq!(deliveries["data"][0]["succeeded"], true);
+    assert_eq!(deliveries["data"][0]["attempts"][0]["status_code"], 503);
+    assert_eq!(deliveries["data"][0]["attempts"][1]["status_code"], 204);
+    assert!(received.try_recv().is_err());
+
+    let response = request("PATCH", &format!("/api/v2/subscriptions/{id}"))
+        .json(&json!({ "enabled": false }))
+        .reply(&router)
+        .await;
+    let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(body["data"]["enabled"], false);
+
+    let response = request("DELETE", &format!("/api/v2/subscriptions/{id}")).reply(&router).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = request("GET", &format!("/api/v2/subscriptions/{id}")).reply(&router).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
 fn sample_summary() -> ListingSummary {
     ListingSummary {
         id: "900001".into(),
@@ -1039,6 +1985,209 @@ let documents = [&created_world_match, &home_world_match, &category_and_duty_mat
     assert_eq!(duty_response.data[0].id, "67890");
 }
 
+fn listing_attribute_fixtures() -> Vec<QueriedListing> {
+    let updated_at = Utc::now() - Duration::minutes(1);
+    let listing = |id: u64, min_item_level: u16| {
+        let mut document = queried_fixture(ACTIVE_FIXTURE_JSON, updated_at, 1200.0);
+        document.listing.id = id;
+        document.listing.min_item_level = min_item_level;
+        document
+    };
+
+    // objective completion+practice, no conditions, no loot rule, cross-world, 7 open slots
+    let plain = listing(1, 600);
+
+    let mut roulette = listing(2, 660);
+    roulette.listing.duty_type = DutyType::Roulette;
+    roulette.listing.objective = ObjectiveFlags::LOOT;
+    roulette.listing.conditions = ConditionFlags::DUTY_COMPLETE;
+    roulette.listing.loot_rules = LootRuleFlags::GREED_ONLY;
+    roulette.listing.beginners_welcome = true;
+    roulette.listing.search_area =
+        SearchAreaFlags::DATA_CENTRE | SearchAreaFlags::ONE_PLAYER_PER_JOB;
+
+    let mut local = listing(3, 700);
+    local.listing.duty_type = DutyType::Other;
+    local.listing.objective = ObjectiveFlags::PRACTICE;
+    local.listing.conditions =
+        ConditionFlags::DUTY_INCOMPLETE | ConditionFlags::DUTY_COMPLETE_WEEKLY_REWARD_UNCLAIMED;
+    local.listing.loot_rules = LootRuleFlags::GREED_ONLY | LootRuleFlags::LOOTMASTER;
+    local.listing.search_area = SearchAreaFlags::empty();
+    local.listing.slots_available = 2;
+    local.listing.category = DutyCategory::Raid;
+
+    vec![plain, roulette, local]
+}
+
+#[test]
+fn attribute_filters_match_in_memory_and_in_the_store_pushdown() {
+    let cases: &[(&str, &[&str])] = &[
+        ("min_item_level_gte=650", &["2", "3"]),
+        ("min_item_level_lte=660", &["1", "2"]),
+        ("min_item_level_gte=600&min_item_level_lte=660", &["1", "2"]),
+        ("duty_type_id=1", &["2"]),
+        ("duty_type_id=2", &["1"]),
+        ("objective_ids=2", &["1", "3"]),
+        ("objective_ids=4", &["2"]),
+        ("objective_ids=4,1", &["1", "2"]),
+        ("condition_ids=2", &["2"]),
+        ("condition_ids=8", &["3"]),
+        ("condition_ids=2,4", &["2", "3"]),
+        ("loot_rule_id=0", &["1"]),
+        ("loot_rule_id=3", &["3"]),
+        ("beginners_welcome=true", &["2"]),
+        ("beginners_welcome=false", &["1", "3"]),
+        ("one_player_per_job=true", &["2"]),
+        ("one_player_per_job=false", &["1", "3"]),
+        ("is_cross_world=true", &["1", "2"]),
+        ("is_cross_world=false", &["3"]),
+        ("slots_open_gte=1", &["1", "2", "3"]),
+        ("slots_open_gte=2", &["1", "2"]),
+        ("slots_open_gte=8", &[]),
+        ("is_cross_world=true&beginners_welcome=false&min_item_level_gte=500", &["1"]),
+        ("category_ids=32", &["3"]),
+        ("category_ids=64,32", &["1", "2", "3"]),
+        ("category_id=64&category_ids=32,64", &["1", "2"]),
+    ];
+    assert_in_memory_and_pushdown_match(&listing_attribute_fixtures(), cases);
+}
+
+/// Runs each query both through `matches_query` and through the store pushdown alone, which
+/// must agree with each other and with the expected listing ids.
+fn assert_in_memory_and_pushdown_match(documents: &[QueriedListing], cases: &[(&str, &[&str])]) {
+    for (query_string, expected) in cases {
+        let params = query_string
+            .split('&')
+            .map(|pair| pair.split_once('=').unwrap())
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let query = crate::web::v2::filters::parse_listings_query(&params).unwrap();
+
+        let in_memory = collection_response_from_documents(query.clone(), documents.iter())
+            .data
+            .into_iter()
+            .map(|summary| summary.id)
+            .collect::<Vec<_>>();
+        assert_eq!(in_memory, *expected, "query: {query_string}");
+
+        let filter = collection_filter(&query);
+        let pushed_down = documents
+            .iter()
+            .filter(|document| filter.matches(&document.listing))
+            .map(|document| document.listing.id.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(pushed_down, *expected, "pushdown for query: {query_string}");
+    }
+}
+
+#[test]
+fn open_slot_filters_skip_filled_slots() {
+    let updated_at = Utc::now() - Duration::minutes(1);
+    let listing = |id: u64, slots: &[JobFlags], jobs_present: &[u8]| {
+        let mut document = queried_fixture(ACTIVE_FIXTURE_JSON, updated_at, 1200.0);
+        document.listing.id = id;
+        document.listing.slots = slots
+            .iter()
+            .map(|accepting| PartyFinderSlot { accepting: *accepting })
+            .collect();
+        document.listing.slots_available = slots.len() as u8;
+        document.listing.jobs_present = jobs_present.to_vec();
+        document
+    };
+    let healer = JobFlags::WHITE_MAGE | JobFlags::SCHOLAR;
+    let tank = JobFlags::PALADIN | JobFlags::WARRIOR;
+
+    let documents = [
+        // the healer seat is taken by a white mage, only the tank seat is open
+        listing(1, &[healer, tank], &[24, 0]),
+        listing(2, &[tank, healer], &[19, 0]),
+        listing(3, &[JobFlags::all()], &[0]),
+        listing(4, &[healer], &[28]),
+    ];
+
+    // job_ids still counts filled slots
+    let any_slot = collection_response_from_documents(
+        ListingsQuery {
+            job_ids: vec![24],
+            ..Default::default()
+        },
+        documents.iter(),
+    );
+    assert_eq!(any_slot.pagination.total, 4);
+
+    assert_in_memory_and_pushdown_match(
+        &documents,
+        &[
+            ("open_role_ids=2", &["2", "3"]),
+            ("open_role_ids=1", &["1", "3"]),
+            ("open_role_ids=1,2", &["1", "2", "3"]),
+            ("open_role_ids=3", &["3"]),
+            ("open_job_ids=24", &["2", "3"]),
+            ("open_job_ids=19,28", &["1", "2", "3"]),
+            ("open_job_ids=21&open_role_ids=2", &["3"]),
+        ],
+    );
+}
+
+fn sort_fixtures() -> Vec<QueriedListing> {
+    let now = Utc::now();
+    let listing = |id: u64, minutes_ago: i64, time_left: f64, min_item_level: u16| {
+        let mut document = queried_fixture(ACTIVE_FIXTURE_JSON, now - Duration::minutes(minutes_ago), time_left);
+        document.listing.id = id;
+        document.listing.min_item_level = min_item_level;
+        document
+    };
+
+    vec![
+        listing(30, 3, 600.0, 700),
+        listing(20, 1, 1200.0, 710),
+        listing(10, 1, 900.0, 710),
+        listing(40, 2, 900.0, 690),
+    ]
+}
+
+fn sorted_ids(sort: &str, per_page: usize, page: usize) -> Vec<String> {
+    let mut params = std::collections::HashMap::new();
+    if !sort.is_empty() {
+        params.insert("sort".to_string(), sort.to_string());
+    }
+    params.insert("per_page".to_string(), per_page.to_string());
+    params.insert("page".to_string(), page.to_string());
+    let query = crate::web::v2::filters::parse_listings_query(&params).unwrap();
+
+    collection_response_from_documents(query, sort_fixtures().iter())
+        .data
+        .into_iter()
+        .map(|summary| summary.id)
+        .collect()
+}
+
+#[test]
+fn default_order_is_newest_first_then_listing_id() {
+    assert_eq!(sorted_ids("", 20, 1), ["10", "20", "40", "30"]);
+
+    // the input order does not leak into the response
+    let mut reversed = sort_fixtures();
+    reversed.reverse();
+    let response = collection_response_from_documents(ListingsQuery::default(), reversed.iter());
+    let ids = response.data.iter().map(|summary| summary.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, ["10", "20", "40", "30"]);
+
+    // pages split one stable order
+    assert_eq!(sorted_ids("", 2, 1), ["10", "20"]);
+    assert_eq!(sorted_ids("", 2, 2), ["40", "30"]);
+}
+
+#[test]
+fn sort_orders_by_each_field_with_id_tie_breaker() {
+    assert_eq!(sorted_ids("updated_at", 20, 1), ["30", "40", "10", "20"]);
+    assert_eq!(sorted_ids("time_left_seconds", 20, 1), ["30", "10", "40", "20"]);
+    assert_eq!(sorted_ids("-time_left_seconds", 20, 1), ["20", "10", "40", "30"]);
+    assert_eq!(sorted_ids("-min_item_level,time_left_seconds", 20, 1), ["10", "20", "30", "40"]);
+    assert_eq!(sorted_ids("-updated_at,time_left_seconds", 20, 1), ["10", "20", "40", "30"]);
+    assert_eq!(sorted_ids("slots_filled", 20, 1), ["10", "20", "30", "40"]);
+}
+
 #[test]
 fn created_world_id_supports_comma_separated_or() {
     let now = Utc::now();
@@ -1062,8 +2211,9 @@ fn created_world_id_supports_comma_separated_or() {
 
     assert_eq!(response.pagination.total, 2);
     assert_eq!(response.data.len(), 2);
-    assert_eq!(response.data[0].id, WIDE_LISTING_ID_STR);
-    assert_eq!(response.data[1].id, "54321");
+    // equal updated_at, so the listing id tie-breaker decides
+    assert_eq!(response.data[0].id, "54321");
+    assert_eq!(response.data[1].id, WIDE_LISTING_ID_STR);
 }
 
 #[test]
@@ -1089,8 +2239,9 @@ fn home_world_id_supports_comma_separated_or() {
 
     assert_eq!(response.pagination.total, 2);
     assert_eq!(response.data.len(), 2);
-    assert_eq!(response.data[0].id, WIDE_LISTING_ID_STR);
-    assert_eq!(response.data[1].id, "54321");
+    // equal updated_at, so the listing id tie-breaker decides
+    assert_eq!(response.data[0].id, "54321");
+    assert_eq!(response.data[1].id, WIDE_LISTING_ID_STR);
 }
 
 #[test]
@@ -1122,8 +2273,9 @@ fn created_and_home_world_filters_intersect_across_fields() {
 
     assert_eq!(response.pagination.total, 2);
     assert_eq!(response.data.len(), 2);
-    assert_eq!(response.data[0].id, WIDE_LISTING_ID_STR);
-    assert_eq!(response.data[1].id, "67890");
+    // equal updated_at, so the listing id tie-breaker decides
+    assert_eq!(response.data[0].id, "67890");
+    assert_eq!(response.data[1].id, WIDE_LISTING_ID_STR);
 }
 
 #[test]
@@ -1317,8 +2469,17 @@ async fn well_formed_unknown_filters_return_empty_collection() {
         "/api/v2/listings?created_world_id=999999",
         "/api/v2/listings?home_world_id=999999",
         "/api/v2/listings?category_id=999999",
+        "/api/v2/listings?category_ids=999999",
+        "/api/v2/listings?category_id=64&category_ids=32",
         "/api/v2/listings?duty_id=999999",
         "/api/v2/listings?job_ids=999999",
+        "/api/v2/listings?duty_type_id=3",
+        "/api/v2/listings?objective_ids=8",
+        "/api/v2/listings?condition_ids=1",
+        "/api/v2/listings?loot_rule_id=4",
+        "/api/v2/listings?open_role_ids=4",
+        "/api/v2/listings?open_job_ids=999999",
+        "/api/v2/listings?min_item_level_gte=700&min_item_level_lte=600",
         "/api/v2/listings?search=fixture&created_world_id=999999",
     ];
 
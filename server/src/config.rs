@@ -1,10 +1,24 @@
-use std::net::SocketAddr;
+use std::{collections::BTreeMap, net::SocketAddr};
 use serde::Deserialize;
 
 #[derive(Deserialize)]
 pub struct Config {
     pub web: Web,
-    pub mongo: Mongo,
+    #[serde(default)]
+    pub storage: Storage,
+    #[serde(default)]
+    pub contribute: Contribute,
+    #[serde(default)]
+    pub uploaders: Uploaders,
+    #[serde(default)]
+    pub admin: Admin,
+    #[serde(default)]
+    pub webhooks: Webhooks,
+    /// Discord channels that get an embed for every new listing their filters match.
+    #[serde(default)]
+    pub discord: Vec<DiscordSink>,
+    /// Required when `storage.kind` is `mongo`.
+    pub mongo: Option<Mongo>,
 }
 
 #[derive(Deserialize)]
@@ -12,6 +26,137 @@ pub struct Web {
     pub host: SocketAddr,
 }
 
+#[derive(Deserialize, Default)]
+pub struct Storage {
+    #[serde(default)]
+    pub kind: StorageKind,
+}
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    #[default]
+    Mongo,
+    /// Keeps listings in process; nothing survives a restart.
+    Memory,
+}
+
+/// Abuse limits applied to `/contribute` and `/contribute/multiple`.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Contribute {
+    /// Largest number of listings accepted by a single `/contribute/multiple` request.
+    pub max_batch_len: usize,
+    /// Largest request body, in bytes, accepted by either route.
+    pub max_body_bytes: u64,
+    /// Requests a single client may make back to back. `0` disables rate limiting.
+    pub rate_limit_burst: u32,
+    /// Requests per second a client's bucket regains after a burst.
+    pub rate_limit_per_second: f64,
+    /// Take the client address from the last `X-Forwarded-For` entry. Only enable this
+    /// behind a single reverse proxy that appends the address it received the request from.
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for Contribute {
+    fn default() -> Self {
+        Self {
+            max_batch_len: 500,
+            max_body_bytes: 4 * 1024 * 1024,
+            rate_limit_burst: 30,
+            rate_limit_per_second: 1.0,
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+/// Bearer tokens accepted on the contribute routes, in addition to the `rpf.uploaders` collection.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Uploaders {
+    pub anonymous: AnonymousUploads,
+    pub tokens: Vec<UploaderToken>,
+}
+
+/// What to do with a contribution that carries no `Authorization` header.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AnonymousUploads {
+    /// Accept it and leave `uploader` unset.
+    #[default]
+    Allow,
+    /// Accept it and record the uploader as `anonymous`.
+    Tag,
+    /// Refuse it with a 401.
+    Reject,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UploaderToken {
+    pub token: String,
+    /// Recorded on every listing this uploader touches.
+    pub label: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Operator-only routes under `/admin` and `/api/v2/subscriptions`. They are not served at all
+/// without a token.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Admin {
+    /// Expected as `Authorization: Bearer <token>`.
+    pub token: Option<String>,
+}
+
+/// How listings are delivered to the webhooks registered under `/api/v2/subscriptions`.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Webhooks {
+    /// Tries per delivery, the first one included.
+    pub max_attempts: u32,
+    /// Delay before the first retry, in milliseconds; doubled per retry up to `retry_max_ms`.
+    pub retry_base_ms: u64,
+    pub retry_max_ms: u64,
+    /// How long a single attempt may take, in milliseconds.
+    pub timeout_ms: u64,
+    /// Failed deliveries in a row after which a subscription is disabled. `0` never disables.
+    pub disable_after_failures: u32,
+}
+
+impl Default for Webhooks {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            retry_base_ms: 2_000,
+            retry_max_ms: 60_000,
+            timeout_ms: 10_000,
+            disable_after_failures: 10,
+        }
+    }
+}
+
+/// One Discord webhook and the saved search that feeds it.
+#[derive(Deserialize, Clone, Debug)]
+pub struct DiscordSink {
+    /// `https://discord.com/api/webhooks/<id>/<token>`, optionally with `?thread_id=`.
+    pub webhook_url: String,
+    /// Same parameters and values as `GET /api/v2/listings`, without `page`, `per_page` or `sort`.
+    #[serde(default)]
+    pub filters: BTreeMap<String, serde_json::Value>,
+    /// Language code for duty names and descriptions, e.g. `zh`. Defaults to English.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Emoji shown for a job code (`PLD`) or an open slot's role (`tank`, `healer`, `dps`, `any`),
+    /// e.g. a custom `<:pld:1234>`. Missing jobs fall back to their code.
+    #[serde(default)]
+    pub emoji: BTreeMap<String, String>,
+}
+
 #[derive(Deserialize)]
 pub struct Mongo {
     pub url: String,
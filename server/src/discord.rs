@@ -0,0 +1,598 @@
+//! Discord notifications for saved searches.
+//!
+//! Every `[[discord]]` entry in the config is a webhook URL plus v2 filters. A created listing
+//! the filters match is posted to the channel as an [`Embed`]; when an upload later changes the
+//! listing's slots, the same message is edited in place. Messages are only remembered in process,
+//! so after a restart listings that are already posted are left as they are.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc,
+};
+use warp::{
+    http::{
+        header::{CONTENT_TYPE, RETRY_AFTER, USER_AGENT},
+        Method, Request, StatusCode,
+    },
+    hyper::{self, Body},
+};
+
+use crate::{
+    config,
+    ffxiv::Language,
+    listing_container::QueriedListing,
+    sestring_ext::SeStringExt,
+    store::{listing_identity, ListingIdentity},
+    tasks::{jitter, Backoff},
+    web::v2::{
+        filters::ListingsQuery,
+        listings::matches_query,
+        stream::{ListingEvent, ListingEventKind},
+        subscriptions::parse_filters,
+    },
+    webhooks::{http_client, HttpClient},
+};
+
+/// Tries per message, so a rate limit or a Discord outage delays a post instead of dropping it.
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BACKOFF: Backoff = Backoff {
+    base: Duration::from_secs(1),
+    max: Duration::from_secs(30),
+};
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Discord's embed limits, in characters.
+const TITLE_LIMIT: usize = 256;
+const DESCRIPTION_LIMIT: usize = 4096;
+const FIELD_LIMIT: usize = 1024;
+
+/// Role icons used when `emoji` does not override them.
+const DEFAULT_ROLE_EMOJI: [(&str, &str); 4] = [
+    ("tank", "🛡️"),
+    ("healer", "💚"),
+    ("dps", "⚔️"),
+    ("any", "⬜"),
+];
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Embed {
+    pub title: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<u32>,
+    pub fields: Vec<EmbedField>,
+    pub footer: EmbedFooter,
+    /// When the listing was last uploaded, shown by Discord next to the footer.
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    pub inline: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EmbedFooter {
+    pub text: String,
+}
+
+#[derive(Serialize)]
+struct WebhookMessage<'a> {
+    embeds: [&'a Embed; 1],
+}
+
+#[derive(Deserialize)]
+struct PostedMessage {
+    id: String,
+}
+
+/// A validated `[[discord]]` entry.
+pub struct Sink {
+    webhook_url: String,
+    query: ListingsQuery,
+    language: Language,
+    emoji: BTreeMap<String, String>,
+}
+
+impl Sink {
+    pub fn new(config: &config::DiscordSink) -> Result<Self> {
+        let valid_url = config
+            .webhook_url
+            .parse::<warp::http::Uri>()
+            .is_ok_and(|uri| uri.scheme_str() == Some("https") && uri.host().is_some());
+        if !valid_url {
+            bail!("discord webhook_url must be an absolute https URL");
+        }
+
+        let (_, query) = parse_filters(config.filters.clone())
+            .map_err(|error| anyhow::anyhow!("invalid discord filters: {}", error.error.message))?;
+
+        Ok(Self {
+            webhook_url: config.webhook_url.clone(),
+            query,
+            language: Language::from_codes(config.language.as_deref()),
+            emoji: config.emoji.clone(),
+        })
+    }
+
+    fn emoji_for<'a>(&'a self, key: &'a str) -> &'a str {
+        self.emoji
+            .get(key)
+            .map(String::as_str)
+            .or_else(|| {
+                DEFAULT_ROLE_EMOJI
+                    .iter()
+                    .find(|(role, _)| *role == key)
+                    .map(|(_, emoji)| *emoji)
+            })
+            .unwrap_or(key)
+    }
+
+    /// Filled slots show their job; open slots show the one job they accept, or else the roles
+    /// they accept.
+    fn slot_icons(&self, listing: &QueriedListing) -> String {
+        let mut icons = Vec::new();
+        for slot in listing.listing.slots() {
+            let icon = match slot {
+                Ok(job) => self.emoji_for(job.code()).to_owned(),
+                Err((_, codes)) if !codes.is_empty() && !codes.contains(' ') => {
+                    self.emoji_for(&codes).to_owned()
+                }
+                Err((classes, _)) if classes.is_empty() || classes == "empty" => {
+                    self.emoji_for("any").to_owned()
+                }
+                Err((classes, _)) => classes.split(' ').map(|role| self.emoji_for(role)).collect(),
+            };
+            icons.push(icon);
+        }
+
+        icons.join(" ")
+    }
+
+    /// Renders `listing` the way the listings page shows it: duty, flags and description, where it
+    /// was made, the slots and how long it has left.
+    pub fn embed(&self, listing: &QueriedListing) -> Embed {
+        let lang = &self.language;
+        let pf = &listing.listing;
+        let (colour_class, flags) = pf.prepend_flags();
+        let description = pf.description.full_text(lang);
+        let description = match (flags.is_empty(), description.is_empty()) {
+            (true, _) => description,
+            (false, true) => flags,
+            (false, false) => format!("{flags}\n{description}"),
+        };
+
+        let mut fields = vec![EmbedField {
+            name: "World".into(),
+            value: pf.created_world_string().into_owned(),
+            inline: true,
+        }];
+        if let Some(data_centre) = pf.data_centre_name() {
+            fields.push(EmbedField {
+                name: "Data Centre".into(),
+                value: data_centre.into(),
+                inline: true,
+            });
+        }
+        let slots = self.slot_icons(listing);
+        if !slots.is_empty() {
+            fields.push(EmbedField {
+                name: format!("Slots ({}/{})", pf.slots_filled(), pf.slots_available),
+                value: truncate(&slots, FIELD_LIMIT),
+                inline: false,
+            });
+        }
+
+        Embed {
+            title: truncate(&pf.duty_name(lang), TITLE_LIMIT),
+            description: truncate(&description, DESCRIPTION_LIMIT),
+            color: colour(colour_class),
+            fields,
+            footer: EmbedFooter {
+                text: format!("Expires {}", listing.human_time_left()),
+            },
+            timestamp: listing.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// The colours of the listings page's `desc-*` classes.
+fn colour(colour_class: &str) -> Option<u32> {
+    match colour_class {
+        "desc-green" => Some(0x37DE99),
+        "desc-blue" => Some(0x5BE2FF),
+        "desc-yellow" => Some(0xFFC240),
+        _ => None,
+    }
+}
+
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_owned();
+    }
+
+    let mut truncated = text.chars().take(limit - 1).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+/// `webhook_url` with `path` appended to its path, keeping its query (`thread_id`).
+fn endpoint(webhook_url: &str, path: &str, wait: bool) -> String {
+    let (base, query) = webhook_url.split_once('?').unwrap_or((webhook_url, ""));
+    let mut url = format!("{}{path}", base.trim_end_matches('/'));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty() && !pair.starts_with("wait="))
+        .chain(wait.then_some("wait=true"))
+        .collect::<Vec<_>>()
+        .join("&");
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(&query);
+    }
+    url
+}
+
+/// The slots as last posted, to tell a change worth an edit from a plain refresh.
+#[derive(Debug, Clone, PartialEq)]
+struct SlotState {
+    jobs_present: Vec<u8>,
+    slots_available: u8,
+}
+
+impl SlotState {
+    fn of(listing: &QueriedListing) -> Self {
+        Self {
+            jobs_present: listing.listing.jobs_present.clone(),
+            slots_available: listing.listing.slots_available,
+        }
+    }
+}
+
+struct PostedListing {
+    message_id: String,
+    slots: SlotState,
+}
+
+/// Fans listing events out to one worker per sink, so each channel sees its posts and edits in
+/// order and a slow channel does not hold up the others.
+pub struct Notifier {
+    sinks: Vec<Sink>,
+    client: HttpClient,
+}
+
+impl Notifier {
+    pub fn new(sinks: &[config::DiscordSink]) -> Result<Self> {
+        let sinks = sinks
+            .iter()
+            .enumerate()
+            .map(|(i, sink)| Sink::new(sink).with_context(|| format!("invalid [[discord]] entry {}", i + 1)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            sinks,
+            client: http_client(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Posts and edits messages until `events` closes.
+    pub async fn run(self: Arc<Self>, mut events: broadcast::Receiver<ListingEvent>) -> Result<()> {
+        let mut queues = Vec::with_capacity(self.sinks.len());
+        let mut workers = Vec::with_capacity(self.sinks.len());
+        for index in 0..self.sinks.len() {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            queues.push(sender);
+            workers.push(tokio::task::spawn(Arc::clone(&self).work(index, receiver)));
+        }
+
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    eprintln!("discord notifications fell behind and skipped {skipped} listing events");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            for queue in &queues {
+                let _ = queue.send(event.clone());
+            }
+        }
+
+        // dropping the queues lets the workers finish what they have
+        drop(queues);
+        for worker in workers {
+            worker.await.context("discord worker panicked")?;
+        }
+        Ok(())
+    }
+
+    async fn work(self: Arc<Self>, index: usize, mut events: mpsc::UnboundedReceiver<ListingEvent>) {
+        let sink = &self.sinks[index];
+        let mut posted = HashMap::<ListingIdentity, PostedListing>::new();
+        while let Some(event) = events.recv().await {
+            let identity = listing_identity(&event.listing.listing);
+            let result = match event.kind {
+                ListingEventKind::Created if matches_query(&event.listing, &sink.query) => {
+                    self.post(sink, &event.listing).await.map(|message_id| {
+                        posted.insert(
+                            identity,
+                            PostedListing {
+                                message_id,
+                                slots: SlotState::of(&event.listing),
+                            },
+                        );
+                    })
+                }
+                ListingEventKind::Updated => match posted.get_mut(&identity) {
+                    Some(message) if message.slots != SlotState::of(&event.listing) => {
+                        match self.edit(sink, &message.message_id, &event.listing).await {
+                            Ok(true) => {
+                                message.slots = SlotState::of(&event.listing);
+                                Ok(())
+                            }
+                            // deleted in Discord; nothing left to keep up to date
+                            Ok(false) => {
+                                posted.remove(&identity);
+                                Ok(())
+                            }
+                            Err(error) => Err(error),
+                        }
+                    }
+                    _ => Ok(()),
+                },
+                ListingEventKind::Expired => {
+                    posted.remove(&identity);
+                    Ok(())
+                }
+                _ => Ok(()),
+            };
+
+            if let Err(error) = result {
+                eprintln!(
+                    "could not notify discord of listing {}: {error:#}",
+                    event.listing.listing.id
+                );
+            }
+        }
+    }
+
+    /// Posts a new message and returns its id.
+    async fn post(&self, sink: &Sink, listing: &QueriedListing) -> Result<String> {
+        let url = endpoint(&sink.webhook_url, "", true);
+        let (status, body) = self.send(Method::POST, &url, &sink.embed(listing)).await?;
+        if !status.is_success() {
+            bail!("discord answered {status}");
+        }
+
+        let message: PostedMessage =
+            serde_json::from_slice(&body).context("discord did not return the posted message")?;
+        Ok(message.id)
+    }
+
+    /// Replaces a posted message's embed. `false` when the message no longer exists.
+    async fn edit(&self, sink: &Sink, message_id: &str, listing: &QueriedListing) -> Result<bool> {
+        let url = endpoint(&sink.webhook_url, &format!("/messages/{message_id}"), false);
+        let (status, _) = self.send(Method::PATCH, &url, &sink.embed(listing)).await?;
+        match status {
+            status if status.is_success() => Ok(true),
+            StatusCode::NOT_FOUND => Ok(false),
+            status => bail!("discord answered {status}"),
+        }
+    }
+
+    /// Sends the embed, waiting out rate limits and retrying server errors.
+    async fn send(&self, method: Method, url: &str, embed: &Embed) -> Result<(StatusCode, hyper::body::Bytes)> {
+        let body = serde_json::to_vec(&WebhookMessage { embeds: [embed] })
+            .expect("discord messages always serialize");
+
+        let mut attempt = 1;
+        loop {
+            let request = Request::builder()
+                .method(method.clone())
+                .uri(url)
+                .header(CONTENT_TYPE, "application/json")
+                .header(USER_AGENT, "remote-party-finder")
+                .body(Body::from(body.clone()))
+                .context("could not build discord request")?;
+            let response = tokio::time::timeout(REQUEST_TIMEOUT, self.client.request(request))
+                .await
+                .context("discord request timed out")?
+                .context("discord request failed")?;
+
+            let status = response.status();
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<f64>().ok())
+                .map(Duration::from_secs_f64);
+            let body = hyper::body::to_bytes(response.into_body())
+                .await
+                .context("could not read discord response")?;
+
+            let retryable = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+            if !retryable || attempt >= MAX_ATTEMPTS {
+                return Ok((status, body));
+            }
+
+            let delay = retry_after.unwrap_or_else(|| RETRY_BACKOFF.delay(attempt, jitter()));
+            tokio::time::sleep(delay.min(RETRY_BACKOFF.max)).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use chrono::Utc;
+    use warp::{hyper::body::Bytes, Filter};
+
+    use super::*;
+    use crate::{
+        listing::{JobFlags, ObjectiveFlags, PartyFinderListing, PartyFinderSlot},
+        store::UpsertOutcome,
+        web::v2::stream::ListingEvents,
+    };
+
+    const WARRIOR: u8 = 21;
+    const PALADIN: u8 = 19;
+
+    /// A local stand-in for Discord that records every request and answers with message `42`.
+    fn stand_in() -> (SocketAddr, mpsc::UnboundedReceiver<(Method, String, Bytes)>) {
+        let (sender, received) = mpsc::unbounded_channel();
+        let route = warp::method()
+            .and(warp::path::full())
+            .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+            .and(warp::body::bytes())
+            .map(move |method, path: warp::path::FullPath, query: String, body| {
+                let target = match query.as_str() {
+                    "" => path.as_str().to_owned(),
+                    query => format!("{}?{query}", path.as_str()),
+                };
+                sender.send((method, target, body)).unwrap();
+                warp::reply::json(&serde_json::json!({ "id": "42" }))
+            });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::task::spawn(server);
+        (addr, received)
+    }
+
+    fn sink(webhook_url: String) -> Sink {
+        Sink {
+            webhook_url,
+            query: Default::default(),
+            language: Language::English,
+            emoji: BTreeMap::from([
+                ("PLD".to_owned(), "<:pld:1>".to_owned()),
+                ("tank".to_owned(), "<:tank:2>".to_owned()),
+            ]),
+        }
+    }
+
+    /// A warrior, then open slots for a paladin, a paladin or white mage, and anyone.
+    fn listing() -> PartyFinderListing {
+        PartyFinderListing {
+            slots_available: 4,
+            objective: ObjectiveFlags::DUTY_COMPLETION,
+            slots: [
+                JobFlags::WARRIOR,
+                JobFlags::PALADIN,
+                JobFlags::PALADIN | JobFlags::WHITE_MAGE,
+                JobFlags::all(),
+            ]
+            .into_iter()
+            .map(|accepting| PartyFinderSlot { accepting })
+            .collect(),
+            jobs_present: vec![WARRIOR, 0, 0, 0],
+            ..crate::test::listing()
+        }
+    }
+
+    #[test]
+    fn embed_shows_duty_flags_world_slots_and_time_left() {
+        let listing = listing();
+        let queried = QueriedListing {
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            updated_minute: Utc::now(),
+            time_left: 1200.0,
+            listing: listing.clone(),
+        };
+
+        let embed = sink("https://example.com/hook".into()).embed(&queried);
+
+        assert_eq!(embed.title, listing.duty_name(&Language::English));
+        assert_eq!(embed.description, listing.prepend_flags().1);
+        assert_eq!(embed.color, colour(listing.prepend_flags().0));
+        let fields = embed
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("World", &*listing.created_world_string()),
+                ("Data Centre", listing.data_centre_name().unwrap()),
+                ("Slots (1/4)", "WAR <:pld:1> 💚<:tank:2> ⬜"),
+            ],
+        );
+        assert_eq!(embed.footer.text, "Expires in 20 minutes");
+    }
+
+    #[test]
+    fn embed_survives_listings_with_fewer_slots_than_jobs() {
+        let mut listing = listing();
+        listing.slots.truncate(1);
+        let queried = QueriedListing {
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            updated_minute: Utc::now(),
+            time_left: 1200.0,
+            listing,
+        };
+
+        let embed = sink("https://example.com/hook".into()).embed(&queried);
+
+        let slots = embed.fields.iter().find(|field| field.name.starts_with("Slots")).unwrap();
+        assert_eq!(slots.value, "WAR ⬜ ⬜ ⬜");
+    }
+
+    #[test]
+    fn endpoints_keep_the_thread_and_wait_only_for_posts() {
+        let url = "https://discord.com/api/webhooks/1/token?thread_id=7";
+        assert_eq!(
+            endpoint(url, "", true),
+            "https://discord.com/api/webhooks/1/token?thread_id=7&wait=true",
+        );
+        assert_eq!(
+            endpoint(url, "/messages/42", false),
+            "https://discord.com/api/webhooks/1/token/messages/42?thread_id=7",
+        );
+    }
+
+    #[tokio::test]
+    async fn slot_changes_edit_the_posted_message() {
+        let (addr, mut received) = stand_in();
+        let notifier = Arc::new(Notifier {
+            sinks: vec![sink(format!("http://{addr}/hook"))],
+            client: http_client(),
+        });
+        let events = ListingEvents::new();
+        tokio::task::spawn(notifier.run(events.subscribe()));
+
+        let mut listing = listing();
+        events.publish_upsert(listing.clone(), UpsertOutcome::Inserted, Utc::now());
+        // a refresh that leaves the slots alone is not worth an edit
+        listing.seconds_remaining -= 60;
+        events.publish_upsert(listing.clone(), UpsertOutcome::Updated, Utc::now());
+        listing.jobs_present[1] = PALADIN;
+        events.publish_upsert(listing, UpsertOutcome::Updated, Utc::now());
+
+        let (method, target, _) = received.recv().await.unwrap();
+        assert_eq!((method, target.as_str()), (Method::POST, "/hook?wait=true"));
+
+        let (method, target, body) = received.recv().await.unwrap();
+        assert_eq!((method, target.as_str()), (Method::PATCH, "/hook/messages/42"));
+        let message: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(message["embeds"][0]["fields"][2]["name"], "Slots (2/4)");
+        assert!(received.try_recv().is_err());
+    }
+}
@@ -10,7 +10,7 @@ use sestring::SeString;
 use crate::ffxiv::{Language, LocalisedText};
 use crate::sestring_ext::SeStringExt;
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct PartyFinderListing {
     pub id: u64,
     // Truncated lower bits from the game payload; not the canonical listing identity.
@@ -73,6 +73,9 @@ impl PartyFinderListing {
         )
     }
 
+    /// One entry per slot: the job filling it, or the role classes and job codes it is open to.
+    /// Uploads do not always carry a slot entry for every open slot; those come back with no
+    /// classes or codes.
     pub fn slots(&self) -> Vec<std::result::Result<ClassJob, (String, String)>> {
         let mut slots = Vec::with_capacity(self.slots_available as usize);
         for i in 0..self.slots_available as usize {
@@ -85,7 +88,11 @@ impl PartyFinderListing {
                 .copied()
             {
                 Some(cj) => Ok(cj),
-                None => Err((self.slots[i].html_classes(), self.slots[i].codes())),
+                None => Err(self
+                    .slots
+                    .get(i)
+                    .map(|slot| (slot.html_classes(), slot.codes()))
+                    .unwrap_or_default()),
             };
             slots.push(cj);
         }
@@ -196,7 +203,7 @@ impl PartyFinderListing {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct PartyFinderSlot {
     pub accepting: JobFlags,
 }
@@ -654,25 +661,6 @@ pub enum PartyFinderCategory {
 }
 
 impl PartyFinderCategory {
-    pub const ALL: [Self; 16] = [
-        Self::DutyRoulette,
-        Self::Dungeons,
-        Self::Guildhests,
-        Self::Trials,
-        Self::Raids,
-        Self::HighEndDuty,
-        Self::Pvp,
-        Self::GoldSaucer,
-        Self::Fates,
-        Self::TreasureHunt,
-        Self::TheHunt,
-        Self::GatheringForays,
-        Self::DeepDungeons,
-        Self::FieldOperations,
-        Self::VariantAndCriterionDungeonFinder,
-        Self::None,
-    ];
-
     pub fn as_str(self) -> &'static str {
         match self {
             Self::DutyRoulette => "DutyRoulette",
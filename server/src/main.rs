@@ -1,4 +1,5 @@
-#![feature(try_blocks, iter_intersperse)]
+#![feature(iter_intersperse)]
+#![cfg_attr(test, feature(test))]
 
 use anyhow::Context;
 use std::borrow::Cow;
@@ -11,9 +12,15 @@ use crate::config::Config;
 mod config;
 mod listing;
 mod listing_container;
+mod metrics;
 mod base64_sestring;
 mod sestring_ext;
+mod search;
 mod stats;
+mod store;
+mod tasks;
+mod webhooks;
+mod discord;
 mod web;
 mod template;
 mod ffxiv;
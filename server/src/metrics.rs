@@ -0,0 +1,316 @@
+//! A small Prometheus text-format registry, so metrics can be collected and rendered without an
+//! exporter process or a Mongo connection.
+
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+/// Request and aggregation latencies, in seconds.
+pub const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+/// Listings per contribute request.
+pub const BATCH_SIZE_BUCKETS: &[f64] = &[1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0];
+
+/// A monotonically increasing value per label set.
+pub struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl CounterVec {
+    pub fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn inc(&self, labels: &[&str]) {
+        self.inc_by(labels, 1);
+    }
+
+    pub fn inc_by(&self, labels: &[&str], by: u64) {
+        debug_assert_eq!(labels.len(), self.labels.len(), "{}: wrong label count", self.name);
+        let key = labels.iter().map(|label| label.to_string()).collect();
+        *self.values.lock().unwrap().entry(key).or_default() += by;
+    }
+
+    #[cfg(test)]
+    pub fn get(&self, labels: &[&str]) -> u64 {
+        let key: Vec<String> = labels.iter().map(|label| label.to_string()).collect();
+        self.values.lock().unwrap().get(&key).copied().unwrap_or_default()
+    }
+
+    pub fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "counter");
+        for (values, count) in self.values.lock().unwrap().iter() {
+            sample(out, self.name, self.labels, values, None, &count.to_string());
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct HistogramState {
+    /// Per bucket, not cumulative; made cumulative when rendered.
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// Observations sorted into fixed upper-bound buckets per label set.
+pub struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    buckets: &'static [f64],
+    values: Mutex<BTreeMap<Vec<String>, HistogramState>>,
+}
+
+impl HistogramVec {
+    pub fn new(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static [&'static str],
+        buckets: &'static [f64],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            buckets,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn observe(&self, labels: &[&str], value: f64) {
+        debug_assert_eq!(labels.len(), self.labels.len(), "{}: wrong label count", self.name);
+        let key = labels.iter().map(|label| label.to_string()).collect();
+        let mut values = self.values.lock().unwrap();
+        let state = values.entry(key).or_insert_with(|| HistogramState {
+            counts: vec![0; self.buckets.len()],
+            ..Default::default()
+        });
+
+        if let Some(bucket) = self.buckets.iter().position(|bound| value <= *bound) {
+            state.counts[bucket] += 1;
+        }
+        state.sum += value;
+        state.count += 1;
+    }
+
+    pub fn observe_duration(&self, labels: &[&str], duration: Duration) {
+        self.observe(labels, duration.as_secs_f64());
+    }
+
+    #[cfg(test)]
+    pub fn count(&self, labels: &[&str]) -> u64 {
+        let key: Vec<String> = labels.iter().map(|label| label.to_string()).collect();
+        self.values.lock().unwrap().get(&key).map(|state| state.count).unwrap_or_default()
+    }
+
+    pub fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "histogram");
+        let bucket_name = format!("{}_bucket", self.name);
+        for (values, state) in self.values.lock().unwrap().iter() {
+            let mut cumulative = 0;
+            for (bound, count) in self.buckets.iter().zip(&state.counts) {
+                cumulative += count;
+                sample(out, &bucket_name, self.labels, values, Some(&bound.to_string()), &cumulative.to_string());
+            }
+            sample(out, &bucket_name, self.labels, values, Some("+Inf"), &state.count.to_string());
+            sample(out, &format!("{}_sum", self.name), self.labels, values, None, &state.sum.to_string());
+            sample(out, &format!("{}_count", self.name), self.labels, values, None, &state.count.to_string());
+        }
+    }
+}
+
+/// Renders a gauge whose values are computed at scrape time rather than stored.
+pub fn render_gauge<'a>(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    values: impl IntoIterator<Item = (&'a str, f64)>,
+) {
+    render_computed(out, name, help, "gauge", label, values);
+}
+
+/// Renders a counter kept elsewhere, such as the contribute limit totals.
+pub fn render_counter<'a>(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    values: impl IntoIterator<Item = (&'a str, f64)>,
+) {
+    render_computed(out, name, help, "counter", label, values);
+}
+
+fn render_computed<'a>(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    kind: &str,
+    label: &str,
+    values: impl IntoIterator<Item = (&'a str, f64)>,
+) {
+    header(out, name, help, kind);
+    for (value_label, value) in values {
+        sample(out, name, &[label], &[value_label.to_string()], None, &value.to_string());
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(
+    out: &mut String,
+    name: &str,
+    labels: &[&str],
+    values: &[String],
+    le: Option<&str>,
+    value: &str,
+) {
+    let mut pairs = labels
+        .iter()
+        .zip(values)
+        .map(|(label, value)| format!("{label}=\"{}\"", escape(value)))
+        .collect::<Vec<_>>();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{le}\""));
+    }
+
+    if pairs.is_empty() {
+        let _ = writeln!(out, "{name} {value}");
+    } else {
+        let _ = writeln!(out, "{name}{{{}}} {value}", pairs.join(","));
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Every metric the server records as it runs. Values derived from other state, such as active
+/// listings, are added by the `/metrics` route when it is scraped.
+pub struct Metrics {
+    pub http_requests: CounterVec,
+    pub http_request_duration: HistogramVec,
+    pub contributions: CounterVec,
+    pub contributions_rejected: CounterVec,
+    pub contribute_batch_size: HistogramVec,
+    pub cache_requests: CounterVec,
+    pub cache_evictions: CounterVec,
+    pub stats_aggregation_duration: HistogramVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            http_requests: CounterVec::new(
+                "rpf_http_requests_total",
+                "HTTP requests by route, method and status.",
+                &["route", "method", "status"],
+            ),
+            http_request_duration: HistogramVec::new(
+                "rpf_http_request_duration_seconds",
+                "Time until the response head was ready, by route.",
+                &["route"],
+                LATENCY_BUCKETS,
+            ),
+            contributions: CounterVec::new(
+                "rpf_contributions_total",
+                "Contributed listings by outcome.",
+                &["outcome"],
+            ),
+            contributions_rejected: CounterVec::new(
+                "rpf_contributions_rejected_total",
+                "Contributed listings that were not stored, by rejection code.",
+                &["reason"],
+            ),
+            contribute_batch_size: HistogramVec::new(
+                "rpf_contribute_batch_size",
+                "Listings per accepted contribute request.",
+                &[],
+                BATCH_SIZE_BUCKETS,
+            ),
+            cache_requests: CounterVec::new(
+                "rpf_cache_requests_total",
+                "Cache lookups by cache and result.",
+                &["cache", "result"],
+            ),
+            cache_evictions: CounterVec::new(
+                "rpf_cache_evictions_total",
+                "Expired cache entries dropped by the sweeper.",
+                &["cache"],
+            ),
+            stats_aggregation_duration: HistogramVec::new(
+                "rpf_stats_aggregation_duration_seconds",
+                "Time spent aggregating statistics, by window.",
+                &["window"],
+                LATENCY_BUCKETS,
+            ),
+        }
+    }
+
+    pub fn cache_lookup(&self, cache: &str, hit: bool) {
+        self.cache_requests.inc(&[cache, if hit { "hit" } else { "miss" }]);
+    }
+
+    pub fn render(&self, out: &mut String) {
+        self.http_requests.render(out);
+        self.http_request_duration.render(out);
+        self.contributions.render(out);
+        self.contributions_rejected.render(out);
+        self.contribute_batch_size.render(out);
+        self.cache_requests.render(out);
+        self.cache_evictions.render(out);
+        self.stats_aggregation_duration.render(out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_and_histograms_render_prometheus_text() {
+        let requests = CounterVec::new("requests_total", "Requests.", &["route"]);
+        requests.inc(&["/a\"b"]);
+        requests.inc_by(&["/a\"b"], 2);
+        let sizes = HistogramVec::new("size", "Sizes.", &[], &[1.0, 10.0]);
+        for value in [1.0, 3.0, 50.0] {
+            sizes.observe(&[], value);
+        }
+
+        let mut out = String::new();
+        requests.render(&mut out);
+        sizes.render(&mut out);
+
+        assert_eq!(
+            out,
+            "# HELP requests_total Requests.\n\
+             # TYPE requests_total counter\n\
+             requests_total{route=\"/a\\\"b\"} 3\n\
+             # HELP size Sizes.\n\
+             # TYPE size histogram\n\
+             size_bucket{le=\"1\"} 1\n\
+             size_bucket{le=\"10\"} 2\n\
+             size_bucket{le=\"+Inf\"} 3\n\
+             size_sum 54\n\
+             size_count 3\n"
+        );
+    }
+}
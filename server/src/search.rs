@@ -0,0 +1,321 @@
+//! Keyword search over listing names and descriptions, shared by the v1 and v2 `search`
+//! parameters.
+//!
+//! Text is normalized first: full-width forms become ASCII, Traditional Chinese becomes
+//! Simplified, and everything is lowercased. It is then split into tokens: whitespace and
+//! punctuation separated words, and overlapping bigrams over runs of CJK characters, which have
+//! no spaces to split on. Auto-translate payloads are expanded into every language, so a
+//! listing written in one client language is found from all of them.
+
+use std::collections::{BTreeSet, HashSet};
+
+use sestring::{Payload, SeString};
+
+use crate::{ffxiv::Language, listing::PartyFinderListing};
+
+pub mod kana;
+pub mod pinyin;
+mod variants;
+
+const LANGUAGES: [Language; 5] = [
+    Language::ChineseSimplified,
+    Language::Japanese,
+    Language::English,
+    Language::German,
+    Language::French,
+];
+
+/// A parsed `search` value. Bare terms must all match; `"quoted phrases"` must appear as
+/// written; terms and phrases prefixed with `-` must not match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    include: Vec<Term>,
+    exclude: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Tokens(Vec<Token>),
+    Phrase(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Token {
+    /// Matches any word it is a prefix of, so `prac` finds `practice`.
+    Word(String),
+    /// A CJK bigram, or a single character when the run is one character long.
+    Gram(String),
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Self {
+        let normalized = normalize(input);
+        let mut query = Self::default();
+        let mut chars = normalized.chars().peekable();
+
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            let Some(first) = chars.next() else {
+                break;
+            };
+
+            let (excluded, first) = match first {
+                '-' => match chars.next() {
+                    Some(c) if !c.is_whitespace() => (true, c),
+                    _ => continue,
+                },
+                _ => (false, first),
+            };
+
+            let term = if is_quote(first) {
+                let phrase = chars.by_ref().take_while(|c| !is_quote(*c)).collect::<String>();
+                Term::Phrase(collapse_whitespace(&phrase))
+            } else {
+                let mut word = first.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    word.push(c);
+                }
+                Term::Tokens(tokenize(&word))
+            };
+
+            let empty = match &term {
+                Term::Tokens(tokens) => tokens.is_empty(),
+                Term::Phrase(phrase) => phrase.is_empty(),
+            };
+            if !empty {
+                if excluded {
+                    query.exclude.push(term);
+                } else {
+                    query.include.push(term);
+                }
+            }
+        }
+
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn matches(&self, listing: &PartyFinderListing) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        let document = SearchDocument::of(listing);
+        self.include.iter().all(|term| document.matches(term))
+            && !self.exclude.iter().any(|term| document.matches(term))
+    }
+}
+
+/// The searchable form of one listing: its normalized texts in every language and their tokens.
+#[derive(Debug, Clone, Default)]
+pub struct SearchDocument {
+    texts: Vec<String>,
+    words: BTreeSet<String>,
+    grams: HashSet<String>,
+}
+
+impl SearchDocument {
+    pub fn of(listing: &PartyFinderListing) -> Self {
+        let mut document = Self::default();
+        let texts = localised_texts(&listing.name)
+            .into_iter()
+            .chain(localised_texts(&listing.description));
+        for text in texts {
+            let text = collapse_whitespace(&normalize(&text));
+            for token in tokenize(&text) {
+                match token {
+                    Token::Word(word) => document.words.insert(word),
+                    Token::Gram(gram) => document.grams.insert(gram),
+                };
+            }
+            // single characters too, for one-character queries
+            document
+                .grams
+                .extend(text.chars().filter(|c| is_cjk(*c)).map(String::from));
+            if !document.texts.contains(&text) {
+                document.texts.push(text);
+            }
+        }
+        document
+    }
+
+    fn matches(&self, term: &Term) -> bool {
+        match term {
+            Term::Tokens(tokens) => tokens.iter().all(|token| match token {
+                Token::Word(prefix) => self
+                    .words
+                    .range(prefix.clone()..)
+                    .next()
+                    .is_some_and(|word| word.starts_with(prefix.as_str())),
+                Token::Gram(gram) => self.grams.contains(gram),
+            }),
+            Term::Phrase(phrase) => self.texts.iter().any(|text| text.contains(phrase.as_str())),
+        }
+    }
+}
+
+/// The distinct renderings of `text`; only auto-translate payloads differ between languages.
+fn localised_texts(text: &SeString) -> Vec<String> {
+    let has_auto_translate = text
+        .0
+        .iter()
+        .any(|payload| matches!(payload, Payload::AutoTranslate(_)));
+    let languages = if has_auto_translate { &LANGUAGES[..] } else { &LANGUAGES[..1] };
+
+    let mut texts = Vec::with_capacity(languages.len());
+    for language in languages {
+        let rendered = crate::sestring_ext::SeStringExt::full_text(text, language);
+        if !texts.contains(&rendered) {
+            texts.push(rendered);
+        }
+    }
+    texts
+}
+
+/// Full-width forms to ASCII, Traditional to Simplified Chinese, then lowercase.
+pub fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => variants::simplified(c),
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_quote(c: char) -> bool {
+    matches!(c, '"' | '“' | '”' | '「' | '」')
+}
+
+/// Han, kana and Hangul: scripts written without spaces between words.
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{31F0}'..='\u{31FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{FF66}'..='\u{FF9F}'
+            | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
+/// Expects normalized text.
+fn tokenize(text: &str) -> Vec<Token> {
+    fn flush_run(run: &mut Vec<char>, tokens: &mut Vec<Token>) {
+        match run.len() {
+            0 => {}
+            1 => tokens.push(Token::Gram(run[0].to_string())),
+            _ => tokens.extend(run.windows(2).map(|pair| Token::Gram(pair.iter().collect()))),
+        }
+        run.clear();
+    }
+
+    fn flush_word(word: &mut String, tokens: &mut Vec<Token>) {
+        if !word.is_empty() {
+            tokens.push(Token::Word(std::mem::take(word)));
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let mut run = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut tokens);
+            run.push(c);
+        } else if c.is_alphanumeric() {
+            flush_run(&mut run, &mut tokens);
+            word.push(c);
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_run(&mut run, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_run(&mut run, &mut tokens);
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sestring::{AutoTranslatePayload, TextPayload};
+
+    fn listing(name: &str, description: Vec<Payload>) -> PartyFinderListing {
+        PartyFinderListing {
+            name: SeString(vec![Payload::Text(TextPayload(name.to_owned()))]),
+            description: SeString(description),
+            ..crate::test::listing()
+        }
+    }
+
+    fn text(text: &str) -> Payload {
+        Payload::Text(TextPayload(text.to_owned()))
+    }
+
+    fn matches(search: &str, listing: &PartyFinderListing) -> bool {
+        SearchQuery::parse(search).matches(listing)
+    }
+
+    #[test]
+    fn cjk_text_is_found_by_bigrams_without_spaces() {
+        let listing = listing("光之战士", vec![text("绝龙诗战争 开荒，来奶妈")]);
+
+        assert!(matches("龙诗", &listing));
+        assert!(matches("绝龙诗", &listing));
+        assert!(matches("开荒 奶妈", &listing));
+        assert!(matches("战", &listing));
+        assert!(matches("战士", &listing));
+        assert!(!matches("龙战", &listing));
+        assert!(!matches("开荒 坦克", &listing));
+    }
+
+    #[test]
+    fn variants_and_full_width_forms_match_each_other() {
+        let listing = listing("Alice", vec![text("絕龍詩 練習 ＰＲＡＣＴＩＣＥ　ｐｆ")]);
+
+        assert!(matches("绝龙诗", &listing));
+        assert!(matches("練習", &listing));
+        assert!(matches("practice", &listing));
+        assert!(matches("ＰＦ", &listing));
+        assert!(matches("prac", &listing));
+        assert!(!matches("ractice", &listing));
+    }
+
+    #[test]
+    fn auto_translate_is_searchable_in_every_language() {
+        let help = Payload::AutoTranslate(AutoTranslatePayload { group: 1, key: 101 });
+        let listing = listing("Alice", vec![text("pf "), help]);
+
+        assert!(matches("定型文翻译", &listing));
+        assert!(matches("auto-translate", &listing));
+        assert!(matches("定型文辞書", &listing));
+        assert!(matches("Übersetzung", &listing));
+        assert!(matches("\"pf please use\"", &listing));
+    }
+
+    #[test]
+    fn quoted_phrases_and_exclusions() {
+        let listing = listing("Alice", vec![text("Savage prog, farm later")]);
+
+        assert!(matches("\"savage prog\"", &listing));
+        assert!(!matches("\"prog savage\"", &listing));
+        assert!(matches("prog savage", &listing));
+        assert!(!matches("savage -farm", &listing));
+        assert!(matches("savage -clear", &listing));
+        assert!(!matches("-\"farm later\"", &listing));
+        assert!(matches("- \"\" ", &listing));
+    }
+}
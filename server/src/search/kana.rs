@@ -0,0 +1,133 @@
+//! Kana and romaji folded into one loose romanization, so `バハムート`, `ばはむーと` and
+//! `bahamuto` compare equal. Kunrei-style spellings are used (`si`, `ti`, `tu`, `hu`, `zi`)
+//! because several Hepburn spellings map onto them but not the other way round; long vowel
+//! marks are dropped since romaji input rarely spells them.
+
+use std::collections::HashMap;
+
+const SYLLABLES: &str = "\
+あa いi うu えe おo かka きki くku けke こko がga ぎgi ぐgu げge ごgo \
+さsa しsi すsu せse そso ざza じzi ずzu ぜze ぞzo たta ちti つtu てte とto \
+だda ぢdi づdu でde どdo なna にni ぬnu ねne のno はha ひhi ふhu へhe ほho \
+ばba びbi ぶbu べbe ぼbo ぱpa ぴpi ぷpu ぺpe ぽpo まma みmi むmu めme もmo \
+やya ゆyu よyo らra りri るru れre ろro わwa ゐi ゑe をo んn ゔvu ゎwa";
+
+/// Hepburn and other common spellings rewritten to the kunrei forms, longest first.
+const SPELLINGS: [(&str, &str); 12] = [
+    ("cch", "tch"),
+    ("shi", "si"),
+    ("chi", "ti"),
+    ("tsu", "tu"),
+    ("sh", "sy"),
+    ("ch", "ty"),
+    ("ji", "zi"),
+    ("fu", "hu"),
+    ("j", "zy"),
+    ("f", "h"),
+    ("nn", "n"),
+    ("-", ""),
+];
+
+lazy_static::lazy_static! {
+    static ref ROMAJI: HashMap<char, &'static str> = SYLLABLES
+        .split(' ')
+        .filter_map(|syllable| {
+            let mut chars = syllable.chars();
+            Some((chars.next()?, chars.as_str()))
+        })
+        .collect();
+}
+
+/// Katakana to hiragana; everything else is left alone.
+fn hiragana(c: char) -> char {
+    match c {
+        '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Romanizes the kana in `text`, keeping other characters as they are.
+pub fn romaji(text: &str) -> String {
+    let mut out = String::new();
+    let mut double_next = false;
+
+    for c in text.chars().map(hiragana) {
+        match c {
+            'ー' | '・' => {}
+            'っ' => double_next = true,
+            'ゃ' | 'ゅ' | 'ょ' => {
+                // きゃ → kya, しゃ → sya
+                if out.ends_with('i') {
+                    out.pop();
+                }
+                out.push('y');
+                out.push(small_vowel(c));
+            }
+            'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' => {
+                // ふぁ → ha, てぃ → ti, うぃ → wi
+                match out.pop() {
+                    Some('u') if !out.ends_with(|c: char| c.is_ascii_alphabetic()) => out.push('w'),
+                    Some(vowel) if "aiueo".contains(vowel) => {}
+                    Some(other) => out.push(other),
+                    None => {}
+                }
+                out.push(small_vowel(c));
+            }
+            _ => match ROMAJI.get(&c) {
+                Some(syllable) => {
+                    if std::mem::take(&mut double_next) {
+                        let consonant = syllable.chars().next().filter(|c| !"aiueo".contains(*c));
+                        out.extend(consonant);
+                    }
+                    out.push_str(syllable);
+                }
+                None => {
+                    double_next = false;
+                    out.push(c);
+                }
+            },
+        }
+    }
+
+    // ジャンヌ and "jannu" both end up as zyanu
+    romaji_spelling(&out)
+}
+
+/// Rewrites typed romaji into the spelling [`romaji`] produces.
+pub fn romaji_spelling(text: &str) -> String {
+    SPELLINGS
+        .iter()
+        .fold(text.to_ascii_lowercase(), |text, (from, to)| {
+            text.replace(from, to)
+        })
+}
+
+fn small_vowel(c: char) -> char {
+    match c {
+        'ぁ' => 'a',
+        'ぃ' => 'i',
+        'ぅ' | 'ゅ' => 'u',
+        'ぇ' => 'e',
+        'ぉ' | 'ょ' => 'o',
+        _ => 'a',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kana_and_typed_romaji_meet() {
+        assert_eq!(romaji("バハムート"), "bahamuto");
+        assert_eq!(romaji("ばはむーと"), romaji_spelling("bahamuto"));
+        assert_eq!(romaji("ティターニア"), romaji_spelling("titania"));
+        assert_eq!(romaji("シヴァ"), romaji_spelling("shiva"));
+        assert_eq!(romaji("ちょっと"), romaji_spelling("chotto"));
+        assert_eq!(romaji("マッチ"), romaji_spelling("matchi"));
+        assert_eq!(romaji("ジャンヌ"), romaji_spelling("jannu"));
+        assert_eq!(romaji("ファイア"), romaji_spelling("faia"));
+        assert_eq!(romaji("ウィンド"), "windo");
+        assert_eq!(romaji("極イフリート討滅戦"), "極ihurito討滅戦");
+    }
+}
@@ -0,0 +1,141 @@
+//! Mandarin readings, without tones, for the characters in the bundled duty and zone names.
+//! Characters with more than one reading are listed under the one those names use.
+
+use std::collections::HashMap;
+
+const READINGS: &str = "\
+a 阿 ; ai 埃爱艾 ; an 安岸庵暗案 ; ang 昂 ; ao 奥鏖 ; ba 八吧巴霸 ; bai 拜摆白百 ; \
+ban 半板般 ; bang 蚌邦 ; bao 保包堡宝报爆豹 ; bei 北悲杯背贝 ; ben 本 ; \
+bi 俾壁币庇比璧臂避闭陛鼻 ; bian 变边遍 ; biao 标表镖 ; bie 别 ; bin 宾 ; \
+bing 兵冰病 ; bo 伯博波 ; bu 不布怖捕部 ; cai 彩裁财 ; can 参惨残灿 ; cang 仓苍藏 ; \
+cao 草 ; ce 策 ; ceng 层 ; cha 差查 ; chan 产缠蟾 ; chang 厂唱场常长 ; \
+chao 巢朝超 ; che 车 ; chen 尘沉陈 ; cheng 城成程 ; chi 赤 ; chong 充冲宠憧 ; \
+chou 仇酬 ; chu 出初处础蜍除 ; chuan 传穿船 ; chuang 创 ; chui 垂 ; chun 纯 ; \
+ci 刺次 ; cong 丛 ; cun 村 ; cuo 错 ; da 大打达 ; dai 代带待戴 ; dan 单弹担淡蛋 ; \
+dang 党 ; dao 倒到导岛盗道 ; de 得德的 ; deng 灯登瞪等 ; di 低地帝底弟敌狄砥第蒂迪邸 ; \
+dian 巅店殿淀点电甸颠 ; diao 钓 ; die 爹碟 ; ding 丁定顶 ; dong 东动栋洞 ; \
+dou 斗豆 ; du 杜毒独督读都 ; duan 断段短端 ; dui 堆对队 ; duo 多夺朵躲铎 ; e 俄厄噩恶鳄 ; \
+en 恩 ; er 二儿尔而耳 ; fa 伐发法 ; fan 反帆翻范 ; fang 仿房放方芳防 ; fei 废菲飞 ; \
+fen 分愤纷 ; feng 丰奉封峰疯缝蜂锋风 ; fu 复夫富府弗拂浮父福腐芙附 ; gai 垓改盖 ; \
+gan 干敢甘赶 ; gang 冈刚港 ; gao 糕高 ; ge 个各哥戈格歌阁隔革 ; gei 给 ; gen 根 ; \
+geng 更 ; gong 供公共宫工攻 ; gou 构 ; gu 古姑孤谷骨 ; gua 挂 ; guai 怪 ; \
+guan 关冠官观馆 ; guang 光广 ; gui 归轨鬼龟 ; guo 国果 ; ha 哈 ; hai 害海还骸 ; \
+han 寒罕酣 ; hang 航 ; hao 号皓 ; he 合和核河 ; hei 黑 ; hen 恨痕 ; heng 恒 ; \
+hong 洪红 ; hou 后逅 ; hu 呼护湖虎 ; hua 化华滑画花 ; huai 坏 ; huan 幻环 ; \
+huang 凰徨惶皇荒黄 ; hui 会回悔晖毁灰辉 ; hun 昏混魂 ; huo 伙惑活火获货霍 ; \
+ji 击吉基姬寂寄忌技机极棘殛济激祭稽级绩记迹际集 ; jia 假加家甲迦驾 ; jian 件剑坚建歼监舰茧见间 ; \
+jiao 交教焦脚蛟角 ; jie 戒接界结街解 ; jin 劲尽巾津禁近进金锦 ; jing 井净境惊憬景晶竞精荆镜静 ; \
+jiu 久九就救旧究纠鹫 ; ju 具剧匊局居巨拘据距 ; juan 眷 ; jue 决爵绝觉 ; jun 军君峻郡 ; \
+ka 卡喀 ; kai 凯开 ; kan 勘坎 ; kang 康抗 ; ke 克刻可客珂科 ; keng 坑 ; \
+kong 恐控空 ; kou 口 ; ku 库枯窟 ; kuai 块 ; kuang 狂矿 ; kui 傀溃蝰 ; \
+kun 困 ; kuo 扩阔 ; la 拉 ; lai 来莱赖 ; lan 兰烂 ; lang 廊朗浪狼 ; lao 劳牢老 ; \
+le 乐了勒 ; lei 儡垒泪雷 ; leng 棱 ; li 丽利力历理礼离立莉醴里黎 ; lian 炼练联莲连镰 ; \
+liang 良量 ; liao 疗 ; lie 冽烈猎 ; lin 临凛林 ; ling 令另岭灵羚铃陵零领 ; \
+liu 六流留 ; long 隆龙 ; lou 楼 ; lu 卢炉路陆露鲁 ; luan 乱孪 ; lun 伦论轮 ; \
+luo 洛罗落 ; lv 吕律旅绿 ; ma 玛马 ; mai 埋脉迈麦 ; man 曼满蔓蛮 ; mang 芒 ; \
+mei 梅每没美魅 ; men 门 ; meng 梦猛盟萌 ; mi 密弥秘米蜜迷 ; mian 棉眠面 ; \
+miao 妙庙苗 ; mie 灭 ; min 敏 ; ming 冥名命明暝鸣 ; mo 摩末模茉莫陌魔 ; \
+mu 墓姆幕慕暮木母目穆 ; na 娜拿纳那 ; nai 乃奈 ; nan 南男难 ; nao 脑闹 ; ne 讷 ; \
+nei 内 ; neng 能 ; ni 你妮尼逆 ; nian 年念 ; niang 娘 ; niao 鸟 ; nie 涅 ; \
+ning 狞 ; niu 牛 ; nu 努怒 ; nuan 暖 ; nuo 诺 ; nv 女 ; ou 偶欧瓯鸥 ; \
+pa 帕 ; pan 判叛畔盘 ; pang 彷 ; pao 炮跑 ; pei 配陪 ; pi 匹披毗 ; piao 漂飘 ; \
+pin 拼 ; ping 平 ; po 破魄 ; pu 圃普瀑 ; qi 七其启器奇弃栖漆起骑齐 ; qia 恰 ; \
+qian 前千堑 ; qiang 强抢 ; qiao 巧桥樵 ; qie 茄 ; qin 亲侵寝 ; qing 清轻青 ; \
+qiong 穹 ; qiu 丘球 ; qu 区曲驱 ; quan 全圈拳泉 ; que 雀 ; qun 群 ; \
+ran 染然燃 ; rao 饶 ; re 热 ; ren 人任刃忍 ; ri 日 ; rong 容溶荣蓉融 ; rou 柔鞣 ; \
+ru 入如 ; ruan 软 ; rui 瑞 ; sa 萨 ; sai 塞赛 ; san 三散 ; sang 桑 ; \
+sao 骚 ; se 色 ; sen 森 ; sha 刹杀沙 ; shan 山汕闪 ; shang 上商 ; \
+shao 哨少烧 ; she 射社舍蛇设 ; shen 深神蜃身 ; sheng 圣生盛胜 ; \
+shi 世事使十士失始实室市师式拾施时是湿狮石示誓识试诗逝食驶 ; shou 兽守手狩首 ; shu 书墅属暑术束枢树殊 ; \
+shuang 双 ; shui 水睡 ; shun 瞬 ; shuo 说 ; si 司四寺思斯死私 ; song 松送 ; \
+su 夙宿苏速 ; sui 岁碎随 ; suo 所索锁 ; ta 塔踏 ; tai 台太泰 ; tan 叹坛坦探滩谭 ; \
+tang 唐堂糖 ; tao 讨逃 ; te 特 ; ti 体提缇 ; tian 天恬甜田 ; tiao 挑跳 ; \
+tie 贴铁 ; ting 亭停厅庭廷艇 ; tong 同统通铜 ; tou 头 ; tu 图土徒突途 ; tuan 团 ; \
+tui 褪 ; tuo 托 ; wa 瓦 ; wai 外 ; wan 万完宛挽晚湾玩 ; wang 亡往忘望王 ; \
+wei 为伟伪位卫围威尾未桅维韦 ; wen 文温闻 ; weng 翁 ; wo 我握沃涡 ; wu 乌务坞屋无武污物舞雾 ; \
+xi 习喜希席息曦溪系蜥袭西隙 ; xia 下夏峡狭瑕 ; xian 仙先显现线贤限险 ; xiang 乡向想相祥翔象香 ; \
+xiao 啸小晓校消笑霄 ; xie 协械楔歇胁谢邂邪 ; xin 信心新 ; xing 型形星猩行醒 ; xiong 兄雄 ; \
+xiu 休修袖 ; xu 墟绪虚须 ; xuan 悬 ; xue 学穴血雪 ; xun 寻巡薰训 ; ya 亚娅崖牙雅 ; \
+yan 宴岩延湮演炎焰燕眼研艳验 ; yang 仰养央扬杨羊阳 ; yao 妖摇耀药要遥 ; ye 业叶夜液耶野 ; \
+yi 一义仪伊依异忆意抑易溢翼艺蜴衣议遗 ; yin 因引银阴隐 ; ying 应影映硬英营迎鹰 ; yong 勇永涌用 ; \
+you 优佑友尤幽有游 ; yu 与予于域宇御欲渔狱玉羽育誉语遇预鱼鹬 ; yuan 元原员园圆愿援渊源缘远院 ; \
+yue 悦月约越 ; yun 云运陨 ; zai 再在灾 ; zan 赞 ; zang 葬 ; zao 造遭 ; ze 泽 ; \
+zei 贼 ; zeng 增 ; zha 扎札炸 ; zhai 摘 ; zhan 占战站粘 ; zhang 掌杖章 ; \
+zhao 兆爪 ; zhe 着者著 ; zhen 真镇阵 ; zheng 争征正蒸证 ; zhi 之制址指植止治滞职至致 ; \
+zhong 中种终重钟 ; zhou 周咒宙昼胄舟 ; zhu 主住朱柱注猪竹诛诸驻 ; zhuan 专砖转 ; \
+zhuang 庄装 ; zhui 追 ; zhuo 卓桌 ; zi 兹子紫自 ; zong 总纵 ; zu 族祖组诅 ; \
+zuan 钻 ; zui 最罪 ; zuo 佐作座";
+
+lazy_static::lazy_static! {
+    static ref PINYIN: HashMap<char, &'static str> = READINGS
+        .split(';')
+        .filter_map(|entry| {
+            let (syllable, chars) = entry.trim().split_once(' ')?;
+            Some(chars.chars().map(move |c| (c, syllable)))
+        })
+        .flatten()
+        .collect();
+}
+
+/// The full spelling and the initials of `text`, e.g. `juelongshi` and `jls` for `绝龙诗`.
+/// Letters and digits are kept in both, punctuation and spacing are dropped. `None` when no
+/// character has a reading.
+pub fn spell(text: &str) -> Option<(String, String)> {
+    let mut full = String::new();
+    let mut initials = String::new();
+    let mut read_any = false;
+
+    for c in text.chars() {
+        if let Some(syllable) = PINYIN.get(&c) {
+            read_any = true;
+            full.push_str(syllable);
+            initials.extend(syllable.chars().next());
+        } else if c.is_alphanumeric() {
+            full.push(c);
+            initials.push(c);
+        }
+    }
+
+    read_any.then_some((full, initials))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spells_full_syllables_and_initials() {
+        assert_eq!(
+            spell("绝龙诗战争"),
+            Some(("juelongshizhanzheng".to_owned(), "jlszz".to_owned()))
+        );
+        assert_eq!(
+            spell("伊弗利特歼灭战"),
+            Some(("yifulitejianmiezhan".to_owned(), "yfltjmz".to_owned()))
+        );
+        assert_eq!(spell("Alexander"), None);
+    }
+
+    #[test]
+    fn every_bundled_chinese_name_has_a_reading() {
+        let names = crate::ffxiv::DUTIES
+            .values()
+            .map(|duty| duty.name.zh)
+            .chain(
+                crate::ffxiv::ROULETTES
+                    .values()
+                    .map(|roulette| roulette.name.zh),
+            )
+            .chain(crate::ffxiv::TREASURE_MAPS.values().map(|name| name.zh))
+            .chain(crate::ffxiv::TERRITORY_NAMES.values().map(|name| name.zh));
+
+        for name in names {
+            for c in name
+                .chars()
+                .filter(|c| ('\u{4E00}'..='\u{9FFF}').contains(c))
+            {
+                assert!(PINYIN.contains_key(&c), "no reading for {c} in {name}");
+            }
+        }
+    }
+}
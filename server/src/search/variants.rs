@@ -0,0 +1,119 @@
+//! Traditional Chinese characters and their Simplified forms, as pairs of one Traditional and
+//! one Simplified character. Only one-to-one conversions are listed; characters whose Simplified
+//! form depends on the word (乾, 著, ...) are left alone.
+
+use std::collections::HashMap;
+
+const PAIRS: &str = "\
+萬万 與与 醜丑 專专 業业 叢丛 東东 絲丝 兩两 嚴严 喪丧 個个 豐丰 臨临 為为 麗丽 \
+舉举 麼么 義义 烏乌 樂乐 喬乔 習习 鄉乡 書书 買买 亂乱 爭争 於于 虧亏 雲云 亞亚 \
+產产 畝亩 親亲 億亿 僅仅 從从 侖仑 倉仓 儀仪 們们 價价 眾众 優优 夥伙 會会 傘伞 \
+偉伟 傳传 傷伤 倫伦 偽伪 體体 餘余 傭佣 僉佥 俠侠 侶侣 僥侥 偵侦 側侧 僑侨 儈侩 \
+儕侪 儂侬 儔俦 儼俨 倆俩 儷俪 儉俭 債债 傾倾 僂偻 僨偾 償偿 儻傥 儐傧 儲储 儺傩 \
+兒儿 兌兑 黨党 蘭兰 關关 興兴 茲兹 養养 獸兽 內内 岡冈 冊册 寫写 軍军 農农 馮冯 \
+沖冲 決决 況况 凍冻 淨净 涼凉 減减 湊凑 凜凛 幾几 鳳凤 鳧凫 憑凭 凱凯 擊击 鑿凿 \
+芻刍 劃划 劉刘 則则 剛刚 創创 刪删 別别 劑剂 剮剐 劍剑 剝剥 劇剧 勸劝 辦办 務务 \
+動动 勵励 勁劲 勞劳 勢势 勳勋 勻匀 匱匮 區区 醫医 華华 協协 單单 賣卖 盧卢 鹵卤 \
+衛卫 卻却 廠厂 廳厅 曆历 厲厉 壓压 厭厌 廁厕 廂厢 廈厦 廚厨 廄厩 廝厮 縣县 參参 \
+雙双 發发 變变 敘叙 疊叠 葉叶 號号 嘆叹 嘰叽 籲吁 後后 嚇吓 呂吕 嗎吗 噸吨 聽听 \
+啟启 吳吴 嘔呕 唄呗 員员 嗆呛 嗚呜 詠咏 嚨咙 響响 啞哑 噠哒 嘩哗 喲哟 嘮唠 嗩唢 \
+喚唤 嘖啧 嗇啬 囉啰 嘯啸 噴喷 嘍喽 囁嗫 噯嗳 噓嘘 嚶嘤 囑嘱 嚕噜 團团 園园 囪囱 \
+圍围 圇囵 國国 圖图 圓圆 聖圣 場场 壞坏 塊块 堅坚 壇坛 壩坝 塢坞 墳坟 墜坠 壟垄 \
+壘垒 墾垦 墊垫 塹堑 墮堕 牆墙 壯壮 聲声 殼壳 壺壶 處处 備备 復复 夠够 頭头 誇夸 \
+夾夹 奪夺 奮奋 獎奖 奧奥 妝妆 婦妇 媽妈 嫵妩 婁娄 嬌娇 娛娱 嫻娴 嬰婴 嬸婶 孫孙 \
+學学 孿孪 寧宁 寶宝 實实 寵宠 審审 憲宪 宮宫 寬宽 賓宾 寢寝 對对 尋寻 導导 壽寿 \
+將将 爾尔 塵尘 堯尧 嘗尝 屍尸 盡尽 層层 屆届 屬属 屢屡 嶼屿 歲岁 豈岂 嶇岖 崗岗 \
+嵐岚 島岛 嶺岭 嶽岳 峽峡 崢峥 巒峦 嶄崭 嶸嵘 巔巅 鞏巩 幣币 帥帅 師师 帳帐 簾帘 \
+幟帜 帶带 幀帧 幫帮 幗帼 幹干 並并 廣广 莊庄 慶庆 廬庐 庫库 應应 廟庙 龐庞 廢废 \
+開开 異异 棄弃 張张 彌弥 彎弯 彈弹 強强 歸归 當当 錄录 彥彦 徹彻 徑径 憶忆 懺忏 \
+憂忧 懷怀 態态 慫怂 悵怅 愴怆 憐怜 總总 戀恋 懇恳 惡恶 惱恼 悅悦 懸悬 憫悯 驚惊 \
+懼惧 慘惨 懲惩 憊惫 愜惬 慚惭 慣惯 憤愤 願愿 懾慑 懶懒 戲戏 戰战 戶户 紮扎 撲扑 \
+託托 執执 擴扩 掃扫 揚扬 擾扰 撫抚 拋抛 摳抠 搶抢 護护 報报 擔担 擬拟 攏拢 揀拣 \
+擁拥 攔拦 擰拧 撥拨 擇择 掛挂 摯挚 攣挛 撻挞 挾挟 撓挠 擋挡 掙挣 擠挤 揮挥 撈捞 \
+損损 撿捡 換换 搗捣 據据 擄掳 擲掷 撣掸 摻掺 攬揽 攙搀 擱搁 摟搂 攪搅 攜携 攝摄 \
+擺摆 搖摇 攤摊 撐撑 攆撵 擷撷 擼撸 攢攒 敵敌 斂敛 數数 齋斋 斕斓 鬥斗 斬斩 斷断 \
+時时 曠旷 曇昙 晝昼 顯显 晉晋 曬晒 曉晓 暈晕 暉晖 暫暂 曖暧 術术 樸朴 機机 殺杀 \
+雜杂 權权 條条 來来 楊杨 傑杰 極极 構构 樞枢 棗枣 櫃柜 檸柠 柵栅 標标 棧栈 棟栋 \
+欄栏 樹树 棲栖 樣样 楨桢 檔档 橋桥 樺桦 槳桨 樁桩 夢梦 檢检 槓杠 樓楼 欖榄 櫚榈 \
+檻槛 檳槟 橫横 櫻樱 櫥橱 櫓橹 簷檐 歡欢 歐欧 殲歼 殘残 殯殡 毆殴 毀毁 畢毕 斃毙 \
+氈毡 氣气 氫氢 漢汉 湯汤 溝沟 沒没 瀝沥 淪沦 滄沧 滬沪 濘泞 淚泪 瀉泻 潑泼 澤泽 \
+潔洁 灑洒 窪洼 淺浅 漿浆 澆浇 濁浊 測测 濟济 瀏浏 渾浑 濃浓 濤涛 澇涝 漣涟 渦涡 \
+滌涤 潤润 漲涨 澀涩 澱淀 淵渊 漬渍 漸渐 漁渔 滲渗 溫温 灣湾 濕湿 潰溃 濺溅 滿满 \
+濾滤 濫滥 濱滨 灘滩 瀟潇 潛潜 瀾澜 瀨濑 滅灭 燈灯 靈灵 災灾 燦灿 爐炉 燉炖 點点 \
+煉炼 熾炽 爍烁 爛烂 燭烛 煙烟 煩烦 燒烧 燴烩 燙烫 燼烬 熱热 煥焕 愛爱 爺爷 牘牍 \
+犛牦 牽牵 犧牺 犢犊 狀状 猶犹 狽狈 獰狞 獨独 狹狭 獅狮 猙狰 獄狱 獵猎 獼猕 豬猪 \
+貓猫 獻献 獺獭 瑪玛 環环 現现 璽玺 瓏珑 璉琏 瑣琐 瓊琼 瑤瑶 甕瓮 電电 畫画 暢畅 \
+療疗 瘍疡 瘡疮 瘋疯 癢痒 癆痨 瘓痪 癇痫 癡痴 瘻瘘 癟瘪 癱瘫 癮瘾 癩癞 癬癣 癲癫 \
+皚皑 皺皱 盞盏 鹽盐 監监 蓋盖 盜盗 盤盘 矚瞩 睜睁 瞼睑 瞞瞒 矯矫 礦矿 碼码 磚砖 \
+硯砚 礪砺 礫砾 礎础 碩硕 確确 礙碍 禮礼 禍祸 禱祷 禪禅 離离 禿秃 種种 積积 稱称 \
+穢秽 穩稳 窮穷 竊窃 竅窍 窯窑 竄窜 窩窝 窺窥 豎竖 競竞 筆笔 筍笋 箋笺 籠笼 築筑 \
+篩筛 箏筝 籌筹 簽签 簡简 簫箫 籃篮 籬篱 類类 糞粪 糧粮 緊紧 糾纠 紀纪 約约 紅红 \
+紋纹 納纳 紐纽 純纯 紗纱 紙纸 級级 紛纷 紡纺 細细 紳绅 紹绍 終终 組组 絆绊 結结 \
+絕绝 絞绞 絡络 絢绚 給给 絨绒 統统 絹绢 綁绑 繡绣 經经 綜综 綠绿 綢绸 維维 綱纲 \
+網网 綴缀 綸纶 綺绮 綻绽 綽绰 綾绫 綿绵 緒绪 線线 緝缉 緞缎 締缔 緣缘 編编 緩缓 \
+緬缅 緯纬 練练 緻致 縈萦 縫缝 縮缩 縱纵 縷缕 績绩 繃绷 織织 繞绕 繩绳 繪绘 繫系 \
+繭茧 繳缴 繼继 繽缤 續续 纏缠 纖纤 纜缆 罰罚 罷罢 羅罗 羈羁 翹翘 聳耸 恥耻 聶聂 \
+聾聋 職职 聯联 聰聪 肅肃 腸肠 膚肤 腎肾 腫肿 脹胀 脅胁 膽胆 勝胜 朧胧 脛胫 膠胶 \
+脈脉 臍脐 腦脑 膿脓 腳脚 脫脱 臉脸 臘腊 膩腻 騰腾 舊旧 艙舱 艦舰 艱艰 豔艳 藝艺 \
+節节 蕪芜 蘆芦 葦苇 蒼苍 蘋苹 莖茎 薦荐 莢荚 蕎荞 薺荠 蕩荡 榮荣 葷荤 熒荧 蔭荫 \
+藥药 萊莱 蓮莲 獲获 瑩莹 鶯莺 蘿萝 螢萤 營营 蕭萧 薩萨 蔥葱 蔣蒋 藍蓝 薔蔷 藹蔼 \
+蘊蕴 蘚藓 虜虏 慮虑 蟲虫 雖虽 蝦虾 蝕蚀 蟻蚁 螞蚂 蠶蚕 蠔蚝 蠱蛊 蠻蛮 蟄蛰 蛻蜕 \
+蝸蜗 蠟蜡 蠅蝇 蟬蝉 蠍蝎 銜衔 補补 襯衬 襖袄 襪袜 襲袭 裝装 襠裆 褲裤 褸褛 襤褴 \
+見见 觀观 規规 覓觅 視视 覽览 覺觉 覬觊 覲觐 覷觑 觸触 計计 訂订 認认 譏讥 討讨 \
+讓让 訓训 議议 訊讯 記记 講讲 諱讳 訝讶 許许 訛讹 論论 訟讼 諷讽 設设 訪访 訣诀 \
+證证 評评 詛诅 識识 詐诈 訴诉 診诊 詞词 譯译 試试 詩诗 詰诘 誠诚 話话 誕诞 詭诡 \
+詢询 該该 詳详 詫诧 誡诫 誣诬 語语 誤误 誘诱 誨诲 說说 誦诵 請请 諸诸 諾诺 讀读 \
+誹诽 課课 誰谁 調调 諒谅 談谈 誼谊 謀谋 諜谍 謊谎 諧谐 謁谒 謂谓 諭谕 諮谘 諺谚 \
+謎谜 謝谢 謠谣 謙谦 謹谨 謬谬 譜谱 譴谴 貝贝 貞贞 負负 貢贡 財财 責责 賢贤 敗败 \
+賬账 貨货 質质 販贩 貪贪 貧贫 貶贬 購购 貯贮 貫贯 貳贰 賤贱 貼贴 貴贵 貸贷 貿贸 \
+費费 賀贺 賊贼 賈贾 賄贿 賃赁 賂赂 贓赃 資资 賦赋 賭赌 贖赎 賞赏 賜赐 賠赔 賴赖 \
+贅赘 賺赚 賽赛 贈赠 贊赞 贏赢 趙赵 趕赶 趨趋 躍跃 踐践 蹺跷 踴踊 蹤踪 軀躯 車车 \
+軌轨 軒轩 轉转 輪轮 軟软 轟轰 軸轴 輕轻 載载 轎轿 較较 輔辅 輛辆 輩辈 輝辉 輯辑 \
+輸输 轄辖 輾辗 轍辙 辭辞 辯辩 邊边 遼辽 達达 遷迁 過过 邁迈 運运 還还 這这 進进 \
+遠远 違违 連连 遲迟 適适 選选 遜逊 遞递 邏逻 遺遗 遙遥 鄧邓 郵邮 鄰邻 鬱郁 鄭郑 \
+醞酝 醬酱 釀酿 釋释 裏里 鑒鉴 針针 釘钉 釣钓 鈣钙 鈍钝 鈔钞 鈴铃 鉛铅 鉤钩 鉗钳 \
+銀银 銅铜 銘铭 鋁铝 鋒锋 鋤锄 鋪铺 錦锦 錫锡 錯错 錶表 錢钱 鍋锅 錐锥 錘锤 鍵键 \
+鋸锯 錨锚 鍍镀 鍊炼 鍛锻 鎖锁 鎧铠 鎮镇 鏈链 鏡镜 鏢镖 鏽锈 鐘钟 鐮镰 鐵铁 鑄铸 \
+鑑鉴 鑰钥 鑲镶 鑼锣 鑽钻 長长 門门 閃闪 閉闭 問问 闖闯 閑闲 間间 閘闸 鬧闹 閨闺 \
+聞闻 閥阀 閣阁 閱阅 閻阎 闆板 闊阔 闡阐 闢辟 隊队 陽阳 陰阴 陣阵 階阶 際际 陸陆 \
+陳陈 隕陨 險险 隨随 隱隐 隸隶 難难 雛雏 靂雳 霧雾 靄霭 靜静 韓韩 韌韧 韻韵 頁页 \
+頂顶 項项 順顺 須须 頑顽 顧顾 頓顿 頒颁 頌颂 預预 顱颅 領领 頗颇 頰颊 頻频 頹颓 \
+穎颖 顆颗 題题 顏颜 額额 顛颠 顫颤 顰颦 風风 颱台 颳刮 飄飘 飆飙 飛飞 飢饥 飯饭 \
+飲饮 飼饲 飽饱 飾饰 餃饺 餅饼 餌饵 餓饿 餡馅 館馆 餵喂 饅馒 饑饥 饒饶 饞馋 馬马 \
+馭驭 馱驮 馳驰 馴驯 駁驳 駐驻 駒驹 駕驾 駛驶 駝驼 駭骇 駱骆 駿骏 騎骑 騙骗 騷骚 \
+騾骡 驅驱 驕骄 驗验 驛驿 驟骤 驢驴 骯肮 髏髅 髒脏 鬆松 鬍胡 鬚须 鬢鬓 魘魇 魚鱼 \
+魯鲁 鮮鲜 鯉鲤 鯊鲨 鯨鲸 鱗鳞 鱷鳄 鳥鸟 鳴鸣 鴉鸦 鴨鸭 鴻鸿 鵝鹅 鵬鹏 鶴鹤 鷹鹰 \
+鷗鸥 鸚鹦 鹹咸 鹼碱 麥麦 黃黄 齊齐 齒齿 齡龄 齣出 龍龙 龔龚 龕龛 龜龟 臺台 檯台 \
+製制 遊游 裡里 麵面 鍾钟 髮发 週周 佔占 纔才 採采 係系 僕仆 噁恶 嚐尝 彆别 彿佛 \
+徵征 慾欲 捨舍 洩泄 濛蒙 瀰弥 睏困 祕秘 稜棱 穀谷 籤签 糰团 衝冲 讚赞 迴回 閒闲 \
+隻只 噹当 暱昵";
+
+lazy_static::lazy_static! {
+    static ref SIMPLIFIED: HashMap<char, char> = PAIRS
+        .split_whitespace()
+        .filter_map(|pair| {
+            let mut chars = pair.chars();
+            Some((chars.next()?, chars.next()?))
+        })
+        .collect();
+}
+
+/// The Simplified form of `c`, or `c` itself.
+pub fn simplified(c: char) -> char {
+    SIMPLIFIED.get(&c).copied().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_are_one_step_conversions() {
+        for pair in PAIRS.split_whitespace() {
+            let chars = pair.chars().collect::<Vec<_>>();
+            assert_eq!(chars.len(), 2, "{pair}");
+            assert_ne!(chars[0], chars[1], "{pair}");
+            assert_eq!(simplified(chars[1]), chars[1], "{pair} converts again");
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
+use chrono::{DateTime, Utc};
 use sestring::SeString;
 use serde::{Deserialize, Deserializer};
 use crate::ffxiv::Language;
-use crate::listing::{DutyCategory, DutyType};
+use crate::listing::{ConditionFlags, DutyCategory, DutyType, ObjectiveFlags};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct CachedStatistics {
@@ -11,6 +12,15 @@ pub struct CachedStatistics {
     pub seven_days: Statistics,
 }
 
+/// One duty's statistics, cached per duty for as long as the global ones.
+#[derive(Debug, Clone)]
+pub struct CachedDutyStatistics {
+    /// `count` is the all-time number of listings.
+    pub duty: DutyInfo,
+    pub all_time: DutyStatistics,
+    pub seven_days: DutyStatistics,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Aliases {
     #[serde(deserialize_with = "alias_de")]
@@ -63,6 +73,144 @@ impl Statistics {
     }
 }
 
+/// `(duty_type, category, duty)`, the same key [`DutyInfo`] is grouped by.
+pub type DutyKey = (u8, u32, u16);
+
+/// Facets for the listings of a single duty.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DutyStatistics {
+    pub count: Vec<Count>,
+    /// Listings created per UTC day, oldest first.
+    pub volume: Vec<VolumeInfo>,
+    pub hours: Vec<HourInfo>,
+    pub days: Vec<DayInfo>,
+    /// Listings per `min_item_level`, lowest first.
+    pub item_levels: Vec<ItemLevelInfo>,
+    /// Listings per raw objective bit set.
+    pub objectives: Vec<FlagsInfo>,
+    /// Listings per raw condition bit set.
+    pub conditions: Vec<FlagsInfo>,
+    pub beginners_welcome: Vec<Count>,
+    /// Listings whose last upload had every slot filled.
+    pub filled: Vec<FillInfo>,
+}
+
+impl DutyStatistics {
+    pub fn num_listings(&self) -> usize {
+        self.count.first().map(|count| count.count).unwrap_or_default()
+    }
+
+    /// The lower median, so it is always a level some listing actually asked for.
+    pub fn median_min_item_level(&self) -> Option<u16> {
+        let half = self.num_listings().div_ceil(2);
+        let mut seen = 0;
+        self.item_levels.iter().find_map(|info| {
+            seen += info.count;
+            (seen >= half && seen > 0).then_some(info.level)
+        })
+    }
+
+    pub fn objective_counts(&self) -> Vec<FlagCount> {
+        flag_counts(
+            &self.objectives,
+            &[ObjectiveFlags::DUTY_COMPLETION, ObjectiveFlags::PRACTICE, ObjectiveFlags::LOOT].map(|flag| flag.bits()),
+        )
+    }
+
+    pub fn condition_counts(&self) -> Vec<FlagCount> {
+        flag_counts(
+            &self.conditions,
+            &[
+                ConditionFlags::DUTY_COMPLETE,
+                ConditionFlags::DUTY_INCOMPLETE,
+                ConditionFlags::DUTY_COMPLETE_WEEKLY_REWARD_UNCLAIMED,
+            ]
+            .map(|flag| flag.bits()),
+        )
+    }
+
+    /// Between 0 and 1; 0 when there are no listings.
+    pub fn beginners_welcome_share(&self) -> f64 {
+        let welcome = self.beginners_welcome.first().map(|count| count.count).unwrap_or_default();
+        match self.num_listings() {
+            0 => 0.0,
+            total => welcome as f64 / total as f64,
+        }
+    }
+
+    pub fn num_filled(&self) -> usize {
+        self.filled.first().map(|info| info.count).unwrap_or_default()
+    }
+
+    /// From `created_at` to the last upload, over the listings that filled. Not the time to fill:
+    /// a full listing keeps its `updated_at` moving for as long as it stays uploaded.
+    pub fn average_filled_lifetime_seconds(&self) -> Option<f64> {
+        self.filled
+            .first()
+            .filter(|info| info.count > 0)
+            .map(|info| info.average_seconds)
+    }
+
+    pub fn average_filled_lifetime_minutes(&self) -> String {
+        match self.average_filled_lifetime_seconds() {
+            Some(seconds) => format!("{:.1}", seconds / 60.0),
+            None => "-".into(),
+        }
+    }
+
+    pub fn beginners_welcome_percent(&self) -> String {
+        format!("{:.1}", self.beginners_welcome_share() * 100.0)
+    }
+}
+
+fn flag_counts(infos: &[FlagsInfo], flags: &[u32]) -> Vec<FlagCount> {
+    flags
+        .iter()
+        .map(|&flag| FlagCount {
+            flag,
+            count: infos
+                .iter()
+                .filter(|info| info.flags & flag != 0)
+                .map(|info| info.count)
+                .sum(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VolumeInfo {
+    #[serde(rename = "_id", with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub day: DateTime<Utc>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemLevelInfo {
+    #[serde(rename = "_id")]
+    pub level: u16,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FlagsInfo {
+    #[serde(rename = "_id")]
+    pub flags: u32,
+    pub count: usize,
+}
+
+/// How many listings carry one objective or condition flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagCount {
+    pub flag: u32,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FillInfo {
+    pub count: usize,
+    pub average_seconds: f64,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Count {
     pub count: usize,
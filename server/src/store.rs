@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+
+use crate::{
+    config::UploaderToken,
+    listing::{PartyFinderListing, SearchAreaFlags},
+    listing_container::QueriedListing,
+    stats::{DutyKey, DutyStatistics, Statistics},
+    webhooks::{Delivery, Subscription},
+};
+
+mod memory;
+mod mongo;
+
+pub use self::{memory::MemoryStore, mongo::MongoStore};
+
+pub const ACTIVE_LISTING_WINDOW_HOURS: i64 = 2;
+/// A listing that has not been re-uploaded within this window is treated as gone.
+pub const ACTIVE_UPDATE_WINDOW: Duration = Duration::minutes(5);
+const UPDATED_MINUTE_BIN: Duration = Duration::minutes(5);
+
+/// Canonical upsert key: `(listing.id, last_server_restart, created_world)`.
+pub type ListingIdentity = (u64, i64, u16);
+
+pub fn listing_identity(listing: &PartyFinderListing) -> ListingIdentity {
+    (listing.id, listing.last_server_restart, listing.created_world)
+}
+
+/// Keeps each player's most recently updated listing, in the order `listings` were in.
+pub(crate) fn keep_latest_per_player(listings: &mut Vec<QueriedListing>) {
+    let mut latest = HashMap::<u32, DateTime<Utc>>::new();
+    for listing in listings.iter() {
+        let updated_at = latest.entry(listing.listing.content_id_lower).or_insert(listing.updated_at);
+        *updated_at = (*updated_at).max(listing.updated_at);
+    }
+    listings.retain(|listing| {
+        // a tie on `updated_at` keeps the first of the player's newest rows
+        latest.get(&listing.listing.content_id_lower) == Some(&listing.updated_at)
+            && latest.remove(&listing.listing.content_id_lower).is_some()
+    });
+}
+
+pub fn active_listing_cutoff(now: DateTime<Utc>) -> DateTime<Utc> {
+    // Legacy coexistence is bounded to the active-listing window; once a truncated historical row
+    // ages out of this window, active reads stop surfacing it.
+    now - Duration::hours(ACTIVE_LISTING_WINDOW_HOURS)
+}
+
+/// The 5-minute bucket listings are grouped by when ordering by freshness.
+pub fn updated_minute(updated_at: DateTime<Utc>) -> DateTime<Utc> {
+    updated_at
+        .duration_trunc(UPDATED_MINUTE_BIN)
+        .unwrap_or(updated_at)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    Inserted,
+    Updated,
+    /// The stored listing was identical to the upload; only `updated_at` moved.
+    Unchanged,
+}
+
+/// Pushdown filters for [`ListingStore::active_listings`]. Empty lists leave that field
+/// unconstrained; non-empty lists match any of their members.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListingFilter {
+    pub created_world_ids: Vec<u32>,
+    pub home_world_ids: Vec<u32>,
+    pub category_ids: Vec<u32>,
+    pub duty_ids: Vec<u32>,
+    /// Matches when any slot accepts every bit of any of these values.
+    pub accepted_slot_bits: Vec<u64>,
+    /// Each group matches when one of the first `slots_available` slots is unfilled and accepts
+    /// every bit of any of the group's values. Every group must match.
+    pub open_slot_bits: Vec<Vec<u64>>,
+    pub duty_type_ids: Vec<u32>,
+    pub loot_rule_ids: Vec<u32>,
+    pub min_item_level_gte: Option<u16>,
+    pub min_item_level_lte: Option<u16>,
+    /// Matches when the objective flags share any bit with this mask.
+    pub objective_bits_any: Option<u32>,
+    /// Matches when the condition flags share any bit with this mask.
+    pub condition_bits_any: Option<u32>,
+    pub beginners_welcome: Option<bool>,
+    pub one_player_per_job: Option<bool>,
+    pub cross_world: Option<bool>,
+    /// Matches when `slots_available` minus the filled slots is at least this.
+    pub slots_open_gte: Option<u32>,
+    /// Keep only the most recently updated listing per `content_id_lower`.
+    pub latest_per_player: bool,
+}
+
+/// Position after which [`ListingStore::history`] resumes: the last row of the previous page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryCursor {
+    pub updated_at: DateTime<Utc>,
+    pub identity: ListingIdentity,
+}
+
+impl HistoryCursor {
+    pub fn of(listing: &QueriedListing) -> Self {
+        Self {
+            updated_at: listing.updated_at,
+            identity: listing_identity(&listing.listing),
+        }
+    }
+
+    /// Whether `listing` sorts strictly after this cursor in history order.
+    pub fn precedes(&self, listing: &QueriedListing) -> bool {
+        (listing.updated_at, listing_identity(&listing.listing)) < (self.updated_at, self.identity)
+    }
+}
+
+/// A page of public rows that were live at some point in `[from, to)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// `latest_per_player` is ignored; history keeps every row.
+    pub filter: ListingFilter,
+    pub after: Option<HistoryCursor>,
+    pub limit: usize,
+}
+
+impl ListingFilter {
+    pub fn matches(&self, listing: &PartyFinderListing) -> bool {
+        (self.created_world_ids.is_empty()
+            || self.created_world_ids.contains(&u32::from(listing.created_world)))
+            && (self.home_world_ids.is_empty()
+                || self.home_world_ids.contains(&u32::from(listing.home_world)))
+            && (self.category_ids.is_empty()
+                || self.category_ids.contains(&listing.category.as_u32()))
+            && (self.duty_ids.is_empty() || self.duty_ids.contains(&u32::from(listing.duty)))
+            && (self.accepted_slot_bits.is_empty()
+                || listing.slots.iter().any(|slot| {
+                    self.accepted_slot_bits
+                        .iter()
+                        .any(|bits| slot.accepting.bits() & bits == *bits)
+                }))
+            && self.open_slot_bits.iter().all(|group| {
+                listing
+                    .slots
+                    .iter()
+                    .take(usize::from(listing.slots_available))
+                    .enumerate()
+                    .any(|(index, slot)| {
+                        listing.jobs_present.get(index).is_none_or(|job| *job == 0)
+                            && group.iter().any(|bits| slot.accepting.bits() & bits == *bits)
+                    })
+            })
+            && (self.duty_type_ids.is_empty()
+                || self.duty_type_ids.contains(&u32::from(listing.duty_type.as_u8())))
+            && (self.loot_rule_ids.is_empty()
+                || self.loot_rule_ids.contains(&listing.loot_rules.bits()))
+            && self.min_item_level_gte.is_none_or(|level| listing.min_item_level >= level)
+            && self.min_item_level_lte.is_none_or(|level| listing.min_item_level <= level)
+            && self.objective_bits_any.is_none_or(|bits| listing.objective.bits() & bits != 0)
+            && self.condition_bits_any.is_none_or(|bits| listing.conditions.bits() & bits != 0)
+            && self.beginners_welcome.is_none_or(|welcome| listing.beginners_welcome == welcome)
+            && self.one_player_per_job.is_none_or(|set| {
+                listing.search_area.contains(SearchAreaFlags::ONE_PLAYER_PER_JOB) == set
+            })
+            && self.cross_world.is_none_or(|set| listing.is_cross_world() == set)
+            && self.slots_open_gte.is_none_or(|open| {
+                usize::from(listing.slots_available).saturating_sub(listing.slots_filled()) >= open as usize
+            })
+    }
+}
+
+/// Where listings are persisted and how the read paths query them.
+///
+/// Every implementation applies the same active-listing semantics: rows updated within
+/// [`ACTIVE_LISTING_WINDOW_HOURS`], not private, with `time_left >= 0` and re-uploaded within
+/// [`ACTIVE_UPDATE_WINDOW`].
+#[async_trait]
+pub trait ListingStore: Send + Sync {
+    /// Inserts or replaces the listing under its [`ListingIdentity`], stamping `updated_at` and
+    /// the uploader label.
+    async fn upsert(
+        &self,
+        listing: &PartyFinderListing,
+        uploader: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<UpsertOutcome>;
+
+    /// Upserts a whole batch, reporting one result per listing in order. Listings sharing an
+    /// identity are applied in order, so the last one wins. Backends that can should do this in
+    /// a single round-trip; the default upserts one listing at a time.
+    async fn upsert_many(
+        &self,
+        listings: &[PartyFinderListing],
+        uploader: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Vec<Result<UpsertOutcome>> {
+        let mut results = Vec::with_capacity(listings.len());
+        for listing in listings {
+            results.push(self.upsert(listing, uploader, now).await);
+        }
+        results
+    }
+
+    /// Active listings matching `filter`, newest `updated_minute` first, then by category
+    /// descending and least time left.
+    async fn active_listings(&self, filter: &ListingFilter) -> Result<Vec<QueriedListing>>;
+
+    /// Every active row sharing `id`, most recently updated first.
+    async fn listings_by_id(&self, id: u64) -> Result<Vec<QueriedListing>>;
+
+    /// Public rows created before `query.to` and last updated at or after `query.from`, ignoring
+    /// the active window. Newest `updated_at` first, ties broken by [`ListingIdentity`]
+    /// descending. `time_left` is the remaining time reported by the row's last upload.
+    async fn history(&self, query: &HistoryQuery) -> Result<Vec<QueriedListing>>;
+
+    /// Aggregates public listings created at or after `since`, or all of them.
+    async fn statistics(&self, since: Option<DateTime<Utc>>) -> Result<Statistics>;
+
+    /// Aggregates the public listings of one duty created at or after `since`, or all of them.
+    async fn duty_statistics(
+        &self,
+        duty: DutyKey,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<DutyStatistics>;
+
+    /// Looks up a stored uploader by its bearer token, whether or not it is enabled.
+    async fn find_uploader(&self, token: &str) -> Result<Option<UploaderToken>>;
+
+    /// Saves a new webhook subscription.
+    async fn insert_subscription(&self, subscription: &Subscription) -> Result<()>;
+
+    /// Every webhook subscription, oldest first.
+    async fn subscriptions(&self) -> Result<Vec<Subscription>>;
+
+    async fn subscription(&self, id: &str) -> Result<Option<Subscription>>;
+
+    /// Removes a subscription and its delivery log; `false` when there was no such subscription.
+    async fn delete_subscription(&self, id: &str) -> Result<bool>;
+
+    /// Turns deliveries to a subscription on or off. Enabling it also clears its failure streak.
+    async fn set_subscription_enabled(&self, id: &str, enabled: bool) -> Result<Option<Subscription>>;
+
+    /// Logs a finished delivery and updates the subscription's failure streak: a success resets
+    /// it, a failure extends it and disables the subscription once the streak reaches
+    /// `disable_after` (`0` never disables). Deliveries to deleted subscriptions are dropped.
+    async fn record_delivery(
+        &self,
+        delivery: &Delivery,
+        disable_after: u32,
+    ) -> Result<Option<Subscription>>;
+
+    /// Up to `limit` of a subscription's logged deliveries, newest first. Backends only keep a
+    /// bounded log, so old deliveries eventually disappear.
+    async fn deliveries(&self, subscription_id: &str, limit: usize) -> Result<Vec<Delivery>>;
+
+    /// Round-trips to the backend to check it is reachable. In-process backends always are.
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// One-off setup such as creating indexes. Retried in the background until it succeeds, so
+    /// it must be idempotent; the store is usable, if slower, before it has run.
+    async fn prepare(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,841 @@
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap, VecDeque},
+    hash::Hash,
+    sync::RwLock,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+
+use crate::{
+    config::UploaderToken,
+    listing::{PartyFinderListing, SearchAreaFlags},
+    listing_container::{ListingContainer, QueriedListing},
+    stats::{
+        Alias, Count, DayInfo, DutyInfo, DutyKey, DutyStatistics, FillInfo, FlagsInfo, HostInfo,
+        HostInfoInfo, HourInfo, ItemLevelInfo, Statistics, VolumeInfo,
+    },
+    webhooks::{Delivery, Subscription},
+};
+
+use super::{
+    active_listing_cutoff, keep_latest_per_player, listing_identity, updated_minute, HistoryQuery, ListingFilter,
+    ListingIdentity, ListingStore, UpsertOutcome, ACTIVE_UPDATE_WINDOW,
+};
+
+/// Number of players listed per world on the stats page.
+const TOP_HOSTS_PER_WORLD: usize = 15;
+/// Deliveries kept per webhook subscription.
+const DELIVERY_LOG_LEN: usize = 100;
+
+/// Keeps every upload in process and evaluates the active-listing rules in Rust.
+///
+/// Nothing is ever evicted, so this backend is meant for local development and tests rather than
+/// a long-running deployment.
+#[derive(Default)]
+pub struct MemoryStore {
+    listings: RwLock<HashMap<ListingIdentity, ListingContainer>>,
+    uploaders: RwLock<HashMap<String, UploaderToken>>,
+    subscriptions: RwLock<Vec<Subscription>>,
+    deliveries: RwLock<HashMap<String, VecDeque<Delivery>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds the stand-in for the `rpf.uploaders` collection.
+    #[cfg(test)]
+    pub fn with_uploaders(uploaders: impl IntoIterator<Item = UploaderToken>) -> Self {
+        Self {
+            uploaders: RwLock::new(
+                uploaders
+                    .into_iter()
+                    .map(|uploader| (uploader.token.clone(), uploader))
+                    .collect(),
+            ),
+            ..Self::default()
+        }
+    }
+
+    /// The uploader label stored on the row with `identity`, if the row exists.
+    #[cfg(test)]
+    pub fn uploader(&self, identity: ListingIdentity) -> Option<Option<String>> {
+        self.listings
+            .read()
+            .unwrap()
+            .get(&identity)
+            .map(|container| container.uploader.clone())
+    }
+
+    fn active_at(
+        &self,
+        now: DateTime<Utc>,
+        keep: impl Fn(&PartyFinderListing) -> bool,
+    ) -> Vec<QueriedListing> {
+        self.listings
+            .read()
+            .unwrap()
+            .values()
+            .filter(|container| keep(&container.listing))
+            .filter_map(|container| query_active(container, now))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ListingStore for MemoryStore {
+    async fn upsert(
+        &self,
+        listing: &PartyFinderListing,
+        uploader: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<UpsertOutcome> {
+        let mut listings = self.listings.write().unwrap();
+
+        Ok(match listings.entry(listing_identity(listing)) {
+            Entry::Occupied(mut entry) => {
+                let container = entry.get_mut();
+                let outcome = if container.listing == *listing {
+                    UpsertOutcome::Unchanged
+                } else {
+                    UpsertOutcome::Updated
+                };
+                container.updated_at = now;
+                container.listing = listing.clone();
+                container.uploader = uploader.map(str::to_owned);
+                outcome
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(ListingContainer {
+                    created_at: now,
+                    updated_at: now,
+                    listing: listing.clone(),
+                    uploader: uploader.map(str::to_owned),
+                });
+                UpsertOutcome::Inserted
+            }
+        })
+    }
+
+    async fn active_listings(&self, filter: &ListingFilter) -> Result<Vec<QueriedListing>> {
+        let mut active = self.active_at(Utc::now(), |listing| filter.matches(listing));
+
+        if filter.latest_per_player {
+            keep_latest_per_player(&mut active);
+        }
+
+        active.sort_by(|a, b| {
+            b.updated_minute
+                .cmp(&a.updated_minute)
+                .then_with(|| b.listing.category.as_u32().cmp(&a.listing.category.as_u32()))
+                .then_with(|| a.time_left.total_cmp(&b.time_left))
+                // the hash map has no order of its own; keep equal-ranked listings stable
+                .then_with(|| a.listing.id.cmp(&b.listing.id))
+        });
+
+        Ok(active)
+    }
+
+    async fn listings_by_id(&self, id: u64) -> Result<Vec<QueriedListing>> {
+        let mut documents = self.active_at(Utc::now(), |listing| listing.id == id);
+        documents.sort_by_key(|document| std::cmp::Reverse(document.updated_at));
+        Ok(documents)
+    }
+
+    async fn history(&self, query: &HistoryQuery) -> Result<Vec<QueriedListing>> {
+        let mut documents = self
+            .listings
+            .read()
+            .unwrap()
+            .values()
+            .filter(|container| {
+                container.created_at < query.to
+                    && container.updated_at >= query.from
+                    && !container.listing.search_area.contains(SearchAreaFlags::PRIVATE)
+                    && query.filter.matches(&container.listing)
+            })
+            .map(query_historical)
+            .filter(|document| query.after.is_none_or(|after| after.precedes(document)))
+            .collect::<Vec<_>>();
+
+        documents.sort_by_key(|document| {
+            std::cmp::Reverse((document.updated_at, listing_identity(&document.listing)))
+        });
+        documents.truncate(query.limit);
+        Ok(documents)
+    }
+
+    async fn statistics(&self, since: Option<DateTime<Utc>>) -> Result<Statistics> {
+        let listings = self.listings.read().unwrap();
+        let public = listings
+            .values()
+            .filter(|container| !container.listing.search_area.contains(SearchAreaFlags::PRIVATE))
+            .filter(|container| since.is_none_or(|since| container.created_at >= since))
+            .collect::<Vec<_>>();
+
+        let mut duties = HashMap::new();
+        let mut hosts = HashMap::<u32, HashMap<u32, usize>>::new();
+        let mut hours = BTreeMap::new();
+        let mut days = BTreeMap::new();
+
+        for container in &public {
+            let listing = &container.listing;
+            *duties
+                .entry((listing.duty_type as u8, listing.category.as_u32(), listing.duty))
+                .or_default() += 1;
+            *hosts
+                .entry(u32::from(listing.created_world))
+                .or_default()
+                .entry(listing.content_id_lower)
+                .or_default() += 1;
+            *hours.entry(container.created_at.hour() as u8).or_default() += 1;
+            // `$dayOfWeek` numbering: 1 is Sunday
+            *days
+                .entry(container.created_at.weekday().number_from_sunday() as u8)
+                .or_default() += 1;
+        }
+
+        let hosts = by_count_desc(
+            hosts
+                .into_iter()
+                .map(|(created_world, players)| {
+                    let count = players.values().sum();
+                    let mut content_ids = by_count_desc(players, |count| *count)
+                        .into_iter()
+                        .map(|(content_id, count)| HostInfoInfo { content_id, count })
+                        .collect::<Vec<_>>();
+                    content_ids.truncate(TOP_HOSTS_PER_WORLD);
+
+                    (created_world, (count, content_ids))
+                })
+                .collect(),
+            |(count, _)| *count,
+        )
+        .into_iter()
+        .map(|(created_world, (count, content_ids))| HostInfo {
+            created_world,
+            count,
+            content_ids,
+        })
+        .collect::<Vec<HostInfo>>();
+
+        // like the Mongo alias query, names come from the newest row of any age or visibility
+        let mut aliases = HashMap::new();
+        for entry in hosts.iter().flat_map(|host| &host.content_ids) {
+            let newest = listings
+                .values()
+                .filter(|container| container.listing.content_id_lower == entry.content_id)
+                .max_by_key(|container| container.created_at);
+            if let Some(container) = newest {
+                aliases.insert(entry.content_id, Alias {
+                    name: container.listing.name.clone(),
+                    home_world: u32::from(container.listing.home_world),
+                });
+            }
+        }
+
+        Ok(Statistics {
+            count: match public.len() {
+                0 => Vec::new(),
+                count => vec![Count { count }],
+            },
+            aliases,
+            duties: by_count_desc(duties, |count| *count)
+                .into_iter()
+                .map(|(info, count)| DutyInfo { info, count })
+                .collect(),
+            hosts,
+            hours: hours
+                .into_iter()
+                .map(|(hour, count)| HourInfo { hour, count })
+                .collect(),
+            days: days
+                .into_iter()
+                .map(|(day, count)| DayInfo { day, count })
+                .collect(),
+        })
+    }
+
+    async fn duty_statistics(
+        &self,
+        duty: DutyKey,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<DutyStatistics> {
+        let listings = self.listings.read().unwrap();
+        let rows = listings
+            .values()
+            .filter(|container| {
+                let listing = &container.listing;
+                !listing.search_area.contains(SearchAreaFlags::PRIVATE)
+                    && (listing.duty_type as u8, listing.category.as_u32(), listing.duty) == duty
+                    && since.is_none_or(|since| container.created_at >= since)
+            })
+            .collect::<Vec<_>>();
+
+        let mut volume = BTreeMap::new();
+        let mut hours = BTreeMap::new();
+        let mut days = BTreeMap::new();
+        let mut item_levels = BTreeMap::new();
+        let mut objectives = BTreeMap::new();
+        let mut conditions = BTreeMap::new();
+        let mut beginners_welcome = 0;
+        let mut fill_seconds = Vec::new();
+
+        for container in &rows {
+            let listing = &container.listing;
+            *volume.entry(container.created_at.date_naive()).or_default() += 1;
+            *hours.entry(container.created_at.hour() as u8).or_default() += 1;
+            *days
+                .entry(container.created_at.weekday().number_from_sunday() as u8)
+                .or_default() += 1;
+            *item_levels.entry(listing.min_item_level).or_default() += 1;
+            *objectives.entry(listing.objective.bits()).or_default() += 1;
+            *conditions.entry(listing.conditions.bits()).or_default() += 1;
+            if listing.beginners_welcome {
+                beginners_welcome += 1;
+            }
+            if listing.slots_filled() == usize::from(listing.slots_available) {
+                fill_seconds.push((container.updated_at - container.created_at).num_milliseconds() as f64 / 1000.0);
+            }
+        }
+
+        let counted = |count: usize| match count {
+            0 => Vec::new(),
+            count => vec![Count { count }],
+        };
+
+        Ok(DutyStatistics {
+            count: counted(rows.len()),
+            volume: volume
+                .into_iter()
+                .map(|(day, count)| VolumeInfo {
+                    day: day.and_time(Default::default()).and_utc(),
+                    count,
+                })
+                .collect(),
+            hours: hours
+                .into_iter()
+                .map(|(hour, count)| HourInfo { hour, count })
+                .collect(),
+            days: days
+                .into_iter()
+                .map(|(day, count)| DayInfo { day, count })
+                .collect(),
+            item_levels: item_levels
+                .into_iter()
+                .map(|(level, count)| ItemLevelInfo { level, count })
+                .collect(),
+            objectives: objectives
+                .into_iter()
+                .map(|(flags, count)| FlagsInfo { flags, count })
+                .collect(),
+            conditions: conditions
+                .into_iter()
+                .map(|(flags, count)| FlagsInfo { flags, count })
+                .collect(),
+            beginners_welcome: counted(beginners_welcome),
+            filled: match fill_seconds.len() {
+                0 => Vec::new(),
+                count => vec![FillInfo {
+                    count,
+                    average_seconds: fill_seconds.iter().sum::<f64>() / count as f64,
+                }],
+            },
+        })
+    }
+
+    async fn find_uploader(&self, token: &str) -> Result<Option<UploaderToken>> {
+        Ok(self.uploaders.read().unwrap().get(token).cloned())
+    }
+
+    async fn insert_subscription(&self, subscription: &Subscription) -> Result<()> {
+        self.subscriptions.write().unwrap().push(subscription.clone());
+        Ok(())
+    }
+
+    async fn subscriptions(&self) -> Result<Vec<Subscription>> {
+        Ok(self.subscriptions.read().unwrap().clone())
+    }
+
+    async fn subscription(&self, id: &str) -> Result<Option<Subscription>> {
+        Ok(self
+            .subscriptions
+            .read()
+            .unwrap()
+            .iter()
+            .find(|subscription| subscription.id == id)
+            .cloned())
+    }
+
+    async fn delete_subscription(&self, id: &str) -> Result<bool> {
+        let mut subscriptions = self.subscriptions.write().unwrap();
+        let before = subscriptions.len();
+        subscriptions.retain(|subscription| subscription.id != id);
+        self.deliveries.write().unwrap().remove(id);
+        Ok(subscriptions.len() < before)
+    }
+
+    async fn set_subscription_enabled(&self, id: &str, enabled: bool) -> Result<Option<Subscription>> {
+        let mut subscriptions = self.subscriptions.write().unwrap();
+        let Some(subscription) = subscriptions.iter_mut().find(|subscription| subscription.id == id) else {
+            return Ok(None);
+        };
+
+        subscription.enabled = enabled;
+        if enabled {
+            subscription.consecutive_failures = 0;
+        }
+        Ok(Some(subscription.clone()))
+    }
+
+    async fn record_delivery(
+        &self,
+        delivery: &Delivery,
+        disable_after: u32,
+    ) -> Result<Option<Subscription>> {
+        let mut subscriptions = self.subscriptions.write().unwrap();
+        let Some(subscription) = subscriptions
+            .iter_mut()
+            .find(|subscription| subscription.id == delivery.subscription_id)
+        else {
+            return Ok(None);
+        };
+
+        if delivery.succeeded {
+            subscription.consecutive_failures = 0;
+        } else {
+            subscription.consecutive_failures = subscription.consecutive_failures.saturating_add(1);
+            if disable_after > 0 && subscription.consecutive_failures >= disable_after {
+                subscription.enabled = false;
+            }
+        }
+
+        let mut deliveries = self.deliveries.write().unwrap();
+        let log = deliveries.entry(subscription.id.clone()).or_default();
+        log.push_front(delivery.clone());
+        log.truncate(DELIVERY_LOG_LEN);
+
+        Ok(Some(subscription.clone()))
+    }
+
+    async fn deliveries(&self, subscription_id: &str, limit: usize) -> Result<Vec<Delivery>> {
+        Ok(self
+            .deliveries
+            .read()
+            .unwrap()
+            .get(subscription_id)
+            .map(|log| log.iter().take(limit).cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+/// Projects a stored row as it stood at its last upload.
+fn query_historical(container: &ListingContainer) -> QueriedListing {
+    QueriedListing {
+        created_at: container.created_at,
+        updated_at: container.updated_at,
+        updated_minute: updated_minute(container.updated_at),
+        time_left: f64::from(container.listing.seconds_remaining),
+        listing: container.listing.clone(),
+    }
+}
+
+/// Projects a stored row into the active-listing view, or `None` if it is not active at `now`.
+fn query_active(container: &ListingContainer, now: DateTime<Utc>) -> Option<QueriedListing> {
+    let listing = &container.listing;
+    if container.updated_at < active_listing_cutoff(now)
+        || listing.search_area.contains(SearchAreaFlags::PRIVATE)
+    {
+        return None;
+    }
+
+    let since_update = now - container.updated_at;
+    let time_left =
+        f64::from(listing.seconds_remaining) - since_update.num_milliseconds() as f64 / 1000.0;
+    if time_left < 0.0 || since_update >= ACTIVE_UPDATE_WINDOW {
+        return None;
+    }
+
+    Some(QueriedListing {
+        created_at: container.created_at,
+        updated_at: container.updated_at,
+        updated_minute: updated_minute(container.updated_at),
+        time_left,
+        listing: listing.clone(),
+    })
+}
+
+/// Sorts grouped counts by count descending, breaking ties on the key so output is stable.
+fn by_count_desc<K: Ord + Hash, V>(
+    counts: HashMap<K, V>,
+    count: impl Fn(&V) -> usize,
+) -> Vec<(K, V)> {
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|(a_key, a), (b_key, b)| count(b).cmp(&count(a)).then_with(|| a_key.cmp(b_key)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+    use crate::listing::{ConditionFlags, ObjectiveFlags};
+    use crate::store::HistoryCursor;
+
+    const LISTING_FIXTURE: &str = r###"
+{
+  "id": 4294967419,
+  "content_id_lower": 456,
+  "name": "VGVzdCBOYW1l",
+  "description": "VGhpcyBpcyBteSB0ZXN0IGRlc2NyaXB0aW9uLg==",
+  "created_world": 1001,
+  "home_world": 1001,
+  "current_world": 1001,
+  "category": 0,
+  "duty": 55,
+  "duty_type": 2,
+  "beginners_welcome": false,
+  "seconds_remaining": 3300,
+  "min_item_level": 0,
+  "num_parties": 1,
+  "slots_available": 7,
+  "last_server_restart": 1234567890,
+  "objective": 3,
+  "conditions": 1,
+  "duty_finder_settings": 0,
+  "loot_rules": 0,
+  "search_area": 1,
+  "slots": [
+    {
+      "accepting": 167772160
+    }
+  ],
+  "jobs_present": [5, 0, 0, 0, 0, 0, 0, 0]
+}"###;
+
+    fn listing(id: u64, content_id_lower: u32) -> PartyFinderListing {
+        let mut listing: PartyFinderListing =
+            serde_json::from_str(LISTING_FIXTURE).expect("listing fixture must deserialize");
+        listing.id = id;
+        listing.content_id_lower = content_id_lower;
+        listing
+    }
+
+    fn ids(documents: &[QueriedListing]) -> Vec<u64> {
+        documents.iter().map(|document| document.listing.id).collect()
+    }
+
+    #[tokio::test]
+    async fn upsert_keeps_created_at_and_replaces_listing() {
+        let store = MemoryStore::new();
+        let created = Utc::now() - Duration::minutes(3);
+        let mut first = listing(1, 10);
+
+        assert_eq!(store.upsert(&first, None, created).await.unwrap(), UpsertOutcome::Inserted);
+        first.min_item_level = 650;
+        assert_eq!(store.upsert(&first, None, Utc::now()).await.unwrap(), UpsertOutcome::Updated);
+        assert_eq!(store.upsert(&first, None, Utc::now()).await.unwrap(), UpsertOutcome::Unchanged);
+
+        // same id after a server restart is a different listing
+        let mut restarted = listing(1, 10);
+        restarted.last_server_restart += 1;
+        assert_eq!(store.upsert(&restarted, None, Utc::now()).await.unwrap(), UpsertOutcome::Inserted);
+
+        let documents = store.listings_by_id(1).await.unwrap();
+        assert_eq!(documents.len(), 2);
+        let updated = documents
+            .iter()
+            .find(|document| document.listing.last_server_restart == first.last_server_restart)
+            .unwrap();
+        assert_eq!(updated.created_at, created);
+        assert!(updated.updated_at > created);
+        assert_eq!(updated.listing.min_item_level, 650);
+    }
+
+    #[tokio::test]
+    async fn upsert_records_the_last_uploader() {
+        let store = MemoryStore::new();
+        let first = listing(1, 10);
+
+        store.upsert(&first, Some("plugin-a"), Utc::now()).await.unwrap();
+        assert_eq!(
+            store.uploader(listing_identity(&first)),
+            Some(Some("plugin-a".to_string())),
+        );
+
+        store.upsert(&first, None, Utc::now()).await.unwrap();
+        assert_eq!(store.uploader(listing_identity(&first)), Some(None));
+    }
+
+    #[tokio::test]
+    async fn active_window_applies_time_left_and_update_window() {
+        let store = MemoryStore::new();
+        let now = Utc::now();
+
+        let fresh = listing(1, 1);
+        let mut out_of_time = listing(2, 2);
+        out_of_time.seconds_remaining = 60;
+        let stale = listing(3, 3);
+        let mut private = listing(4, 4);
+        private.search_area |= SearchAreaFlags::PRIVATE;
+
+        store.upsert(&fresh, None, now - Duration::seconds(30)).await.unwrap();
+        store.upsert(&out_of_time, None, now - Duration::minutes(2)).await.unwrap();
+        store.upsert(&stale, None, now - Duration::minutes(6)).await.unwrap();
+        store.upsert(&private, None, now).await.unwrap();
+
+        let active = store.active_listings(&ListingFilter::default()).await.unwrap();
+        assert_eq!(ids(&active), vec![1]);
+
+        let time_left = active[0].time_left;
+        assert!(time_left <= 3270.0 && time_left > 3260.0, "time_left was {time_left}");
+        assert_eq!(active[0].updated_minute, updated_minute(active[0].updated_at));
+        assert_eq!(active[0].updated_minute.minute() % 5, 0);
+        assert_eq!(active[0].updated_minute.second(), 0);
+
+        assert!(store.listings_by_id(3).await.unwrap().is_empty());
+        assert!(store.listings_by_id(4).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn latest_per_player_keeps_newest_row() {
+        let store = MemoryStore::new();
+        let now = Utc::now();
+
+        store.upsert(&listing(1, 7), None, now - Duration::minutes(2)).await.unwrap();
+        store.upsert(&listing(2, 7), None, now - Duration::minutes(1)).await.unwrap();
+        store.upsert(&listing(3, 8), None, now).await.unwrap();
+
+        let all = store.active_listings(&ListingFilter::default()).await.unwrap();
+        assert_eq!(all.len(), 3);
+
+        let mut latest = ids(&store
+            .active_listings(&ListingFilter {
+                latest_per_player: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap());
+        latest.sort_unstable();
+        assert_eq!(latest, vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn active_listings_sort_by_bucket_category_then_time_left() {
+        let store = MemoryStore::new();
+        let now = Utc::now();
+
+        let mut short = listing(1, 1);
+        short.seconds_remaining = 1000;
+        let long = listing(2, 2);
+        let mut other_category = listing(3, 3);
+        other_category.category = crate::listing::DutyCategory::HighEndDuty;
+
+        for document in [&long, &short, &other_category] {
+            store.upsert(document, None, now).await.unwrap();
+        }
+
+        let active = store.active_listings(&ListingFilter::default()).await.unwrap();
+        assert_eq!(ids(&active), vec![3, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn filters_match_any_member_per_field() {
+        let store = MemoryStore::new();
+        let now = Utc::now();
+
+        let first = listing(1, 1);
+        let mut second = listing(2, 2);
+        second.created_world = 1002;
+        second.duty = 56;
+        store.upsert(&first, None, now).await.unwrap();
+        store.upsert(&second, None, now).await.unwrap();
+
+        let store = &store;
+        let query = |filter: ListingFilter| async move {
+            let mut found = ids(&store.active_listings(&filter).await.unwrap());
+            found.sort_unstable();
+            found
+        };
+
+        assert_eq!(
+            query(ListingFilter {
+                created_world_ids: vec![1002, 9999],
+                ..Default::default()
+            })
+            .await,
+            vec![2]
+        );
+        assert_eq!(
+            query(ListingFilter {
+                duty_ids: vec![55, 56],
+                ..Default::default()
+            })
+            .await,
+            vec![1, 2]
+        );
+        assert!(query(ListingFilter {
+            category_ids: vec![crate::listing::DutyCategory::Raid.as_u32()],
+            ..Default::default()
+        })
+        .await
+        .is_empty());
+        assert_eq!(
+            query(ListingFilter {
+                accepted_slot_bits: vec![1 << 25, 1 << 40],
+                ..Default::default()
+            })
+            .await,
+            vec![1, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn history_selects_rows_live_in_range_and_pages_by_cursor() {
+        let store = MemoryStore::new();
+        let tuesday = Utc.with_ymd_and_hms(2026, 5, 5, 21, 0, 0).unwrap();
+
+        // created 20:40, last refreshed 21:10: overlaps 21:00-22:00
+        store.upsert(&listing(1, 1), None, tuesday - Duration::minutes(20)).await.unwrap();
+        store.upsert(&listing(1, 1), None, tuesday + Duration::minutes(10)).await.unwrap();
+        // two rows sharing an update time, ordered by identity
+        store.upsert(&listing(2, 2), None, tuesday + Duration::minutes(30)).await.unwrap();
+        store.upsert(&listing(3, 3), None, tuesday + Duration::minutes(30)).await.unwrap();
+        // ended before the range, created after it, private
+        store.upsert(&listing(4, 4), None, tuesday - Duration::minutes(5)).await.unwrap();
+        store.upsert(&listing(5, 5), None, tuesday + Duration::hours(1)).await.unwrap();
+        let mut private = listing(6, 6);
+        private.search_area |= SearchAreaFlags::PRIVATE;
+        store.upsert(&private, None, tuesday + Duration::minutes(5)).await.unwrap();
+
+        let mut query = HistoryQuery {
+            from: tuesday,
+            to: tuesday + Duration::hours(1),
+            filter: ListingFilter::default(),
+            after: None,
+            limit: 2,
+        };
+
+        let first = store.history(&query).await.unwrap();
+        assert_eq!(ids(&first), vec![3, 2]);
+        assert_eq!(first[0].time_left, 3300.0);
+
+        query.after = first.last().map(HistoryCursor::of);
+        let second = store.history(&query).await.unwrap();
+        assert_eq!(ids(&second), vec![1]);
+        assert_eq!(second[0].created_at, tuesday - Duration::minutes(20));
+
+        query.after = None;
+        query.filter.duty_ids = vec![56];
+        assert!(store.history(&query).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn statistics_match_mongo_facets() {
+        let store = MemoryStore::new();
+        // 2026-05-03 is a Sunday
+        let sunday = Utc.with_ymd_and_hms(2026, 5, 3, 9, 0, 0).unwrap();
+        let monday = sunday + Duration::days(1);
+
+        store.upsert(&listing(1, 7), None, sunday).await.unwrap();
+        store.upsert(&listing(2, 7), None, monday).await.unwrap();
+        let mut other = listing(3, 8);
+        other.duty = 56;
+        store.upsert(&other, None, monday).await.unwrap();
+        let mut private = listing(4, 9);
+        private.search_area |= SearchAreaFlags::PRIVATE;
+        store.upsert(&private, None, monday).await.unwrap();
+
+        let stats = store.statistics(None).await.unwrap();
+        assert_eq!(stats.num_listings(), 3);
+        assert_eq!(
+            stats.duties.iter().map(|duty| (duty.info, duty.count)).collect::<Vec<_>>(),
+            vec![((2, 0, 55), 2), ((2, 0, 56), 1)]
+        );
+        assert_eq!(stats.hosts.len(), 1);
+        assert_eq!(stats.hosts[0].created_world, 1001);
+        assert_eq!(stats.hosts[0].count, 3);
+        assert_eq!(
+            stats.hosts[0]
+                .content_ids
+                .iter()
+                .map(|host| (host.content_id, host.count))
+                .collect::<Vec<_>>(),
+            vec![(7, 2), (8, 1)]
+        );
+        assert_eq!(stats.hours.iter().map(|hour| (hour.hour, hour.count)).collect::<Vec<_>>(), vec![(9, 3)]);
+        assert_eq!(stats.days.iter().map(|day| (day.day, day.count)).collect::<Vec<_>>(), vec![(1, 1), (2, 2)]);
+        assert_eq!(stats.aliases[&7].home_world, 1001);
+        assert!(stats.aliases.contains_key(&8));
+
+        let since_monday = store.statistics(Some(monday)).await.unwrap();
+        assert_eq!(since_monday.num_listings(), 2);
+
+        let empty = MemoryStore::new().statistics(None).await.unwrap();
+        assert!(empty.count.is_empty());
+        assert_eq!(empty.num_listings(), 0);
+    }
+
+    #[tokio::test]
+    async fn duty_statistics_cover_only_the_requested_duty() {
+        let store = MemoryStore::new();
+        let sunday = Utc.with_ymd_and_hms(2026, 5, 3, 9, 0, 0).unwrap();
+        let monday = sunday + Duration::days(1);
+
+        let mut filled = listing(1, 7);
+        filled.min_item_level = 600;
+        filled.beginners_welcome = true;
+        store.upsert(&filled, None, sunday).await.unwrap();
+        filled.jobs_present = vec![5; 7];
+        store.upsert(&filled, None, sunday + Duration::minutes(10)).await.unwrap();
+
+        let mut loot = listing(2, 8);
+        loot.min_item_level = 640;
+        loot.objective = ObjectiveFlags::LOOT;
+        loot.conditions = ConditionFlags::DUTY_COMPLETE;
+        store.upsert(&loot, None, monday).await.unwrap();
+        let mut practice = listing(3, 9);
+        practice.min_item_level = 620;
+        store.upsert(&practice, None, monday).await.unwrap();
+
+        let mut other_duty = listing(4, 10);
+        other_duty.duty = 56;
+        store.upsert(&other_duty, None, monday).await.unwrap();
+        let mut private = listing(5, 11);
+        private.search_area |= SearchAreaFlags::PRIVATE;
+        store.upsert(&private, None, monday).await.unwrap();
+
+        let stats = store.duty_statistics((2, 0, 55), None).await.unwrap();
+        assert_eq!(stats.num_listings(), 3);
+        assert_eq!(
+            stats.volume.iter().map(|info| (info.day, info.count)).collect::<Vec<_>>(),
+            vec![(sunday - Duration::hours(9), 1), (monday - Duration::hours(9), 2)]
+        );
+        assert_eq!(stats.days.iter().map(|day| (day.day, day.count)).collect::<Vec<_>>(), vec![(1, 1), (2, 2)]);
+        assert_eq!(stats.median_min_item_level(), Some(620));
+        assert_eq!(
+            stats.objective_counts().iter().map(|flag| flag.count).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert_eq!(
+            stats.condition_counts().iter().map(|flag| flag.count).collect::<Vec<_>>(),
+            vec![1, 0, 0]
+        );
+        assert!((stats.beginners_welcome_share() - 1.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(stats.num_filled(), 1);
+        assert_eq!(stats.average_filled_lifetime_seconds(), Some(600.0));
+
+        let since_monday = store.duty_statistics((2, 0, 55), Some(monday)).await.unwrap();
+        assert_eq!(since_monday.num_listings(), 2);
+        assert_eq!(since_monday.average_filled_lifetime_seconds(), None);
+
+        let empty = store.duty_statistics((2, 0, 57), None).await.unwrap();
+        assert_eq!(empty.num_listings(), 0);
+        assert_eq!(empty.median_min_item_level(), None);
+        assert_eq!(empty.beginners_welcome_share(), 0.0);
+    }
+}
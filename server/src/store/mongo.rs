@@ -0,0 +1,1530 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mongodb::{
+    bson::{doc, from_bson, to_bson, Bson, Document},
+    options::{AggregateOptions, FindOneAndUpdateOptions, FindOptions, IndexOptions, ReturnDocument},
+    Client as MongoClient,
+    Collection,
+    Database,
+    IndexModel,
+};
+use tokio_stream::StreamExt;
+
+use crate::{
+    config::UploaderToken,
+    listing::{PartyFinderListing, SearchAreaFlags},
+    listing_container::{ListingContainer, QueriedListing},
+    stats::{Aliases, DutyKey, DutyStatistics, Statistics},
+    webhooks::{Delivery, Subscription},
+};
+
+use super::{
+    active_listing_cutoff, keep_latest_per_player, listing_identity, HistoryCursor, HistoryQuery,
+    ListingFilter, ListingIdentity, ListingStore, UpsertOutcome,
+};
+
+const LISTING_ID_FIELD: &str = "listing.id";
+const LISTING_LAST_SERVER_RESTART_FIELD: &str = "listing.last_server_restart";
+const LISTING_CREATED_WORLD_FIELD: &str = "listing.created_world";
+/// How long webhook deliveries stay in the log.
+const DELIVERY_LOG_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+lazy_static::lazy_static! {
+    static ref STATS_QUERY: [Document; 2] = [
+        doc! {
+            "$match": {
+                // filter private pfs
+                "listing.search_area": { "$bitsAllClear": 2 },
+            }
+        },
+        doc! {
+            "$facet": {
+                "count": [
+                    {
+                        "$count": "count",
+                    },
+                ],
+                "duties": [
+                    {
+                        "$group": {
+                            "_id": [
+                                "$listing.duty_type",
+                                "$listing.category",
+                                "$listing.duty",
+                            ],
+                            "count": {
+                                "$sum": 1
+                            },
+                        }
+                    },
+                    {
+                        "$sort": {
+                            "count": -1,
+                        }
+                    }
+                ],
+                "hosts": [
+                    {
+                        "$group": {
+                            "_id": {
+                                "world": "$listing.created_world",
+                                "content_id": "$listing.content_id_lower",
+                            },
+                            "count": { "$sum": 1 },
+                        }
+                    },
+                    {
+                        "$sort": {
+                            "count": -1,
+                        }
+                    },
+                    {
+                        "$group": {
+                            "_id": "$_id.world",
+                            "count": {
+                                "$sum": "$count",
+                            },
+                            "content_ids": {
+                                "$push": {
+                                    "content_id": "$_id.content_id",
+                                    "count": "$count",
+                                }
+                            }
+                        }
+                    },
+                    {
+                        "$addFields": {
+                            "content_ids": {
+                                "$slice": ["$content_ids", 0, 15],
+                            },
+                        }
+                    },
+                    {
+                        "$sort": { "count": -1 }
+                    },
+                ],
+                "hours": [
+                    {
+                        "$group": {
+                            "_id": {
+                                "$hour": "$created_at",
+                            },
+                            "count": {
+                                "$sum": 1
+                            },
+                        }
+                    },
+                    {
+                        "$sort": {
+                            "_id": 1,
+                        }
+                    }
+                ],
+                "days": [
+                    {
+                        "$group": {
+                            "_id": {
+                                "$dayOfWeek": "$created_at",
+                            },
+                            "count": {
+                                "$sum": 1
+                            },
+                        }
+                    },
+                    {
+                        "$sort": {
+                            "_id": 1,
+                        }
+                    }
+                ],
+            }
+        },
+    ];
+
+    static ref DUTY_STATS_QUERY: [Document; 1] = [
+        doc! {
+            "$facet": {
+                "count": [
+                    {
+                        "$count": "count",
+                    },
+                ],
+                "volume": [
+                    {
+                        "$group": {
+                            "_id": {
+                                "$dateTrunc": {
+                                    "date": "$created_at",
+                                    "unit": "day",
+                                },
+                            },
+                            "count": { "$sum": 1 },
+                        }
+                    },
+                    {
+                        "$sort": { "_id": 1 }
+                    },
+                ],
+                "hours": [
+                    {
+                        "$group": {
+                            "_id": { "$hour": "$created_at" },
+                            "count": { "$sum": 1 },
+                        }
+                    },
+                    {
+                        "$sort": { "_id": 1 }
+                    },
+                ],
+                "days": [
+                    {
+                        "$group": {
+                            "_id": { "$dayOfWeek": "$created_at" },
+                            "count": { "$sum": 1 },
+                        }
+                    },
+                    {
+                        "$sort": { "_id": 1 }
+                    },
+                ],
+                "item_levels": [
+                    {
+                        "$group": {
+                            "_id": "$listing.min_item_level",
+                            "count": { "$sum": 1 },
+                        }
+                    },
+                    {
+                        "$sort": { "_id": 1 }
+                    },
+                ],
+                "objectives": [
+                    {
+                        "$group": {
+                            "_id": "$listing.objective",
+                            "count": { "$sum": 1 },
+                        }
+                    },
+                ],
+                "conditions": [
+                    {
+                        "$group": {
+                            "_id": "$listing.conditions",
+                            "count": { "$sum": 1 },
+                        }
+                    },
+                ],
+                "beginners_welcome": [
+                    {
+                        "$match": { "listing.beginners_welcome": true }
+                    },
+                    {
+                        "$count": "count",
+                    },
+                ],
+                "filled": [
+                    {
+                        "$match": {
+                            "$expr": {
+                                "$eq": [
+                                    {
+                                        "$size": {
+                                            "$filter": {
+                                                "input": "$listing.jobs_present",
+                                                "cond": { "$ne": ["$$this", 0] },
+                                            }
+                                        }
+                                    },
+                                    "$listing.slots_available",
+                                ]
+                            }
+                        }
+                    },
+                    {
+                        "$group": {
+                            "_id": null,
+                            "count": { "$sum": 1 },
+                            "average_seconds": {
+                                "$avg": {
+                                    "$divide": [{ "$subtract": ["$updated_at", "$created_at"] }, 1000],
+                                }
+                            },
+                        }
+                    },
+                ],
+            }
+        },
+    ];
+
+    static ref ALIASES_QUERY: [Document; 1] = [
+        doc! {
+            "$facet": {
+                "aliases": [
+                    {
+                        "$sort": {
+                            "created_at": -1,
+                        }
+                    },
+                    {
+                        "$group": {
+                            "_id": "$listing.content_id_lower",
+                            "alias": {
+                                "$first": {
+                                    "name": "$listing.name",
+                                    "home_world": "$listing.home_world",
+                                },
+                            },
+                        }
+                    }
+                ],
+            },
+        },
+    ];
+}
+
+/// The production backend: the `rpf.listings` and `rpf.uploaders` collections, plus
+/// `rpf.subscriptions` and `rpf.webhook_deliveries` for webhooks.
+pub struct MongoStore {
+    client: MongoClient,
+    database: String,
+}
+
+impl MongoStore {
+    pub async fn connect(url: &str) -> Result<Self> {
+        Self::connect_to(url, "rpf").await
+    }
+
+    /// Like [`MongoStore::connect`], but on `database` instead of `rpf`.
+    pub(crate) async fn connect_to(url: &str, database: &str) -> Result<Self> {
+        let client = MongoClient::with_uri_str(url)
+            .await
+            .context("could not create mongodb client")?;
+
+        Ok(Self {
+            client,
+            database: database.to_owned(),
+        })
+    }
+
+    fn database(&self) -> Database {
+        self.client.database(&self.database)
+    }
+
+    pub fn collection(&self) -> Collection<ListingContainer> {
+        self.database().collection("listings")
+    }
+
+    fn uploaders(&self) -> Collection<UploaderToken> {
+        self.database().collection("uploaders")
+    }
+
+    fn subscription_collection(&self) -> Collection<Subscription> {
+        self.database().collection("subscriptions")
+    }
+
+    fn delivery_collection(&self) -> Collection<Delivery> {
+        self.database().collection("webhook_deliveries")
+    }
+
+    async fn create_indexes(&self) -> Result<()> {
+        self.collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(listing_identity_index_keys())
+                    .options(IndexOptions::builder()
+                        .unique(true)
+                        .build())
+                    .build(),
+                None,
+            )
+            .await
+            .context("could not create unique index")?;
+
+        let indexes = [
+            (doc! { "updated_at": 1 }, "could not create updated_at index"),
+            (listing_id_index_keys(), "could not create listing.id index"),
+            (history_index_keys(), "could not create history order index"),
+            (doc! { "listing.category": 1 }, "could not create listing.category index"),
+            (doc! { "listing.created_world": 1 }, "could not create listing.created_world index"),
+            (doc! { "listing.search_area": 1 }, "could not create listing.search_area index"),
+            (doc! { "listing.slots.job": 1 }, "could not create listing.slots.job index"),
+            (
+                doc! { "listing.slots.accepting_classes": 1 },
+                "could not create listing.slots.accepting_classes index",
+            ),
+            (doc! { "listing.duty": 1 }, "could not create listing.duty index"),
+            (doc! { "uploader": 1 }, "could not create uploader index"),
+        ];
+
+        for (keys, context) in indexes {
+            self.collection()
+                .create_index(IndexModel::builder().keys(keys).build(), None)
+                .await
+                .context(context)?;
+        }
+
+        self.uploaders()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "token": 1 })
+                    .options(IndexOptions::builder()
+                        .unique(true)
+                        .build())
+                    .build(),
+                None,
+            )
+            .await
+            .context("could not create uploaders.token index")?;
+
+        self.subscription_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "id": 1 })
+                    .options(IndexOptions::builder()
+                        .unique(true)
+                        .build())
+                    .build(),
+                None,
+            )
+            .await
+            .context("could not create subscriptions.id index")?;
+
+        self.delivery_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "subscription_id": 1, "created_at": -1 })
+                    .build(),
+                None,
+            )
+            .await
+            .context("could not create webhook_deliveries.subscription_id index")?;
+
+        self.delivery_collection()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "created_at": 1 })
+                    .options(IndexOptions::builder()
+                        .expire_after(DELIVERY_LOG_TTL)
+                        .build())
+                    .build(),
+                None,
+            )
+            .await
+            .context("could not create webhook_deliveries.created_at index")?;
+
+        Ok(())
+    }
+
+    async fn aggregate_listings(&self, pipeline: Vec<Document>) -> Result<Vec<QueriedListing>> {
+        let mut cursor = self
+            .collection()
+            .aggregate(pipeline, None)
+            .await
+            .context("could not query listings")?;
+
+        let mut listings = Vec::new();
+        while let Some(document) = cursor.try_next().await? {
+            // legacy or malformed rows are skipped rather than failing every read they turn up in
+            match mongodb::bson::from_document(document) {
+                Ok(listing) => listings.push(listing),
+                Err(error) => eprintln!("skipping listing that could not be decoded: {error}"),
+            }
+        }
+
+        Ok(listings)
+    }
+
+    /// Two round-trips per batch: one read of the rows the batch touches, so outcomes can be
+    /// told apart, then one unordered `update` command holding every upsert. Only the last
+    /// listing of each identity is sent, since unordered statements for the same new identity
+    /// could run in any order and race on the unique index; the earlier ones are still reported
+    /// as if they had been applied in order.
+    async fn bulk_upsert(
+        &self,
+        listings: &[PartyFinderListing],
+        uploader: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Result<UpsertOutcome>>> {
+        // `$or` may not be empty, and there is nothing to write anyway
+        if listings.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let statements = last_by_identity(listings);
+        let filters = statements
+            .iter()
+            .map(|&index| listing_identity_filter(&listings[index]))
+            .collect::<Result<Vec<_>>>()?;
+
+        let stored = self.stored_listings(&filters).await?;
+
+        let updates = statements
+            .iter()
+            .zip(&filters)
+            .map(|(&index, filter)| {
+                let bson_value = to_bson(&listings[index]).context("could not serialize listing")?;
+                Ok(doc! {
+                    "q": filter.clone(),
+                    "u": listing_update(bson_value, uploader, now),
+                    "upsert": true,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let reply = self
+            .database()
+            .run_command(
+                doc! {
+                    "update": "listings",
+                    "updates": updates,
+                    "ordered": false,
+                },
+                None,
+            )
+            .await
+            .context("could not insert records")?;
+
+        Ok(batch_outcomes(listings, &statements, stored, &reply))
+    }
+
+    async fn stored_listings(
+        &self,
+        filters: &[Document],
+    ) -> Result<HashMap<ListingIdentity, PartyFinderListing>> {
+        let options = FindOptions::builder()
+            .projection(doc! { "listing": 1 })
+            .build();
+        let mut cursor = self
+            .collection()
+            .clone_with_type::<Document>()
+            .find(doc! { "$or": filters }, options)
+            .await
+            .context("could not query existing listings")?;
+
+        let mut stored = HashMap::new();
+        while let Some(document) = cursor.try_next().await? {
+            // rows that no longer decode are simply reported as updated
+            let listing = document
+                .get("listing")
+                .cloned()
+                .and_then(|listing| from_bson::<PartyFinderListing>(listing).ok());
+            if let Some(listing) = listing {
+                stored.insert(listing_identity(&listing), listing);
+            }
+        }
+
+        Ok(stored)
+    }
+}
+
+#[async_trait]
+impl ListingStore for MongoStore {
+    async fn upsert(
+        &self,
+        listing: &PartyFinderListing,
+        uploader: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<UpsertOutcome> {
+        let opts = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::Before)
+            .build();
+        let bson_value = to_bson(listing).context("could not serialize listing")?;
+        // Canonical writes always upsert on the widened `(listing.id, last_server_restart,
+        // created_world)` identity only. Pre-migration truncated-id rows remain legacy data; the
+        // server does not reconstruct guessed wide ids from `content_id_lower` or any other surrogate.
+        let filter = listing_identity_filter(listing)?;
+        let previous = self
+            .collection()
+            .clone_with_type::<Document>()
+            .find_one_and_update(filter, listing_update(bson_value.clone(), uploader, now), opts)
+            .await
+            .context("could not insert record")?;
+
+        Ok(match previous {
+            None => UpsertOutcome::Inserted,
+            Some(previous) if previous.get("listing") == Some(&bson_value) => UpsertOutcome::Unchanged,
+            Some(_) => UpsertOutcome::Updated,
+        })
+    }
+
+    async fn upsert_many(
+        &self,
+        listings: &[PartyFinderListing],
+        uploader: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Vec<Result<UpsertOutcome>> {
+        // a lone listing is cheaper as a single find-and-modify than a read plus a bulk write
+        match listings {
+            [] => return Vec::new(),
+            [listing] => return vec![self.upsert(listing, uploader, now).await],
+            _ => {}
+        }
+
+        match self.bulk_upsert(listings, uploader, now).await {
+            Ok(results) => results,
+            Err(e) => {
+                let message = format!("{:#}", e);
+                listings.iter().map(|_| Err(anyhow::anyhow!("{}", message))).collect()
+            }
+        }
+    }
+
+    async fn active_listings(&self, filter: &ListingFilter) -> Result<Vec<QueriedListing>> {
+        let mut listings = self
+            .aggregate_listings(active_listings_pipeline(filter, Utc::now()))
+            .await?;
+        if !filter.open_slot_bits.is_empty() {
+            // the pipeline left these unchecked and undeduplicated; a player's newest row may
+            // only look like it has the open slot, and then an older one is their latest match
+            listings.retain(|listing| filter.matches(&listing.listing));
+            if filter.latest_per_player {
+                keep_latest_per_player(&mut listings);
+            }
+        }
+        Ok(listings)
+    }
+
+    async fn listings_by_id(&self, id: u64) -> Result<Vec<QueriedListing>> {
+        let listing_id = to_bson(&id).context("could not serialize listing.id")?;
+
+        let mut pipeline = vec![doc! {
+            "$match": {
+                "updated_at": { "$gte": active_listing_cutoff(Utc::now()) },
+                LISTING_ID_FIELD: listing_id,
+                "listing.search_area": { "$bitsAllClear": SearchAreaFlags::PRIVATE.bits() as i32 },
+            }
+        }];
+        pipeline.extend(active_window_stages());
+        pipeline.push(doc! {
+            "$sort": {
+                "updated_at": -1,
+            }
+        });
+
+        self.aggregate_listings(pipeline).await
+    }
+
+    async fn history(&self, query: &HistoryQuery) -> Result<Vec<QueriedListing>> {
+        self.aggregate_listings(history_pipeline(query)?).await
+    }
+
+    async fn statistics(&self, since: Option<DateTime<Utc>>) -> Result<Statistics> {
+        let mut docs = STATS_QUERY.to_vec();
+        if let Some(since) = since {
+            docs.insert(0, doc! {
+                "$match": {
+                    "created_at": {
+                        "$gte": since,
+                    },
+                },
+            });
+        }
+
+        let mut cursor = self
+            .collection()
+            .aggregate(docs, AggregateOptions::builder()
+                .allow_disk_use(true)
+                .build())
+            .await?;
+        let doc = cursor.try_next().await?;
+        let doc = doc.ok_or_else(|| anyhow::anyhow!("missing document"))?;
+        let mut stats: Statistics = mongodb::bson::from_document(doc)?;
+
+        let ids: Vec<u32> = stats.hosts.iter().flat_map(|host| host.content_ids.iter().map(|entry| entry.content_id)).collect();
+        let mut aliases_query: Vec<Document> = ALIASES_QUERY.iter().cloned().collect();
+        aliases_query.insert(0, doc! {
+            "$match": {
+                "listing.content_id_lower": {
+                    "$in": ids,
+                }
+            }
+        });
+        let mut cursor = self
+            .collection()
+            .aggregate(aliases_query, AggregateOptions::builder()
+                .allow_disk_use(true)
+                .build())
+            .await?;
+        let doc = cursor.try_next().await?;
+        let doc = doc.ok_or_else(|| anyhow::anyhow!("missing document"))?;
+        let aliases: Aliases = mongodb::bson::from_document(doc)?;
+
+        stats.aliases = aliases.aliases;
+
+        Ok(stats)
+    }
+
+    async fn duty_statistics(
+        &self,
+        duty: DutyKey,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<DutyStatistics> {
+        let (duty_type, category, duty) = duty;
+        let mut filter = doc! {
+            "listing.duty_type": i32::from(duty_type),
+            "listing.category": i64::from(category),
+            "listing.duty": i32::from(duty),
+            // filter private pfs
+            "listing.search_area": { "$bitsAllClear": SearchAreaFlags::PRIVATE.bits() as i32 },
+        };
+        if let Some(since) = since {
+            filter.insert("created_at", doc! { "$gte": since });
+        }
+
+        let mut docs = vec![doc! { "$match": filter }];
+        docs.extend(DUTY_STATS_QUERY.iter().cloned());
+
+        let mut cursor = self
+            .collection()
+            .aggregate(docs, AggregateOptions::builder()
+                .allow_disk_use(true)
+                .build())
+            .await?;
+        let doc = cursor.try_next().await?;
+        let doc = doc.ok_or_else(|| anyhow::anyhow!("missing document"))?;
+
+        Ok(mongodb::bson::from_document(doc)?)
+    }
+
+    async fn find_uploader(&self, token: &str) -> Result<Option<UploaderToken>> {
+        self.uploaders()
+            .find_one(doc! { "token": token }, None)
+            .await
+            .context("could not query uploaders")
+    }
+
+    async fn insert_subscription(&self, subscription: &Subscription) -> Result<()> {
+        self.subscription_collection()
+            .insert_one(subscription, None)
+            .await
+            .context("could not insert subscription")?;
+        Ok(())
+    }
+
+    async fn subscriptions(&self) -> Result<Vec<Subscription>> {
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": 1, "id": 1 })
+            .build();
+        let mut cursor = self
+            .subscription_collection()
+            .find(doc! {}, options)
+            .await
+            .context("could not query subscriptions")?;
+
+        let mut subscriptions = Vec::new();
+        while let Some(subscription) = cursor.try_next().await? {
+            subscriptions.push(subscription);
+        }
+        Ok(subscriptions)
+    }
+
+    async fn subscription(&self, id: &str) -> Result<Option<Subscription>> {
+        self.subscription_collection()
+            .find_one(doc! { "id": id }, None)
+            .await
+            .context("could not query subscriptions")
+    }
+
+    async fn delete_subscription(&self, id: &str) -> Result<bool> {
+        let deleted = self
+            .subscription_collection()
+            .delete_one(doc! { "id": id }, None)
+            .await
+            .context("could not delete subscription")?;
+        self.delivery_collection()
+            .delete_many(doc! { "subscription_id": id }, None)
+            .await
+            .context("could not delete webhook deliveries")?;
+        Ok(deleted.deleted_count > 0)
+    }
+
+    async fn set_subscription_enabled(&self, id: &str, enabled: bool) -> Result<Option<Subscription>> {
+        let mut set = doc! { "enabled": enabled };
+        if enabled {
+            set.insert("consecutive_failures", 0);
+        }
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.subscription_collection()
+            .find_one_and_update(doc! { "id": id }, doc! { "$set": set }, options)
+            .await
+            .context("could not update subscription")
+    }
+
+    async fn record_delivery(
+        &self,
+        delivery: &Delivery,
+        disable_after: u32,
+    ) -> Result<Option<Subscription>> {
+        let mut update = Vec::new();
+        if delivery.succeeded {
+            update.push(doc! { "$set": { "consecutive_failures": 0 } });
+        } else {
+            update.push(doc! {
+                "$set": { "consecutive_failures": { "$add": ["$consecutive_failures", 1] } }
+            });
+            if disable_after > 0 {
+                update.push(doc! {
+                    "$set": {
+                        "enabled": {
+                            "$and": [
+                                "$enabled",
+                                { "$lt": ["$consecutive_failures", i64::from(disable_after)] },
+                            ]
+                        }
+                    }
+                });
+            }
+        }
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        let subscription = self
+            .subscription_collection()
+            .find_one_and_update(doc! { "id": &delivery.subscription_id }, update, options)
+            .await
+            .context("could not update subscription")?;
+
+        if subscription.is_some() {
+            self.delivery_collection()
+                .insert_one(delivery, None)
+                .await
+                .context("could not insert webhook delivery")?;
+        }
+        Ok(subscription)
+    }
+
+    async fn deliveries(&self, subscription_id: &str, limit: usize) -> Result<Vec<Delivery>> {
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .limit(i64::try_from(limit).unwrap_or(i64::MAX))
+            .build();
+        let mut cursor = self
+            .delivery_collection()
+            .find(doc! { "subscription_id": subscription_id }, options)
+            .await
+            .context("could not query webhook deliveries")?;
+
+        let mut deliveries = Vec::new();
+        while let Some(delivery) = cursor.try_next().await? {
+            deliveries.push(delivery);
+        }
+        Ok(deliveries)
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.database()
+            .run_command(doc! { "ping": 1 }, None)
+            .await
+            .context("could not ping mongodb")?;
+        Ok(())
+    }
+
+    async fn prepare(&self) -> Result<()> {
+        self.create_indexes().await
+    }
+}
+
+fn listing_identity_index_keys() -> Document {
+    let mut keys = Document::new();
+    keys.insert(LISTING_ID_FIELD, 1);
+    keys.insert(LISTING_LAST_SERVER_RESTART_FIELD, 1);
+    keys.insert(LISTING_CREATED_WORLD_FIELD, 1);
+    keys
+}
+
+/// Serves the `$sort` of [`history_pipeline`], so cursor pages walk the index in order.
+fn history_index_keys() -> Document {
+    let mut keys = Document::new();
+    keys.insert("updated_at", -1);
+    keys.insert(LISTING_ID_FIELD, -1);
+    keys.insert(LISTING_LAST_SERVER_RESTART_FIELD, -1);
+    keys.insert(LISTING_CREATED_WORLD_FIELD, -1);
+    keys
+}
+
+fn listing_id_index_keys() -> Document {
+    let mut keys = Document::new();
+    keys.insert(LISTING_ID_FIELD, 1);
+    keys
+}
+
+fn listing_update(listing: Bson, uploader: Option<&str>, now: DateTime<Utc>) -> Document {
+    doc! {
+        "$currentDate": {
+            "updated_at": true,
+        },
+        "$set": {
+            "listing": listing,
+            "uploader": uploader,
+        },
+        "$setOnInsert": {
+            "created_at": now,
+        },
+    }
+}
+
+/// Statement indexes of an `update` command reply that upserted a new document, and the error
+/// message of each statement that failed.
+fn bulk_write_indexes(reply: &Document) -> (HashSet<usize>, HashMap<usize, String>) {
+    let entries = |field| {
+        reply
+            .get_array(field)
+            .into_iter()
+            .flatten()
+            .filter_map(Bson::as_document)
+            .filter_map(|entry| Some((usize::try_from(entry.get_i32("index").ok()?).ok()?, entry)))
+    };
+
+    let upserted = entries("upserted").map(|(index, _)| index).collect();
+    let write_errors = entries("writeErrors")
+        .map(|(index, entry)| {
+            let message = entry.get_str("errmsg").unwrap_or("unknown write error");
+            (index, message.to_string())
+        })
+        .collect();
+
+    (upserted, write_errors)
+}
+
+/// Indexes of the last listing of each identity in `listings`, in batch order.
+fn last_by_identity(listings: &[PartyFinderListing]) -> Vec<usize> {
+    let last = listings
+        .iter()
+        .enumerate()
+        .map(|(index, listing)| (listing_identity(listing), index))
+        .collect::<HashMap<_, _>>();
+
+    (0..listings.len())
+        .filter(|index| last[&listing_identity(&listings[*index])] == *index)
+        .collect()
+}
+
+/// One outcome per listing in `listings`, given the `update` reply for `statements` and the rows
+/// stored before the batch. A failed statement fails every listing sharing its identity.
+fn batch_outcomes(
+    listings: &[PartyFinderListing],
+    statements: &[usize],
+    mut stored: HashMap<ListingIdentity, PartyFinderListing>,
+    reply: &Document,
+) -> Vec<Result<UpsertOutcome>> {
+    let (upserted, write_errors) = bulk_write_indexes(reply);
+    let statement_identity = |statement: &usize| listing_identity(&listings[statements[*statement]]);
+    let upserted = upserted.iter().map(statement_identity).collect::<HashSet<_>>();
+    let write_errors = write_errors
+        .iter()
+        .map(|(statement, message)| (statement_identity(statement), message))
+        .collect::<HashMap<_, _>>();
+
+    listings
+        .iter()
+        .map(|listing| {
+            let identity = listing_identity(listing);
+            if let Some(message) = write_errors.get(&identity) {
+                anyhow::bail!("could not insert record: {}", message);
+            }
+
+            let outcome = match stored.get(&identity) {
+                None if upserted.contains(&identity) => UpsertOutcome::Inserted,
+                Some(previous) if previous == listing => UpsertOutcome::Unchanged,
+                _ => UpsertOutcome::Updated,
+            };
+            // later duplicates in the batch compare against this upload, not the stored row
+            stored.insert(identity, listing.clone());
+
+            Ok(outcome)
+        })
+        .collect()
+}
+
+fn listing_identity_filter(listing: &PartyFinderListing) -> Result<Document> {
+    let mut filter = Document::new();
+    filter.insert(LISTING_ID_FIELD, to_bson(&listing.id).context("could not serialize listing.id for upsert filter")?);
+    filter.insert(
+        LISTING_LAST_SERVER_RESTART_FIELD,
+        to_bson(&listing.last_server_restart)
+            .context("could not serialize listing.last_server_restart for upsert filter")?,
+    );
+    filter.insert(
+        LISTING_CREATED_WORLD_FIELD,
+        to_bson(&listing.created_world)
+            .context("could not serialize listing.created_world for upsert filter")?,
+    );
+    Ok(filter)
+}
+
+/// Computes `time_left`/`updated_minute` and drops rows outside the active window.
+fn active_window_stages() -> [Document; 2] {
+    [
+        doc! {
+            "$set": {
+                "time_left": {
+                    "$divide": [
+                        {
+                            "$subtract": [
+                                { "$multiply": ["$listing.seconds_remaining", 1000] },
+                                { "$subtract": ["$$NOW", "$updated_at"] },
+                            ]
+                        },
+                        1000,
+                    ]
+                },
+                "minutes_since_update": {
+                    "$divide": [
+                        { "$subtract": ["$$NOW", "$updated_at"] },
+                        60000,
+                    ]
+                },
+                "updated_minute": {
+                    "$dateTrunc": {
+                        "date": "$updated_at",
+                        "unit": "minute",
+                        "binSize": 5,
+                    }
+                },
+            }
+        },
+        doc! {
+            "$match": {
+                "$and": [
+                    { "time_left": { "$gte": 0 } },
+                    { "minutes_since_update": { "$lt": 5.0 } },
+                ]
+            }
+        },
+    ]
+}
+
+fn active_listings_pipeline(filter: &ListingFilter, now: DateTime<Utc>) -> Vec<Document> {
+    let mut pipeline = vec![doc! {
+        "$match": {
+            "updated_at": { "$gte": active_listing_cutoff(now) },
+            // filter private pfs
+            "listing.search_area": { "$bitsAllClear": SearchAreaFlags::PRIVATE.bits() as i32 },
+        }
+    }];
+    pipeline.extend(filter_stages(filter));
+    pipeline.extend(active_window_stages());
+
+    // The open-slot pushdown is approximate, so deduplicating here could keep a row the exact
+    // check then drops; `active_listings` deduplicates those results itself.
+    if filter.latest_per_player && filter.open_slot_bits.is_empty() {
+        pipeline.extend([
+            doc! {
+                "$sort": {
+                    "updated_at": -1,
+                }
+            },
+            doc! {
+                "$group": {
+                    "_id": "$listing.content_id_lower",
+                    "doc": { "$first": "$$ROOT" },
+                }
+            },
+            doc! {
+                "$replaceRoot": { "newRoot": "$doc" }
+            },
+        ]);
+    }
+
+    pipeline.push(doc! {
+        "$sort": {
+            "updated_minute": -1,
+            "listing.category": -1,
+            "time_left": 1,
+        }
+    });
+
+    pipeline
+}
+
+/// One `$match` stage per constrained [`ListingFilter`] field.
+fn filter_stages(filter: &ListingFilter) -> Vec<Document> {
+    let mut stages = Vec::new();
+
+    let fields = [
+        ("listing.created_world", &filter.created_world_ids),
+        ("listing.home_world", &filter.home_world_ids),
+        ("listing.category", &filter.category_ids),
+        ("listing.duty", &filter.duty_ids),
+        ("listing.duty_type", &filter.duty_type_ids),
+        ("listing.loot_rules", &filter.loot_rule_ids),
+    ];
+    for (field, ids) in fields {
+        if !ids.is_empty() {
+            let ids: Vec<i64> = ids.iter().map(|&id| i64::from(id)).collect();
+            stages.push(doc! {
+                "$match": {
+                    field: { "$in": ids },
+                }
+            });
+        }
+    }
+
+    if !filter.accepted_slot_bits.is_empty() {
+        stages.push(doc! {
+            "$match": {
+                "$or": job_match_conditions(&filter.accepted_slot_bits),
+            }
+        });
+    }
+
+    let mut item_level = Document::new();
+    if let Some(level) = filter.min_item_level_gte {
+        item_level.insert("$gte", i32::from(level));
+    }
+    if let Some(level) = filter.min_item_level_lte {
+        item_level.insert("$lte", i32::from(level));
+    }
+    if !item_level.is_empty() {
+        stages.push(doc! { "$match": { "listing.min_item_level": item_level } });
+    }
+
+    let any_bits = [
+        ("listing.objective", filter.objective_bits_any),
+        ("listing.conditions", filter.condition_bits_any),
+    ];
+    for (field, bits) in any_bits {
+        if let Some(bits) = bits {
+            stages.push(doc! { "$match": { field: { "$bitsAnySet": i64::from(bits) } } });
+        }
+    }
+
+    let search_area_flags = [
+        (SearchAreaFlags::ONE_PLAYER_PER_JOB, filter.one_player_per_job),
+        (SearchAreaFlags::DATA_CENTRE, filter.cross_world),
+    ];
+    for (flag, set) in search_area_flags {
+        if let Some(set) = set {
+            let operator = if set { "$bitsAllSet" } else { "$bitsAllClear" };
+            stages.push(doc! { "$match": { "listing.search_area": { operator: i64::from(flag.bits()) } } });
+        }
+    }
+
+    if let Some(welcome) = filter.beginners_welcome {
+        stages.push(doc! { "$match": { "listing.beginners_welcome": welcome } });
+    }
+
+    if let Some(open) = filter.slots_open_gte {
+        stages.push(doc! { "$match": { "$expr": open_slots_at_least(open) } });
+    }
+
+    // Mongo has no bitwise expression operators before 6.3, so this cannot tie the accepted job
+    // to the unfilled slot; it narrows to listings with both and callers re-check the rest.
+    for group in &filter.open_slot_bits {
+        stages.push(doc! {
+            "$match": {
+                "$or": job_match_conditions(group),
+                "$expr": open_slots_at_least(1),
+            }
+        });
+    }
+
+    stages
+}
+
+fn history_pipeline(query: &HistoryQuery) -> Result<Vec<Document>> {
+    let mut pipeline = vec![doc! {
+        "$match": {
+            "created_at": { "$lt": query.to },
+            "updated_at": { "$gte": query.from },
+            "listing.search_area": { "$bitsAllClear": SearchAreaFlags::PRIVATE.bits() as i32 },
+        }
+    }];
+    pipeline.extend(filter_stages(&query.filter));
+
+    if let Some(after) = &query.after {
+        pipeline.push(doc! {
+            "$match": {
+                "$or": history_cursor_conditions(after)?,
+            }
+        });
+    }
+
+    let limit = i64::try_from(query.limit).context("history page size out of range")?;
+    pipeline.extend([
+        doc! {
+            "$sort": {
+                "updated_at": -1,
+                LISTING_ID_FIELD: -1,
+                LISTING_LAST_SERVER_RESTART_FIELD: -1,
+                LISTING_CREATED_WORLD_FIELD: -1,
+            }
+        },
+        doc! { "$limit": limit },
+        doc! {
+            "$set": {
+                "time_left": "$listing.seconds_remaining",
+                "updated_minute": {
+                    "$dateTrunc": {
+                        "date": "$updated_at",
+                        "unit": "minute",
+                        "binSize": 5,
+                    }
+                },
+            }
+        },
+    ]);
+
+    Ok(pipeline)
+}
+
+/// Rows sorting strictly after `after` in `(updated_at, id, last_server_restart, created_world)`
+/// descending order, as one `$or` branch per key position.
+fn history_cursor_conditions(after: &HistoryCursor) -> Result<Vec<Document>> {
+    let (id, last_server_restart, created_world) = after.identity;
+    let keys = [
+        ("updated_at", Bson::DateTime(after.updated_at.into())),
+        (LISTING_ID_FIELD, to_bson(&id).context("could not serialize history cursor")?),
+        (LISTING_LAST_SERVER_RESTART_FIELD, Bson::Int64(last_server_restart)),
+        (LISTING_CREATED_WORLD_FIELD, Bson::Int32(i32::from(created_world))),
+    ];
+
+    Ok((0..keys.len())
+        .map(|position| {
+            let mut condition = Document::new();
+            for (field, value) in &keys[..position] {
+                condition.insert(*field, value.clone());
+            }
+            let (field, value) = &keys[position];
+            condition.insert(*field, doc! { "$lt": value.clone() });
+            condition
+        })
+        .collect())
+}
+
+/// `slots_available` minus the non-empty `jobs_present` entries is at least `open`.
+fn open_slots_at_least(open: u32) -> Document {
+    doc! {
+        "$gte": [
+            {
+                "$subtract": [
+                    "$listing.slots_available",
+                    {
+                        "$size": {
+                            "$filter": {
+                                "input": "$listing.jobs_present",
+                                "cond": { "$gt": ["$$this", 0] },
+                            }
+                        }
+                    },
+                ]
+            },
+            i64::from(open),
+        ]
+    }
+}
+
+fn job_match_conditions(accepted_slot_bits: &[u64]) -> Vec<Document> {
+    accepted_slot_bits
+        .iter()
+        .map(|&accepted_slot_bit| {
+            let accepted_slot_bit =
+                i64::try_from(accepted_slot_bit).expect("accepted slot bit must fit Mongo bit query");
+
+            doc! {
+                "listing.slots": {
+                    "$elemMatch": {
+                        "accepting": {
+                            "$bitsAllSet": accepted_slot_bit
+                        }
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDE_LISTING_ID: u64 = 4_294_967_296;
+
+    const LISTING_FIXTURE: &str = r###"
+{
+  "id": 4294967419,
+  "content_id_lower": 456,
+  "name": "VGVzdCBOYW1l",
+  "description": "VGhpcyBpcyBteSB0ZXN0IGRlc2NyaXB0aW9uLg==",
+  "created_world": 1001,
+  "home_world": 1001,
+  "current_world": 1001,
+  "category": 0,
+  "duty": 55,
+  "duty_type": 2,
+  "beginners_welcome": false,
+  "seconds_remaining": 3300,
+  "min_item_level": 0,
+  "num_parties": 1,
+  "slots_available": 7,
+  "last_server_restart": 1234567890,
+  "objective": 3,
+  "conditions": 1,
+  "duty_finder_settings": 0,
+  "loot_rules": 0,
+  "search_area": 1,
+  "slots": [
+    {
+      "accepting": 167772160
+    }
+  ],
+  "jobs_present": [5, 0, 0, 0, 0, 0, 0, 0]
+}"###;
+
+    /// A store on a fresh database of the MongoDB at `RPF_TEST_MONGODB_URL`, dropped first so
+    /// reruns start empty. Tests that need one pass without running when it is unset.
+    async fn test_store(test: &str) -> Option<MongoStore> {
+        let url = std::env::var("RPF_TEST_MONGODB_URL").ok()?;
+        let store = MongoStore::connect_to(&url, &format!("rpf_test_{test}")).await.unwrap();
+        store.database().drop(None).await.unwrap();
+        store.prepare().await.unwrap();
+        Some(store)
+    }
+
+    fn fixture_listing() -> PartyFinderListing {
+        serde_json::from_str(LISTING_FIXTURE).expect("listing fixture must deserialize")
+    }
+
+    fn wide_listing_fixture(content_id_lower: u32) -> PartyFinderListing {
+        let mut listing = fixture_listing();
+        listing.id = WIDE_LISTING_ID;
+        listing.content_id_lower = content_id_lower;
+        listing
+    }
+
+    #[tokio::test]
+    async fn empty_batches_make_no_round_trip() {
+        // nothing listens here, so any command would fail once server selection times out
+        let store = MongoStore::connect_to("mongodb://127.0.0.1:9/?serverSelectionTimeoutMS=100", "rpf_test")
+            .await
+            .unwrap();
+
+        assert!(store.bulk_upsert(&[], None, Utc::now()).await.unwrap().is_empty());
+        assert!(store.upsert_many(&[], None, Utc::now()).await.is_empty());
+        assert!(store.bulk_upsert(&[fixture_listing()], None, Utc::now()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rows_that_fail_to_decode_are_skipped() {
+        let Some(store) = test_store("undecodable_rows").await else { return };
+        let now = Utc::now();
+        let good = fixture_listing();
+        let mut bad = fixture_listing();
+        bad.id += 1;
+        store.upsert_many(&[good.clone(), bad.clone()], None, now).await;
+        store
+            .collection()
+            .clone_with_type::<Document>()
+            .update_one(
+                listing_identity_filter(&bad).unwrap(),
+                doc! { "$set": { "listing.slots": "not a list of slots" } },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let listings = store.active_listings(&ListingFilter::default()).await.unwrap();
+        assert_eq!(listings.iter().map(|listing| listing.listing.id).collect::<Vec<_>>(), [good.id]);
+        assert!(store.listings_by_id(bad.id).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn listing_identity_filter_keeps_legacy_rows_non_reconstructable() {
+        let listing = fixture_listing();
+
+        let filter = listing_identity_filter(&listing).expect("filter must serialize");
+
+        assert_eq!(filter.len(), 3);
+        assert_eq!(filter.get(LISTING_ID_FIELD), Some(&to_bson(&listing.id).unwrap()));
+        assert_eq!(
+            filter.get(LISTING_LAST_SERVER_RESTART_FIELD),
+            Some(&to_bson(&listing.last_server_restart).unwrap()),
+        );
+        assert_eq!(
+            filter.get(LISTING_CREATED_WORLD_FIELD),
+            Some(&to_bson(&listing.created_world).unwrap()),
+        );
+        assert!(!filter.contains_key("listing.content_id_lower"));
+    }
+
+    #[test]
+    fn listing_identity_index_keys_has_three_canonical_fields() {
+        let keys = listing_identity_index_keys();
+        assert_eq!(keys.len(), 3);
+        assert!(keys.contains_key(LISTING_ID_FIELD));
+        assert!(keys.contains_key(LISTING_LAST_SERVER_RESTART_FIELD));
+        assert!(keys.contains_key(LISTING_CREATED_WORLD_FIELD));
+    }
+
+    #[test]
+    fn listing_id_index_keys_has_single_field() {
+        let keys = listing_id_index_keys();
+        assert_eq!(keys.len(), 1);
+        assert!(keys.contains_key(LISTING_ID_FIELD));
+    }
+
+    #[test]
+    fn canonical_upsert_filter_is_independent_of_non_key_fields() {
+        let first = wide_listing_fixture(456);
+        let mut second = wide_listing_fixture(789);
+        second.seconds_remaining = first.seconds_remaining - 1;
+
+        let first_filter = listing_identity_filter(&first).expect("first filter must build");
+        let second_filter = listing_identity_filter(&second).expect("second filter must build");
+
+        assert_eq!(first_filter, second_filter);
+        assert_eq!(first_filter.len(), 3);
+        assert!(!first_filter.contains_key("listing.content_id_lower"));
+    }
+
+    #[test]
+    fn beastmaster_job_condition_uses_canonical_slot_bit() {
+        assert_eq!(
+            job_match_conditions(&[1u64 << 32]),
+            vec![doc! {
+                "listing.slots": {
+                    "$elemMatch": {
+                        "accepting": {
+                            "$bitsAllSet": 4_294_967_296i64,
+                        }
+                    }
+                }
+            }]
+        );
+    }
+
+    #[test]
+    fn flag_filters_use_bitwise_matches() {
+        let stages = filter_stages(&ListingFilter {
+            objective_bits_any: Some(4),
+            one_player_per_job: Some(false),
+            cross_world: Some(true),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            stages,
+            vec![
+                doc! { "$match": { "listing.objective": { "$bitsAnySet": 4i64 } } },
+                doc! { "$match": { "listing.search_area": { "$bitsAllClear": i64::from(SearchAreaFlags::ONE_PLAYER_PER_JOB.bits()) } } },
+                doc! { "$match": { "listing.search_area": { "$bitsAllSet": 1i64 } } },
+            ]
+        );
+    }
+
+    #[test]
+    fn active_pipeline_only_dedupes_when_asked() {
+        let now = Utc::now();
+        let has_group = |pipeline: &[Document]| pipeline.iter().any(|stage| stage.contains_key("$group"));
+
+        assert!(!has_group(&active_listings_pipeline(&ListingFilter::default(), now)));
+        assert!(has_group(&active_listings_pipeline(
+            &ListingFilter {
+                latest_per_player: true,
+                ..Default::default()
+            },
+            now,
+        )));
+        assert!(!has_group(&active_listings_pipeline(
+            &ListingFilter {
+                open_slot_bits: vec![vec![1]],
+                latest_per_player: true,
+                ..Default::default()
+            },
+            now,
+        )));
+    }
+
+    /// The newer listing has an open slot and a slot accepting the job, which is all the Mongo
+    /// pushdown can check, but the accepting slot is the filled one. Only the older one matches.
+    async fn latest_listing_with_an_open_slot(store: &dyn ListingStore) -> Vec<u64> {
+        let now = Utc::now();
+        let mut older = fixture_listing();
+        older.id = 1;
+        older.jobs_present = vec![0; 8];
+        let mut newer = fixture_listing();
+        newer.id = 2;
+        store.upsert(&older, None, now - chrono::Duration::minutes(1)).await.unwrap();
+        store.upsert(&newer, None, now).await.unwrap();
+
+        let filter = ListingFilter {
+            open_slot_bits: vec![vec![167772160]],
+            latest_per_player: true,
+            ..Default::default()
+        };
+        let listings = store.active_listings(&filter).await.unwrap();
+        listings.iter().map(|listing| listing.listing.id).collect()
+    }
+
+    #[tokio::test]
+    async fn latest_per_player_is_picked_after_the_exact_open_slot_check() {
+        assert_eq!(latest_listing_with_an_open_slot(&crate::store::MemoryStore::new()).await, vec![1]);
+        let Some(store) = test_store("latest_open_slot").await else { return };
+        assert_eq!(latest_listing_with_an_open_slot(&store).await, vec![1]);
+    }
+
+    #[test]
+    fn bulk_write_indexes_reads_upserts_and_errors_by_statement() {
+        let reply = doc! {
+            "n": 3,
+            "nModified": 1,
+            "upserted": [
+                { "index": 0, "_id": 1 },
+                { "index": 2, "_id": 2 },
+            ],
+            "writeErrors": [
+                { "index": 1, "code": 11000, "errmsg": "E11000 duplicate key error" },
+            ],
+            "ok": 1.0,
+        };
+
+        let (upserted, write_errors) = bulk_write_indexes(&reply);
+
+        assert_eq!(upserted, HashSet::from([0, 2]));
+        assert_eq!(write_errors.len(), 1);
+        assert_eq!(write_errors[&1], "E11000 duplicate key error");
+        assert_eq!(bulk_write_indexes(&doc! { "n": 0, "ok": 1.0 }), Default::default());
+    }
+
+    #[test]
+    fn duplicate_identities_send_the_last_listing_and_report_in_order() {
+        let first = wide_listing_fixture(456);
+        let mut second = first.clone();
+        second.min_item_level = 600;
+        let mut other = fixture_listing();
+        other.created_world = 1002;
+        let listings = [first, other.clone(), second];
+
+        let statements = last_by_identity(&listings);
+        assert_eq!(statements, [1, 2]);
+
+        // the new identity is upserted by its last listing, statement 1
+        let reply = doc! { "n": 2, "upserted": [{ "index": 1, "_id": 1 }], "ok": 1.0 };
+        let stored = HashMap::from([(listing_identity(&other), other)]);
+        let outcomes = batch_outcomes(&listings, &statements, stored, &reply)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        assert_eq!(outcomes, [UpsertOutcome::Inserted, UpsertOutcome::Unchanged, UpsertOutcome::Updated]);
+
+        let reply = doc! {
+            "n": 1,
+            "writeErrors": [{ "index": 1, "code": 11000, "errmsg": "E11000 duplicate key error" }],
+            "ok": 1.0,
+        };
+        let outcomes = batch_outcomes(&listings, &statements, HashMap::new(), &reply);
+        assert!(outcomes[0].is_err() && outcomes[2].is_err());
+        assert_eq!(outcomes[1].as_ref().unwrap(), &UpsertOutcome::Updated);
+    }
+}
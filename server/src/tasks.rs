@@ -0,0 +1,185 @@
+//! Runs the server's periodic background jobs, retrying failures with exponential backoff and
+//! keeping each job's last outcome so it can be inspected while the server runs.
+
+use std::{
+    collections::{hash_map::RandomState, BTreeMap},
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// When a job runs again after it succeeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// Right away, then this long after each success.
+    Every(Duration),
+    /// Right away, and never again once it has succeeded. Used for one-off setup such as
+    /// creating indexes.
+    UntilSuccess,
+}
+
+/// Delay before retrying a failed job: `base` doubled per consecutive failure up to `max`, of
+/// which a random half is added as jitter so restarted nodes do not retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl Backoff {
+    /// `failures` counts the failure that was just recorded, so it starts at 1. `jitter` is in
+    /// `[0, 1)`.
+    pub fn delay(&self, failures: u32, jitter: f64) -> Duration {
+        let exponent = failures.saturating_sub(1).min(31);
+        let ceiling = self.base.saturating_mul(1 << exponent).min(self.max);
+        ceiling / 2 + ceiling.mul_f64(jitter.clamp(0.0, 1.0) / 2.0)
+    }
+}
+
+/// What the admin route shows for one job.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JobStatus {
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    /// Unset while the job runs, and once an [`Schedule::UntilSuccess`] job is done.
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Default)]
+pub struct Supervisor {
+    jobs: Arc<Mutex<BTreeMap<&'static str, JobStatus>>>,
+}
+
+impl Supervisor {
+    /// Spawns `job` on the runtime. Errors are logged and retried after [`Backoff::delay`];
+    /// the job never stops unless it is [`Schedule::UntilSuccess`] and has succeeded.
+    pub fn spawn<F, Fut>(&self, name: &'static str, schedule: Schedule, backoff: Backoff, mut job: F)
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.jobs.lock().unwrap().entry(name).or_default();
+
+        let supervisor = self.clone();
+        tokio::task::spawn(async move {
+            loop {
+                let result = job().await;
+                let delay = supervisor.record(name, schedule, backoff, result);
+                match delay {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => break,
+                }
+            }
+        });
+    }
+
+    fn record(
+        &self,
+        name: &'static str,
+        schedule: Schedule,
+        backoff: Backoff,
+        result: Result<()>,
+    ) -> Option<Duration> {
+        let now = Utc::now();
+        let mut jobs = self.jobs.lock().unwrap();
+        let status = jobs.entry(name).or_default();
+
+        let delay = match result {
+            Ok(()) => {
+                status.last_success_at = Some(now);
+                status.consecutive_failures = 0;
+                match schedule {
+                    Schedule::Every(period) => Some(period),
+                    Schedule::UntilSuccess => None,
+                }
+            }
+            Err(error) => {
+                eprintln!("job {name} failed: {error:#?}");
+                status.last_error_at = Some(now);
+                status.last_error = Some(format!("{error:#}"));
+                status.consecutive_failures = status.consecutive_failures.saturating_add(1);
+                Some(backoff.delay(status.consecutive_failures, jitter()))
+            }
+        };
+
+        status.next_run_at = delay
+            .and_then(|delay| chrono::Duration::from_std(delay).ok())
+            .map(|delay| now + delay);
+        delay
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<&'static str, JobStatus> {
+        self.jobs.lock().unwrap().clone()
+    }
+}
+
+/// A number in `[0, 1)`; `RandomState` is seeded randomly, which is all jitter needs.
+pub fn jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_with_jitter_in_the_upper_half() {
+        let backoff = Backoff {
+            base: Duration::from_secs(2),
+            max: Duration::from_secs(60),
+        };
+
+        assert_eq!(backoff.delay(1, 0.0), Duration::from_secs(1));
+        assert!(backoff.delay(1, 0.999) < Duration::from_secs(2));
+        assert_eq!(backoff.delay(3, 0.0), Duration::from_secs(4));
+        assert_eq!(backoff.delay(10, 0.0), Duration::from_secs(30));
+        assert_eq!(backoff.delay(u32::MAX, 0.5), Duration::from_secs(45));
+        assert!((0..100).map(|_| jitter()).all(|jitter| (0.0..1.0).contains(&jitter)));
+    }
+
+    #[tokio::test]
+    async fn failing_job_is_retried_until_it_succeeds() {
+        let supervisor = Supervisor::default();
+        let attempts = Arc::new(AtomicU32::new(0));
+        let job_attempts = Arc::clone(&attempts);
+        supervisor.spawn(
+            "flaky",
+            Schedule::UntilSuccess,
+            Backoff {
+                base: Duration::from_millis(1),
+                max: Duration::from_millis(4),
+            },
+            move || {
+                let attempt = job_attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    anyhow::ensure!(attempt >= 3, "attempt {attempt} failed");
+                    Ok(())
+                }
+            },
+        );
+
+        for _ in 0..100 {
+            if supervisor.snapshot()["flaky"].last_success_at.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let status = &supervisor.snapshot()["flaky"];
+        assert!(status.last_success_at.is_some());
+        assert_eq!(status.last_error.as_deref(), Some("attempt 2 failed"));
+        assert_eq!(status.consecutive_failures, 0);
+        assert_eq!(status.next_run_at, None);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,26 @@
+use askama::Template;
+use crate::ffxiv::Language;
+
+/// Escaped like HTML, which is also valid for the XML of an Atom document.
+#[derive(Debug, Template)]
+#[template(path = "listings.atom", escape = "html")]
+pub struct ListingsFeedTemplate {
+    pub id: String,
+    pub updated: String,
+    pub self_href: String,
+    pub next_href: Option<String>,
+    pub entries: Vec<ListingsFeedEntry>,
+    pub lang: Language,
+}
+
+#[derive(Debug)]
+pub struct ListingsFeedEntry {
+    pub id: String,
+    pub title: String,
+    pub author: String,
+    pub published: String,
+    pub updated: String,
+    pub category_term: &'static str,
+    pub category_label: String,
+    pub content: String,
+}
@@ -1,13 +1,219 @@
 use askama::Template;
 use crate::listing_container::QueriedListing;
 use std::borrow::Borrow;
-use crate::ffxiv::Language;
+use crate::ffxiv::{Language, LocalisedText};
 use crate::sestring_ext::SeStringExt;
-use crate::listing::PartyFinderCategory;
+use crate::listing::{DutyCategory, DutyFinderSettingsFlags, PartyFinderListing, SearchAreaFlags};
+use crate::web::v2::{contracts::Pagination, filters::ListingsQuery, id_inventory, lookups};
 
 #[derive(Debug, Template)]
 #[template(path = "listings.html")]
 pub struct ListingsTemplate {
     pub containers: Vec<QueriedListing>,
+    pub pagination: Pagination,
+    /// The filters the page was rendered with, to fill the form back in.
+    pub query: ListingsQuery,
+    pub lang: Language,
+    pub error: Option<String>,
+    pub previous_href: Option<String>,
+    pub next_href: Option<String>,
+    pub categories: Vec<(u32, &'static str)>,
+    pub jobs: Vec<(u32, String)>,
+}
+
+/// The data centres offered by the filter form, grouped by region.
+pub const DATA_CENTRE_GROUPS: [(&str, &[&str]); 3] = [
+    ("china", &["陆行鸟", "莫古力", "猫小胖", "豆豆柴"]),
+    ("korean", &["한국"]),
+    ("traditional chinese", &["陸行鳥"]),
+];
+
+impl ListingsTemplate {
+    pub fn new(
+        containers: Vec<QueriedListing>,
+        pagination: Pagination,
+        query: ListingsQuery,
+        lang: Language,
+    ) -> Self {
+        let categories = id_inventory::CATEGORY_IDS
+            .into_iter()
+            .filter_map(|id| Some((id, DutyCategory::from_u32(id)?.pf_category().name().text(&lang))))
+            .collect();
+        let jobs = lookups::jobs()
+            .into_iter()
+            .filter(|job| job.accepted_in_slots)
+            .map(|job| (job.id, job.code))
+            .collect();
+
+        Self {
+            containers,
+            pagination,
+            query,
+            lang,
+            error: None,
+            previous_href: None,
+            next_href: None,
+            categories,
+            jobs,
+        }
+    }
+
+    pub fn with_error(mut self, error: String) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    pub fn with_page_links(mut self, previous_href: Option<String>, next_href: Option<String>) -> Self {
+        self.previous_href = previous_href;
+        self.next_href = next_href;
+        self
+    }
+
+    fn data_centre_selected(&self, name: &str) -> bool {
+        self.query
+            .datacenter
+            .as_deref()
+            .is_some_and(|selected| selected.split(',').any(|selected| selected.trim() == name))
+    }
+
+    fn category_selected(&self, id: &u32) -> bool {
+        self.query.category_id == Some(*id) || self.query.category_ids.contains(id)
+    }
+
+    fn job_selected(&self, id: &u32) -> bool {
+        self.query.job_ids.contains(id)
+    }
+}
+
+/// One listing with every flag spelled out, at `/listings/{id}`.
+#[derive(Debug, Template)]
+#[template(path = "listing.html")]
+pub struct ListingTemplate {
+    pub container: QueriedListing,
+    pub lang: Language,
+    /// OpenGraph and Twitter card text, so a pasted link previews the listing.
+    pub preview_title: String,
+    pub preview_description: String,
+    pub details: Vec<(&'static str, String)>,
+    pub slots: Vec<SlotDetail>,
+}
+
+#[derive(Debug)]
+pub struct SlotDetail {
+    pub filled: bool,
+    pub role_class: String,
+    pub jobs: String,
+}
+
+/// Longest `og:description`; link previews cut them off around here anyway.
+const PREVIEW_DESCRIPTION_CHARS: usize = 200;
+
+impl ListingTemplate {
+    pub fn new(container: QueriedListing, lang: Language) -> Self {
+        let listing = &container.listing;
+        let host = format!("{} @ {}", listing.name.full_text(&lang), listing.home_world_string());
+        let description = listing.description.full_text(&lang);
+        let description = description.trim();
+        let preview_description = if description.is_empty() {
+            host
+        } else {
+            let mut text = format!("{host}: {description}");
+            if text.chars().count() > PREVIEW_DESCRIPTION_CHARS {
+                text = text.chars().take(PREVIEW_DESCRIPTION_CHARS - 1).collect();
+                text.push('…');
+            }
+            text
+        };
+
+        Self {
+            preview_title: listing.duty_name(&lang).into_owned(),
+            preview_description,
+            details: listing_details(listing, &lang),
+            slots: slot_details(listing),
+            container,
+            lang,
+        }
+    }
+}
+
+fn listing_details(listing: &PartyFinderListing, lang: &Language) -> Vec<(&'static str, String)> {
+    let labels = |labels: Vec<&LocalisedText>| match labels.is_empty() {
+        true => "None".to_string(),
+        false => labels.iter().map(|label| label.text(lang)).collect::<Vec<_>>().join(", "),
+    };
+    let yes_no = |value: bool| if value { "Yes" } else { "No" }.to_string();
+    let objectives = id_inventory::objective_ids(listing.objective)
+        .into_iter()
+        .filter_map(lookups::objective_label)
+        .collect();
+    let conditions = id_inventory::condition_ids(listing.conditions)
+        .into_iter()
+        .filter_map(lookups::condition_label)
+        .collect();
+    let loot_rules = lookups::loot_rule_label(id_inventory::loot_rule_id(listing.loot_rules))
+        .into_iter()
+        .collect();
+
+    let mut duty_finder_settings = Vec::new();
+    if listing.duty_finder_settings.contains(DutyFinderSettingsFlags::UNDERSIZED_PARTY) {
+        duty_finder_settings.push("Undersized Party");
+    }
+    if listing.duty_finder_settings.contains(DutyFinderSettingsFlags::MINIMUM_ITEM_LEVEL) {
+        duty_finder_settings.push("Minimum Item Level");
+    }
+    if listing.duty_finder_settings.contains(DutyFinderSettingsFlags::SILENCE_ECHO) {
+        duty_finder_settings.push("Silence Echo");
+    }
+
+    vec![
+        ("Category", listing.pf_category().name().text(lang).to_string()),
+        ("Objective", labels(objectives)),
+        ("Conditions", labels(conditions)),
+        ("Loot Rules", labels(loot_rules)),
+        ("Duty Finder Settings", match duty_finder_settings.is_empty() {
+            true => "None".to_string(),
+            false => duty_finder_settings.join(", "),
+        }),
+        ("Min IL", listing.min_item_level.to_string()),
+        ("Parties", listing.num_parties.to_string()),
+        ("Data Centre", listing.data_centre_name().unwrap_or("Unknown").to_string()),
+        ("Cross-World", yes_no(listing.is_cross_world())),
+        ("One Player per Job", yes_no(listing.search_area.contains(SearchAreaFlags::ONE_PLAYER_PER_JOB))),
+        ("Beginners Welcome", yes_no(listing.beginners_welcome)),
+    ]
+}
+
+fn slot_details(listing: &PartyFinderListing) -> Vec<SlotDetail> {
+    listing
+        .slots()
+        .into_iter()
+        .map(|slot| match slot {
+            Ok(job) => SlotDetail {
+                filled: true,
+                role_class: job
+                    .role()
+                    .map(|role| role.as_str().to_lowercase())
+                    .unwrap_or_default(),
+                jobs: job.code().to_string(),
+            },
+            Err((role_class, codes)) => SlotDetail {
+                filled: false,
+                jobs: match role_class.as_str() {
+                    "empty" => "Any".to_string(),
+                    // no slot entry was uploaded for this slot
+                    "" => "Unknown".to_string(),
+                    _ => codes,
+                },
+                role_class,
+            },
+        })
+        .collect()
+}
+
+/// What `/listings/{id}` shows once the listing is gone, or was never visible.
+#[derive(Debug, Template)]
+#[template(path = "listing_expired.html")]
+pub struct ListingExpiredTemplate {
+    pub id: String,
     pub lang: Language,
 }
@@ -1,2 +1,3 @@
+pub mod feeds;
 pub mod listings;
 pub mod stats;
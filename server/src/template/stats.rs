@@ -1,6 +1,7 @@
 use askama::Template;
-use crate::ffxiv::Language;
-use crate::stats::Statistics;
+use crate::ffxiv::{Language, LocalisedText};
+use crate::stats::{DutyInfo, DutyStatistics, Statistics};
+use crate::web::v2::{id_inventory, lookups};
 
 #[derive(Debug, Template)]
 #[template(path = "stats.html")]
@@ -8,3 +9,43 @@ pub struct StatsTemplate {
     pub stats: Statistics,
     pub lang: Language,
 }
+
+#[derive(Debug, Template)]
+#[template(path = "duty_stats.html")]
+pub struct DutyStatsTemplate {
+    pub duty: DutyInfo,
+    pub stats: DutyStatistics,
+    pub seven_days: bool,
+    pub lang: Language,
+}
+
+impl DutyStatsTemplate {
+    /// Path of this duty's page, relative to `/stats` or `/stats/7days`.
+    pub fn duty_path(&self) -> String {
+        let (duty_type, category, duty) = self.duty.info;
+        format!("duty/{}/{}/{}", duty_type, category, duty)
+    }
+
+    pub fn objective_name(&self, flag: &u32) -> &'static str {
+        label(&id_inventory::OBJECTIVE_IDS, &lookups::OBJECTIVE_LABELS, *flag, &self.lang)
+    }
+
+    pub fn condition_name(&self, flag: &u32) -> &'static str {
+        label(&id_inventory::CONDITION_IDS, &lookups::CONDITION_LABELS, *flag, &self.lang)
+    }
+}
+
+fn label(ids: &[u32], labels: &[LocalisedText], id: u32, lang: &Language) -> &'static str {
+    ids.iter()
+        .position(|candidate| *candidate == id)
+        .and_then(|index| labels.get(index))
+        .map(|label| label.text(lang))
+        .unwrap_or("<unknown>")
+}
+
+/// Shown by the stats pages until the first aggregation has finished.
+#[derive(Debug, Template)]
+#[template(path = "stats_warming_up.html")]
+pub struct StatsWarmingUpTemplate {
+    pub lang: Language,
+}
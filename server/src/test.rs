@@ -80,7 +80,37 @@ lazy_static::lazy_static! {
     };
 }
 
-fn listing_json_with_id(id: u64) -> String {
+/// A public high-end duty listing on world 1042 with no name, description or slots. Tests
+/// override what they need with struct update syntax: `PartyFinderListing { duty: 56, ..listing() }`.
+pub(crate) fn listing() -> PartyFinderListing {
+    PartyFinderListing {
+        id: 1,
+        content_id_lower: 1,
+        name: SeString(Vec::new()),
+        description: SeString(Vec::new()),
+        created_world: 1042,
+        home_world: 1042,
+        current_world: 1042,
+        category: DutyCategory::HighEndDuty,
+        last_server_restart: 1000,
+        duty: 55,
+        duty_type: DutyType::Normal,
+        beginners_welcome: false,
+        seconds_remaining: 1200,
+        min_item_level: 0,
+        num_parties: 1,
+        slots_available: 8,
+        objective: ObjectiveFlags::PRACTICE | ObjectiveFlags::DUTY_COMPLETION,
+        conditions: ConditionFlags::NONE,
+        duty_finder_settings: DutyFinderSettingsFlags::NONE,
+        loot_rules: LootRuleFlags::NONE,
+        search_area: SearchAreaFlags::DATA_CENTRE,
+        slots: Vec::new(),
+        jobs_present: Vec::new(),
+    }
+}
+
+pub(crate) fn listing_json_with_id(id: u64) -> String {
     LISTING.replacen(
         &format!("\"id\": {NARROW_LISTING_ID},"),
         &format!("\"id\": {id},"),
@@ -251,10 +281,11 @@ mod beastmaster_web_compat {
                 .expect("must read icons asset");
         assert_eq!(icons.matches(r#"id="BST""#).count(), 1);
 
+        // the slot markup is shared by /listings and /listings/{id}
         let listings_template = fs::read_to_string(
-            Path::new(env!("CARGO_MANIFEST_DIR")).join("templates/listings.html"),
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("templates/_listing.html"),
         )
-        .expect("must read listings template");
+        .expect("must read listing template");
         assert!(
             listings_template.contains(r#"<use href="/assets/icons.svg#{{ title }}"></use>"#),
             "listings template must keep existing icon contract"
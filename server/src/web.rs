@@ -1,46 +1,58 @@
 use std::{
-    cmp::Ordering,
     convert::Infallible,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
     collections::HashMap,
 };
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use mongodb::{
-    bson::{doc, to_bson, Document},
-    Client as MongoClient,
-    Collection,
-    IndexModel,
-    options::{IndexOptions, UpdateOptions},
-    results::UpdateResult,
-};
 use tokio::sync::RwLock;
-use tokio_stream::StreamExt;
 use warp::{
     Filter,
     filters::BoxedFilter,
-    http::Uri,
+    http::{StatusCode, Uri},
+    reply::Response,
     Reply,
 };
 
 use crate::{
-    config::Config,
+    config::{Config, StorageKind},
+    discord::Notifier,
     ffxiv::Language,
     listing::PartyFinderListing,
-    listing_container::{ListingContainer, QueriedListing},
-    sestring_ext::SeStringExt,
-    stats::CachedStatistics,
-    template::listings::ListingsTemplate,
-    template::stats::StatsTemplate,
+    metrics::Metrics,
+    stats::{CachedDutyStatistics, CachedStatistics, DutyKey},
+    store::{ListingStore, MemoryStore, MongoStore},
+    tasks::{Backoff, Schedule, Supervisor},
+    template::listings::{ListingExpiredTemplate, ListingTemplate, ListingsTemplate},
+    template::stats::{DutyStatsTemplate, StatsTemplate, StatsWarmingUpTemplate},
+    webhooks::Dispatcher,
 };
 
+mod admin;
+mod contribute;
+#[cfg(test)]
+mod contribute_bench;
+mod feeds;
+mod health;
+mod limits;
+mod metrics;
 mod stats;
+mod uploaders;
 pub mod api;
 pub mod v2;
 
 use crate::web::api::{ApiResponse, DetailedApiListing, ApiListing};
+use crate::web::contribute::{validate_listing, ContributeResponse, ListingRejection, ListingResult};
+use crate::web::health::Health;
+use crate::web::limits::{contribute_guard, recover_contribute_rejection, ContributeLimits, Contributor};
+use crate::web::uploaders::Uploaders;
+use crate::web::v2::filters::{parse_listings_query, ListingsQuery};
+use crate::web::v2::listings::{
+    empty_collection_response, matching_listings, paginated_collection_response, resolve_visible_listing,
+};
+use crate::web::v2::stream::ListingEvents;
 
 pub async fn start(config: Arc<Config>) -> Result<()> {
     let state = State::new(Arc::clone(&config)).await?;
@@ -53,10 +65,22 @@ pub async fn start(config: Arc<Config>) -> Result<()> {
 }
 
 pub struct State {
-    mongo: MongoClient,
+    store: Arc<dyn ListingStore>,
     stats: RwLock<Option<CachedStatistics>>,
+    duty_stats: RwLock<HashMap<DutyKey, CacheEntry<Arc<CachedDutyStatistics>>>>,
+    /// One lock per duty whose statistics are being computed; see [`stats::get_duty_stats`].
+    duty_stats_loads: std::sync::Mutex<HashMap<DutyKey, Arc<tokio::sync::Mutex<()>>>>,
     listings_cache: RwLock<ListingsCache>,
     detail_cache: RwLock<DetailCache>,
+    listing_events: Arc<ListingEvents>,
+    contribute_limits: ContributeLimits,
+    uploaders: Uploaders,
+    metrics: Metrics,
+    health: Health,
+    tasks: Supervisor,
+    admin: crate::config::Admin,
+    webhooks: Arc<Dispatcher>,
+    discord: Arc<Notifier>,
 }
 
 struct CacheEntry<T> {
@@ -72,232 +96,196 @@ struct DetailCache {
     entries: HashMap<u64, CacheEntry<DetailedApiListing>>,
 }
 
-const LISTING_ID_FIELD: &str = "listing.id";
-const LISTING_LAST_SERVER_RESTART_FIELD: &str = "listing.last_server_restart";
-const LISTING_CREATED_WORLD_FIELD: &str = "listing.created_world";
-const ACTIVE_LISTING_WINDOW_HOURS: i64 = 2;
-
-fn listing_identity_index_keys() -> Document {
-    let mut keys = Document::new();
-    keys.insert(LISTING_ID_FIELD, 1);
-    keys.insert(LISTING_LAST_SERVER_RESTART_FIELD, 1);
-    keys.insert(LISTING_CREATED_WORLD_FIELD, 1);
-    keys
-}
-
-fn listing_id_index_keys() -> Document {
-    let mut keys = Document::new();
-    keys.insert(LISTING_ID_FIELD, 1);
-    keys
-}
-
-fn listing_identity_filter(listing: &PartyFinderListing) -> Result<Document> {
-    let mut filter = Document::new();
-    filter.insert(LISTING_ID_FIELD, to_bson(&listing.id).context("could not serialize listing.id for upsert filter")?);
-    filter.insert(
-        LISTING_LAST_SERVER_RESTART_FIELD,
-        to_bson(&listing.last_server_restart)
-            .context("could not serialize listing.last_server_restart for upsert filter")?,
-    );
-    filter.insert(
-        LISTING_CREATED_WORLD_FIELD,
-        to_bson(&listing.created_world)
-            .context("could not serialize listing.created_world for upsert filter")?,
-    );
-    Ok(filter)
-}
+const LISTING_EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(15);
+const CACHE_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+const STATS_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 5);
 
-fn active_listing_cutoff(now: DateTime<Utc>) -> DateTime<Utc> {
-    // Legacy coexistence is bounded to the active-listing window; once a truncated historical row
-    // ages out of this window, active reads stop surfacing it.
-    now - chrono::Duration::hours(ACTIVE_LISTING_WINDOW_HOURS)
-}
+const SETUP_BACKOFF: Backoff = Backoff {
+    base: Duration::from_secs(1),
+    max: Duration::from_secs(60),
+};
+const STATS_BACKOFF: Backoff = Backoff {
+    base: Duration::from_secs(5),
+    max: STATS_REFRESH_INTERVAL,
+};
+const SWEEP_BACKOFF: Backoff = Backoff {
+    base: Duration::from_secs(1),
+    max: Duration::from_secs(60),
+};
+const WEBHOOKS_BACKOFF: Backoff = Backoff {
+    base: Duration::from_secs(1),
+    max: Duration::from_secs(60),
+};
+const DISCORD_BACKOFF: Backoff = Backoff {
+    base: Duration::from_secs(1),
+    max: Duration::from_secs(60),
+};
 
 impl State {
     pub async fn new(config: Arc<Config>) -> Result<Arc<Self>> {
-        let mongo = MongoClient::with_uri_str(&config.mongo.url)
-            .await
-            .context("could not create mongodb client")?;
+        let store: Arc<dyn ListingStore> = match config.storage.kind {
+            StorageKind::Mongo => {
+                let mongo = config
+                    .mongo
+                    .as_ref()
+                    .context("[mongo] url is required when [storage] kind = \"mongo\"")?;
+                Arc::new(MongoStore::connect(&mongo.url).await?)
+            }
+            StorageKind::Memory => Arc::new(MemoryStore::new()),
+        };
 
         let state = Arc::new(Self {
-            mongo,
+            webhooks: Arc::new(Dispatcher::new(Arc::clone(&store), config.webhooks.clone())),
+            discord: Arc::new(Notifier::new(&config.discord)?),
+            store,
             stats: Default::default(),
+            duty_stats: Default::default(),
+        duty_stats_loads: Default::default(),
             listings_cache: RwLock::new(ListingsCache {
                 entries: HashMap::new(),
             }),
             detail_cache: RwLock::new(DetailCache {
                 entries: HashMap::new(),
             }),
+            listing_events: Default::default(),
+            contribute_limits: ContributeLimits::new(config.contribute.clone()),
+            uploaders: Uploaders::new(config.uploaders.clone()),
+            metrics: Metrics::new(),
+            health: Default::default(),
+            tasks: Default::default(),
+            admin: config.admin.clone(),
         });
 
-        state.collection()
-            .create_index(
-                IndexModel::builder()
-                    .keys(listing_identity_index_keys())
-                    .options(IndexOptions::builder()
-                        .unique(true)
-                        .build())
-                    .build(),
-                None,
-            )
-            .await
-            .context("could not create unique index")?;
-
-        state.collection()
-            .create_index(
-                IndexModel::builder()
-                    .keys(mongodb::bson::doc! {
-                        "updated_at": 1,
-                    })
-                    .build(),
-                None,
-            )
-            .await
-            .context("could not create updated_at index")?;
-
-        state.collection()
-            .create_index(
-                IndexModel::builder()
-                    .keys(listing_id_index_keys())
-                    .build(),
-                None,
-            )
-            .await
-            .context("could not create listing.id index")?;
-            
-        state.collection()
-            .create_index(
-                IndexModel::builder()
-                    .keys(mongodb::bson::doc! {
-                        "listing.category": 1,
-                    })
-                    .build(),
-                None,
-            )
-            .await
-            .context("could not create listing.category index")?;
-            
-        state.collection()
-            .create_index(
-                IndexModel::builder()
-                    .keys(mongodb::bson::doc! {
-                        "listing.created_world": 1,
-                    })
-                    .build(),
-                None,
-            )
-            .await
-            .context("could not create listing.created_world index")?;
-
-        state.collection()
-            .create_index(
-                IndexModel::builder()
-                    .keys(mongodb::bson::doc! {
-                        "listing.search_area": 1,
-                    })
-                    .build(),
-                None,
-            )
-            .await
-            .context("could not create listing.search_area index")?;
-
-        state.collection()
-            .create_index(
-                IndexModel::builder()
-                    .keys(mongodb::bson::doc! {
-                        "listing.slots.job": 1,
-                    })
-                    .build(),
-                None,
-            )
-            .await
-            .context("could not create listing.slots.job index")?;
-            
-        state.collection()
-            .create_index(
-                IndexModel::builder()
-                    .keys(mongodb::bson::doc! {
-                        "listing.slots.accepting_classes": 1,
-                    })
-                    .build(),
-                None,
-            )
-            .await
-            .context("could not create listing.slots.accepting_classes index")?;
-
-        state.collection()
-            .create_index(
-                IndexModel::builder()
-                    .keys(mongodb::bson::doc! {
-                        "listing.duty": 1,
-                    })
-                    .build(),
-                None,
-            )
-            .await
-            .context("could not create listing.duty index")?;
-
-        let task_state = Arc::clone(&state);
-        tokio::task::spawn(async move {
-            loop {
-                let all_time = match self::stats::get_stats(&*task_state).await {
-                    Ok(stats) => stats,
-                    Err(e) => {
-                        eprintln!("error generating stats: {:#?}", e);
-                        continue;
-                    }
-                };
-
-                let seven_days = match self::stats::get_stats_seven_days(&*task_state).await {
-                    Ok(stats) => stats,
-                    Err(e) => {
-                        eprintln!("error generating stats: {:#?}", e);
-                        continue;
-                    }
-                };
-
-                *task_state.stats.write().await = Some(CachedStatistics {
-                    all_time,
-                    seven_days,
-                });
-
-                tokio::time::sleep(Duration::from_secs(60 * 5)).await;
+        state.spawn_background_jobs();
+
+        Ok(state)
+    }
+
+    pub fn store(&self) -> &dyn ListingStore {
+        &*self.store
+    }
+
+    /// Index creation, the stats refresher, the sweepers, webhook delivery and, when configured,
+    /// Discord notifications, all supervised so a store that is down at startup or fails later
+    /// only delays them.
+    fn spawn_background_jobs(self: &Arc<Self>) {
+        let state = Arc::clone(self);
+        self.tasks.spawn("prepare_store", Schedule::UntilSuccess, SETUP_BACKOFF, move || {
+            let state = Arc::clone(&state);
+            async move { state.store().prepare().await }
+        });
+
+        let state = Arc::clone(self);
+        self.tasks.spawn("stats", Schedule::Every(STATS_REFRESH_INTERVAL), STATS_BACKOFF, move || {
+            let state = Arc::clone(&state);
+            async move { state.refresh_stats().await }
+        });
+
+        let state = Arc::clone(self);
+        self.tasks.spawn("cache_sweeper", Schedule::Every(CACHE_SWEEP_INTERVAL), SWEEP_BACKOFF, move || {
+            let state = Arc::clone(&state);
+            async move {
+                state.sweep_caches().await;
+                Ok(())
             }
         });
 
-        let cache_state = Arc::clone(&state);
-        tokio::task::spawn(async move {
-            loop {
-                tokio::time::sleep(Duration::from_secs(60)).await;
-                
-                {
-                    let mut cache = cache_state.listings_cache.write().await;
-                    let now = Utc::now();
-                    cache.entries.retain(|_, entry| entry.expires_at > now);
-                }
-                
-                {
-                    let mut cache = cache_state.detail_cache.write().await;
-                    let now = Utc::now();
-                    cache.entries.retain(|_, entry| entry.expires_at > now);
-                }
+        let state = Arc::clone(self);
+        self.tasks.spawn("listing_expiry", Schedule::Every(LISTING_EXPIRY_SWEEP_INTERVAL), SWEEP_BACKOFF, move || {
+            let state = Arc::clone(&state);
+            async move {
+                state.listing_events.expire_stale(Utc::now());
+                Ok(())
             }
         });
 
-        Ok(state)
+        self.spawn_webhook_dispatcher();
+        if !self.discord.is_empty() {
+            self.spawn_discord_notifier();
+        }
+    }
+
+    /// Runs for as long as the server does; it only stops early on a store error, and is then
+    /// restarted with a fresh subscription to listing events.
+    fn spawn_webhook_dispatcher(self: &Arc<Self>) {
+        let state = Arc::clone(self);
+        self.tasks.spawn("webhooks", Schedule::UntilSuccess, WEBHOOKS_BACKOFF, move || {
+            let state = Arc::clone(&state);
+            async move {
+                let events = state.listing_events.subscribe();
+                Arc::clone(&state.webhooks).run(events).await
+            }
+        });
+    }
+
+    /// Like the webhook dispatcher, runs until shutdown.
+    fn spawn_discord_notifier(self: &Arc<Self>) {
+        let state = Arc::clone(self);
+        self.tasks.spawn("discord", Schedule::UntilSuccess, DISCORD_BACKOFF, move || {
+            let state = Arc::clone(&state);
+            async move {
+                let events = state.listing_events.subscribe();
+                Arc::clone(&state.discord).run(events).await
+            }
+        });
     }
 
-    pub fn collection(&self) -> Collection<ListingContainer> {
-        self.mongo.database("rpf").collection("listings")
+    async fn refresh_stats(&self) -> Result<()> {
+        let started = Instant::now();
+        let all_time = self::stats::get_stats(self).await?;
+        self.metrics.stats_aggregation_duration.observe_duration(&["all_time"], started.elapsed());
+
+        let started = Instant::now();
+        let seven_days = self::stats::get_stats_seven_days(self).await?;
+        self.metrics.stats_aggregation_duration.observe_duration(&["seven_days"], started.elapsed());
+
+        *self.stats.write().await = Some(CachedStatistics {
+            all_time,
+            seven_days,
+        });
+        self.health.stats_refreshed(Utc::now());
+
+        Ok(())
+    }
+
+    async fn sweep_caches(&self) {
+        {
+            let mut cache = self.listings_cache.write().await;
+            let now = Utc::now();
+            let before = cache.entries.len();
+            cache.entries.retain(|_, entry| entry.expires_at > now);
+            self.metrics.cache_evictions.inc_by(&["listings"], (before - cache.entries.len()) as u64);
+        }
+
+        {
+            let mut cache = self.detail_cache.write().await;
+            let now = Utc::now();
+            let before = cache.entries.len();
+            cache.entries.retain(|_, entry| entry.expires_at > now);
+            self.metrics.cache_evictions.inc_by(&["detail"], (before - cache.entries.len()) as u64);
+        }
+
+        {
+            let mut cache = self.duty_stats.write().await;
+            let now = Utc::now();
+            let before = cache.len();
+            cache.retain(|_, entry| entry.expires_at > now);
+            self.metrics.cache_evictions.inc_by(&["duty_stats"], (before - cache.len()) as u64);
+        }
+
+        self.contribute_limits.sweep(Instant::now());
+        self.uploaders.sweep(Instant::now());
     }
 
     pub async fn get_listings_cache(&self, cache_key: &str) -> Option<ApiResponse<Vec<ApiListing>>> {
         let cache = self.listings_cache.read().await;
         if let Some(entry) = cache.entries.get(cache_key) {
             if entry.expires_at > Utc::now() {
+                self.metrics.cache_lookup("listings", true);
                 return Some(entry.data.clone());
             }
         }
+        self.metrics.cache_lookup("listings", false);
         None
     }
     
@@ -311,9 +299,11 @@ impl State {
         let cache = self.detail_cache.read().await;
         if let Some(entry) = cache.entries.get(&id) {
             if entry.expires_at > Utc::now() {
+                self.metrics.cache_lookup("detail", true);
                 return Some(entry.data.clone());
             }
         }
+        self.metrics.cache_lookup("detail", false);
         None
     }
     
@@ -325,34 +315,107 @@ impl State {
 }
 
 pub(crate) fn router(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
+    let log_state = Arc::clone(&state);
     assets()
         .or(listings(Arc::clone(&state)))
+        .or(listing_page(Arc::clone(&state)))
+        .or(self::feeds::routes(Arc::clone(&state)))
         .or(stats(Arc::clone(&state)))
         .or(stats_seven_days(Arc::clone(&state)))
+        .or(duty_stats(Arc::clone(&state)))
+        .or(duty_stats_seven_days(Arc::clone(&state)))
         .or(contribute(Arc::clone(&state)))
         .or(contribute_multiple(Arc::clone(&state)))
         .or(crate::web::api::listings_api(Arc::clone(&state)))
         .or(crate::web::api::listing_detail_api(Arc::clone(&state)))
         .or(crate::web::v2::routes(Arc::clone(&state)))
+        .or(self::metrics::route(Arc::clone(&state)))
+        .or(self::health::routes(Arc::clone(&state)))
+        .or(self::admin::routes(Arc::clone(&state)))
         .or(index())
+        .with(warp::log::custom(move |info| self::metrics::observe_request(&log_state, info)))
         .boxed()
 }
 
 #[cfg(test)]
 pub(crate) async fn state_for_router_tests() -> Arc<State> {
-    let mongo = MongoClient::with_uri_str("mongodb://127.0.0.1:27017")
-        .await
-        .expect("router tests should construct a Mongo client without touching the network");
+    state_for_router_tests_with_limits(Default::default()).await
+}
+
+#[cfg(test)]
+pub(crate) async fn state_for_router_tests_with_limits(
+    contribute: crate::config::Contribute,
+) -> Arc<State> {
+    state_for_router_tests_with(Arc::new(MemoryStore::new()), contribute, Default::default()).await
+}
+
+#[cfg(test)]
+pub(crate) async fn state_for_router_tests_with(
+    store: Arc<dyn ListingStore>,
+    contribute: crate::config::Contribute,
+    uploaders: crate::config::Uploaders,
+) -> Arc<State> {
+    router_test_state(store, contribute, uploaders, Default::default(), Default::default())
+}
+
+#[cfg(test)]
+pub(crate) async fn state_for_router_tests_with_admin(admin: crate::config::Admin) -> Arc<State> {
+    router_test_state(
+        Arc::new(MemoryStore::new()),
+        Default::default(),
+        Default::default(),
+        admin,
+        Default::default(),
+    )
+}
+
+/// Like [`state_for_router_tests_with_admin`], with webhook delivery running.
+#[cfg(test)]
+pub(crate) async fn state_for_router_tests_with_webhooks(
+    admin: crate::config::Admin,
+    webhooks: crate::config::Webhooks,
+) -> Arc<State> {
+    let state = router_test_state(
+        Arc::new(MemoryStore::new()),
+        Default::default(),
+        Default::default(),
+        admin,
+        webhooks,
+    );
+    state.spawn_webhook_dispatcher();
+    // let the dispatcher subscribe to listing events before the test uploads anything
+    tokio::task::yield_now().await;
+    state
+}
 
+#[cfg(test)]
+fn router_test_state(
+    store: Arc<dyn ListingStore>,
+    contribute: crate::config::Contribute,
+    uploaders: crate::config::Uploaders,
+    admin: crate::config::Admin,
+    webhooks: crate::config::Webhooks,
+) -> Arc<State> {
     Arc::new(State {
-        mongo,
+        webhooks: Arc::new(Dispatcher::new(Arc::clone(&store), webhooks)),
+        discord: Arc::new(Notifier::new(&[]).unwrap()),
+        store,
         stats: Default::default(),
+        duty_stats: Default::default(),
+        duty_stats_loads: Default::default(),
         listings_cache: RwLock::new(ListingsCache {
             entries: HashMap::new(),
         }),
         detail_cache: RwLock::new(DetailCache {
             entries: HashMap::new(),
         }),
+        listing_events: Default::default(),
+        contribute_limits: ContributeLimits::new(contribute),
+        uploaders: Uploaders::new(uploaders),
+        metrics: Metrics::new(),
+        health: Default::default(),
+        tasks: Default::default(),
+        admin,
     })
 }
 
@@ -366,13 +429,11 @@ fn assets() -> BoxedFilter<(impl Reply, )> {
                 .or(ffxiv_loadestone_ssf_ttf())
                 .or(ffxiv_loadestone_ssf_woff())
                 .or(listings_css())
-                .or(listings_js())
                 .or(stats_css())
                 .or(stats_js())
                 .or(d3())
                 .or(pico())
                 .or(common_js())
-                .or(list_js())
         )
         .boxed()
 }
@@ -419,13 +480,6 @@ fn listings_css() -> BoxedFilter<(impl Reply, )> {
         .boxed()
 }
 
-fn listings_js() -> BoxedFilter<(impl Reply, )> {
-    warp::path("listings.js")
-        .and(warp::path::end())
-        .and(warp::fs::file("./assets/listings.js"))
-        .boxed()
-}
-
 fn stats_css() -> BoxedFilter<(impl Reply, )> {
     warp::path("stats.css")
         .and(warp::path::end())
@@ -461,13 +515,6 @@ fn common_js() -> BoxedFilter<(impl Reply, )> {
         .boxed()
 }
 
-fn list_js() -> BoxedFilter<(impl Reply, )> {
-    warp::path("list.js")
-        .and(warp::path::end())
-        .and(warp::fs::file("./assets/list.min.js"))
-        .boxed()
-}
-
 fn index() -> BoxedFilter<(impl Reply, )> {
     let route = warp::path::end()
         .map(|| warp::redirect(Uri::from_static("/listings")));
@@ -475,118 +522,46 @@ fn index() -> BoxedFilter<(impl Reply, )> {
 }
 
 fn listings(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
-    async fn logic(state: Arc<State>, codes: Option<String>) -> std::result::Result<impl Reply, Infallible> {
+    async fn logic(state: Arc<State>, raw_query: String, codes: Option<String>) -> std::result::Result<Response, Infallible> {
         let lang = Language::from_codes(codes.as_deref());
 
-        let two_hours_ago = active_listing_cutoff(Utc::now());
-        let res = state
-            .collection()
-            .aggregate(
-                [
-                    // don't ask me why, but mongo shits itself unless you provide a hard date
-                    // doc! {
-                    //     "$match": {
-                    //         "created_at": {
-                    //             "$gte": {
-                    //                 "$dateSubtract": {
-                    //                     "startDate": "$$NOW",
-                    //                     "unit": "hour",
-                    //                     "amount": 2,
-                    //                 },
-                    //             },
-                    //         },
-                    //     }
-                    // },
-                    doc! {
-                        "$match": {
-                            "updated_at": { "$gte": two_hours_ago },
-                        }
-                    },
-                    doc! {
-                        "$match": {
-                            // filter private pfs
-                            "listing.search_area": { "$bitsAllClear": 2 },
-                        }
-                    },
-                    doc! {
-                        "$set": {
-                            "time_left": {
-                                "$divide": [
-                                    {
-                                        "$subtract": [
-                                            { "$multiply": ["$listing.seconds_remaining", 1000] },
-                                            { "$subtract": ["$$NOW", "$updated_at"] },
-                                        ]
-                                    },
-                                    1000,
-                                ]
-                            },
-                            "minutes_since_update": {
-                                "$divide": [
-                                    { "$subtract": ["$$NOW", "$updated_at"] },
-                                    60000
-                                ]
-                            },
-                            "updated_minute": {
-                                "$dateTrunc": {
-                                    "date": "$updated_at",
-                                    "unit": "minute",
-                                    "binSize": 5,
-                                },
-                            },
-                        }
-                    },
-                    doc! {
-                        "$match": {
-                            "$and": [
-                                { "time_left": { "$gte": 0 } },
-                                { "minutes_since_update": { "$lt": 5.0 } }
-                            ]
-                        }
-                    },
-                ],
-                None,
-            )
-            .await;
-        Ok(match res {
-            Ok(mut cursor) => {
-                let mut containers = Vec::new();
-
-                while let Ok(Some(container)) = cursor.try_next().await {
-                    let res: Result<QueriedListing> = try {
-                        let result: QueriedListing = mongodb::bson::from_document(container)?;
-                        result
-                    };
-                    if let Ok(listing) = res {
-                        containers.push(listing);
-                    }
-                }
-
-                containers.sort_by(|a, b| a.time_left.partial_cmp(&b.time_left).unwrap_or(Ordering::Equal));
-
-                containers.sort_by_key(|container| container.listing.pf_category());
-                containers.reverse();
-
-                containers.sort_by_key(|container| container.updated_minute);
-                containers.reverse();
-
-                ListingsTemplate {
-                    containers,
+        let query = match parse_listings_query(&listings_page_params(&raw_query)) {
+            Ok(query) => query,
+            Err(error) => {
+                let template = ListingsTemplate::new(
+                    Default::default(),
+                    empty_collection_response::<()>(&ListingsQuery::default()).pagination,
+                    ListingsQuery::default(),
                     lang,
-                }
+                )
+                .with_error(error.error.message);
+                return Ok(warp::reply::with_status(template, StatusCode::BAD_REQUEST).into_response());
             }
+        };
+
+        // the same filtering, sorting and paging as GET /api/v2/listings
+        let containers = match matching_listings(&state, &query).await {
+            Ok(containers) => containers,
             Err(e) => {
                 eprintln!("{:#?}", e);
-                ListingsTemplate {
-                    containers: Default::default(),
-                    lang,
-                }
+                Default::default()
             }
-        })
+        };
+        let page = paginated_collection_response(query.clone(), containers);
+        let pages = &page.pagination;
+        let previous_href = (pages.page > 1 && pages.page <= pages.total_pages)
+            .then(|| page_href(LISTINGS_PATH, &raw_query, Some(pages.page - 1)));
+        let next_href = (pages.page < pages.total_pages)
+            .then(|| page_href(LISTINGS_PATH, &raw_query, Some(pages.page + 1)));
+
+        Ok(ListingsTemplate::new(page.data, page.pagination, query, lang)
+            .with_page_links(previous_href, next_href)
+            .into_response())
     }
 
     let route = warp::path("listings")
         .and(warp::path::end())
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
         .and(
             warp::cookie::<String>("lang")
                 .or(warp::header::<String>("accept-language"))
@@ -595,20 +570,101 @@ fn listings(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
                 .or(warp::any().map(|| None))
                 .unify()
         )
-        .and_then(move |codes: Option<String>| logic(Arc::clone(&state), codes));
+        .and_then(move |raw_query: String, codes: Option<String>| logic(Arc::clone(&state), raw_query, codes));
 
     warp::get().and(route).boxed()
 }
 
-async fn stats_logic(state: Arc<State>, codes: Option<String>, seven_days: bool) -> std::result::Result<impl Reply, Infallible> {
+const LISTINGS_PATH: &str = "/listings";
+
+/// The filter form submits every field, so empty ones are dropped, and a multi-select or a set
+/// of checkboxes repeats its name, so repeated values are joined into the comma-separated list
+/// the v2 parameters take.
+fn listings_page_params(raw_query: &str) -> HashMap<String, String> {
+    let pairs = serde_urlencoded::from_str::<Vec<(String, String)>>(raw_query).unwrap_or_default();
+    let mut params = HashMap::<String, String>::new();
+    for (field, value) in pairs {
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+
+        params
+            .entry(field)
+            .and_modify(|values| {
+                values.push(',');
+                values.push_str(value);
+            })
+            .or_insert_with(|| value.to_string());
+    }
+    params
+}
+
+/// `path` with the request's parameters as sent, and `page` replaced when given.
+pub(crate) fn page_href(path: &str, raw_query: &str, page: Option<usize>) -> String {
+    let page = page.map(|page| format!("page={page}"));
+    let query = raw_query
+        .split('&')
+        .filter(|pair| !pair.is_empty() && (page.is_none() || !pair.starts_with("page=")))
+        .chain(page.as_deref())
+        .collect::<Vec<_>>()
+        .join("&");
+
+    match query.as_str() {
+        "" => path.to_owned(),
+        query => format!("{path}?{query}"),
+    }
+}
+
+fn listing_page(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
+    async fn logic(state: Arc<State>, id: String, codes: Option<String>) -> std::result::Result<Response, Infallible> {
+        let lang = Language::from_codes(codes.as_deref());
+        let expired = |id: String| {
+            warp::reply::with_status(ListingExpiredTemplate { id, lang }, StatusCode::NOT_FOUND).into_response()
+        };
+        let Ok(listing_id) = id.parse::<u64>() else {
+            return Ok(expired(id));
+        };
+
+        // same visibility as /api/v2/listings/{id}
+        Ok(match state.store().listings_by_id(listing_id).await {
+            Ok(documents) => match resolve_visible_listing(listing_id, &documents) {
+                Some(container) => ListingTemplate::new(container.clone(), lang).into_response(),
+                None => expired(id),
+            },
+            Err(e) => {
+                eprintln!("{:#?}", e);
+                warp::reply::with_status("could not load listing", StatusCode::INTERNAL_SERVER_ERROR).into_response()
+            }
+        })
+    }
+
+    let route = warp::path!("listings" / String)
+        .and(
+            warp::cookie::<String>("lang")
+                .or(warp::header::<String>("accept-language"))
+                .unify()
+                .map(Some)
+                .or(warp::any().map(|| None))
+                .unify()
+        )
+        .and_then(move |id: String, codes: Option<String>| logic(Arc::clone(&state), id, codes));
+
+    warp::get().and(route).boxed()
+}
+
+async fn stats_logic(state: Arc<State>, codes: Option<String>, seven_days: bool) -> std::result::Result<Response, Infallible> {
     let lang = Language::from_codes(codes.as_deref());
     let stats = state.stats.read().await.clone();
     Ok(match stats {
         Some(stats) => StatsTemplate {
             stats: if seven_days { stats.seven_days } else { stats.all_time },
             lang,
-        },
-        None => panic!(),
+        }.into_response(),
+        None => warp::reply::with_status(
+            StatsWarmingUpTemplate { lang },
+            StatusCode::SERVICE_UNAVAILABLE,
+        ).into_response(),
     })
 }
 
@@ -645,135 +701,154 @@ fn stats_seven_days(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
     warp::get().and(route).boxed()
 }
 
+async fn duty_stats_logic(state: Arc<State>, duty: DutyKey, codes: Option<String>, seven_days: bool) -> std::result::Result<Response, Infallible> {
+    if !self::stats::is_known_duty(duty) {
+        return Ok(warp::reply::with_status("unknown duty", StatusCode::NOT_FOUND).into_response());
+    }
+
+    let lang = Language::from_codes(codes.as_deref());
+    Ok(match self::stats::get_duty_stats(&state, duty).await {
+        Ok(stats) => DutyStatsTemplate {
+            duty: stats.duty.clone(),
+            stats: if seven_days { stats.seven_days.clone() } else { stats.all_time.clone() },
+            seven_days,
+            lang,
+        }.into_response(),
+        Err(e) => {
+            eprintln!("error generating duty stats: {:#?}", e);
+            warp::reply::with_status("could not generate stats", StatusCode::INTERNAL_SERVER_ERROR).into_response()
+        }
+    })
+}
+
+fn duty_stats(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
+    let route = warp::path!("stats" / "duty" / u8 / u32 / u16)
+        .and(
+            warp::cookie::<String>("lang")
+                .or(warp::header::<String>("accept-language"))
+                .unify()
+                .map(Some)
+                .or(warp::any().map(|| None))
+                .unify()
+        )
+        .and_then(move |duty_type: u8, category: u32, duty: u16, codes: Option<String>| {
+            duty_stats_logic(Arc::clone(&state), (duty_type, category, duty), codes, false)
+        });
+
+    warp::get().and(route).boxed()
+}
+
+fn duty_stats_seven_days(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
+    let route = warp::path!("stats" / "7days" / "duty" / u8 / u32 / u16)
+        .and(
+            warp::cookie::<String>("lang")
+                .or(warp::header::<String>("accept-language"))
+                .unify()
+                .map(Some)
+                .or(warp::any().map(|| None))
+                .unify()
+        )
+        .and_then(move |duty_type: u8, category: u32, duty: u16, codes: Option<String>| {
+            duty_stats_logic(Arc::clone(&state), (duty_type, category, duty), codes, true)
+        });
+
+    warp::get().and(route).boxed()
+}
+
 fn contribute(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
-    async fn logic(state: Arc<State>, listing: PartyFinderListing) -> std::result::Result<impl Reply, Infallible> {
-        let result = validate_and_insert_listing(&*state, listing).await;
-        Ok(format!("{:#?}", result))
+    async fn logic(state: Arc<State>, contributor: Contributor, listing: PartyFinderListing) -> std::result::Result<impl Reply, Infallible> {
+        let response = validate_and_insert_listings(&*state, vec![listing], contributor.uploader.as_deref()).await;
+        Ok(warp::reply::json(&response))
     }
 
     let route = warp::path("contribute")
         .and(warp::path::end())
+        .and(contribute_guard(Arc::clone(&state)))
         .and(warp::body::json())
-        .and_then(move |listing: PartyFinderListing| logic(Arc::clone(&state), listing));
-    warp::post().and(route).boxed()
+        .and_then(move |contributor: Contributor, listing: PartyFinderListing| logic(Arc::clone(&state), contributor, listing));
+    warp::post()
+        .and(route)
+        .recover(recover_contribute_rejection)
+        .boxed()
 }
 
 fn contribute_multiple(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
-    async fn logic(state: Arc<State>, listings: Vec<PartyFinderListing>) -> std::result::Result<impl Reply, Infallible> {
-        let total = listings.len();
-        let mut successful = 0;
-
-        for listing in listings {
-            let result = validate_and_insert_listing(&*state, listing).await;
-            if result.is_ok() {
-                successful += 1;
-            } else {
-                eprintln!("{:#?}", result);
-            }
+    async fn logic(state: Arc<State>, contributor: Contributor, listings: Vec<PartyFinderListing>) -> std::result::Result<Response, Infallible> {
+        if let Err(rejection) = state.contribute_limits.check_batch_len(contributor.client, listings.len()) {
+            return Ok(rejection.to_response());
         }
 
-        Ok(format!("{}/{} updated", successful, total))
+        let response = validate_and_insert_listings(&*state, listings, contributor.uploader.as_deref()).await;
+        Ok(warp::reply::json(&response).into_response())
     }
 
     let route = warp::path("contribute")
         .and(warp::path("multiple"))
         .and(warp::path::end())
+        .and(contribute_guard(Arc::clone(&state)))
         .and(warp::body::json())
-        .and_then(move |listings: Vec<PartyFinderListing>| logic(Arc::clone(&state), listings));
-    warp::post().and(route).boxed()
+        .and_then(move |contributor: Contributor, listings: Vec<PartyFinderListing>| logic(Arc::clone(&state), contributor, listings));
+    warp::post()
+        .and(route)
+        .recover(recover_contribute_rejection)
+        .boxed()
 }
 
-async fn validate_and_insert_listing(state: &State, listing: PartyFinderListing) -> Result<UpdateResult> {
-    // Validate remaining time
-    if listing.seconds_remaining > 60 * 60 {
-        anyhow::bail!("invalid listing: remaining time greater than 1 hour");
-    }
-
-    if listing.last_server_restart < i64::from(i32::MIN)
-        || listing.last_server_restart > i64::from(i32::MAX)
-    {
-        anyhow::bail!(
-            "invalid listing: last_server_restart {} out of range (expected signed 32-bit integer)",
-            listing.last_server_restart
-        );
-    }
-
-    if !matches!(listing.created_world, 1000..=1999 | 4000..=4999) {
-        anyhow::bail!(
-            "invalid listing: created_world {} out of range (expected 1000-1999 or 4000-4999)",
-            listing.created_world
-        );
+/// Validates the whole batch in Rust, then writes every valid listing with a single
+/// [`ListingStore::upsert_many`] call. Results keep submission order.
+async fn validate_and_insert_listings(state: &State, listings: Vec<PartyFinderListing>, uploader: Option<&str>) -> ContributeResponse {
+    let mut results = Vec::with_capacity(listings.len());
+    let mut valid = Vec::with_capacity(listings.len());
+    for listing in listings {
+        match validate_listing(&listing) {
+            Ok(()) => {
+                results.push(None);
+                valid.push(listing);
+            }
+            Err(rejection) => results.push(Some(ListingResult::rejected(listing.id, rejection))),
+        }
     }
 
-    // Validate duty/category/duty_type combination (fast path, no allocation)
-    match crate::ffxiv::is_valid_duty_combination(listing.duty_type, listing.category, listing.duty) {
-        Ok(()) => {}
-        Err(base_error) => {
-            // Only extract strings when validation fails (lazy evaluation)
-            let player_name = listing.name.full_text(&crate::ffxiv::Language::ChineseSimplified);
-            let player_name = if player_name.is_empty() { None } else { Some(player_name.as_str()) };
-
-            let world = listing.created_world();
-            let world_str = world.map(|w| w.name());
-
-            let description = listing.description.full_text(&crate::ffxiv::Language::ChineseSimplified);
-            let description = if description.is_empty() { None } else { Some(description.as_str()) };
-
-            // Build full error message with context
-            let mut full_error = base_error;
-            if let Some(name) = player_name {
-                full_error.push_str(&format!(" | player: {}", name));
-            }
-            if let Some(w) = world_str {
-                full_error.push_str(&format!(" | world: {}", w));
+    let now = Utc::now();
+    let outcomes = state.store().upsert_many(&valid, uploader, now).await;
+
+    let mut written = valid.into_iter().zip(outcomes).map(|(listing, outcome)| {
+        let id = listing.id;
+        match outcome {
+            Ok(outcome) => {
+                state.listing_events.publish_upsert(listing, outcome, now);
+                ListingResult::accepted(id, outcome)
             }
-            if let Some(desc) = description {
-                let desc_preview: String = desc.chars().take(5).collect();
-                full_error.push_str(&format!(" | desc: {}", desc_preview));
+            Err(e) => {
+                eprintln!("{:#?}", e);
+                ListingResult::rejected(id, ListingRejection::storage_error())
             }
+        }
+    });
+
+    let response: ContributeResponse = results
+        .into_iter()
+        .map(|result| result.or_else(|| written.next()).expect("one outcome per valid listing"))
+        .collect();
 
-            eprintln!("未插入: {}", full_error);
-            anyhow::bail!("invalid listing: {}", full_error);
+    if response.results.iter().any(|result| result.code.is_none()) {
+        state.health.contribution_accepted(now);
+    }
+    for result in &response.results {
+        state.metrics.contributions.inc(&[result.outcome.as_str()]);
+        if let Some(code) = result.code {
+            state.metrics.contributions_rejected.inc(&[code.as_str()]);
         }
     }
+    state.metrics.contribute_batch_size.observe(&[], response.results.len() as f64);
 
-    insert_listing(state, listing).await
-}
-
-async fn insert_listing(state: &State, listing: PartyFinderListing) -> Result<UpdateResult> {
-    let opts = UpdateOptions::builder()
-        .upsert(true)
-        .build();
-    let bson_value = mongodb::bson::to_bson(&listing).unwrap();
-    // Canonical writes always upsert on the widened `(listing.id, last_server_restart,
-    // created_world)` identity only. Pre-migration truncated-id rows remain legacy data; the
-    // server does not reconstruct guessed wide ids from `content_id_lower` or any other surrogate.
-    let filter = listing_identity_filter(&listing)?;
-    let now = Utc::now();
-    state
-        .collection()
-        .update_one(
-            filter,
-            doc! {
-                    "$currentDate": {
-                        "updated_at": true,
-                    },
-                    "$set": {
-                        "listing": bson_value,
-                    },
-                    "$setOnInsert": {
-                        "created_at": now,
-                    },
-                },
-            opts,
-        )
-        .await
-        .context("could not insert record")
+    response
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::{active_listing_cutoff, ACTIVE_LISTING_WINDOW_HOURS};
 
     const WIDE_LISTING_ID: u64 = 4_294_967_296;
 
@@ -822,7 +897,7 @@ mod tests {
     }
 
     fn wide_listing_fixture(content_id_lower: u32) -> PartyFinderListing {
-        let mut listing = fixture_listing();
+        let mut listing = valid_upload_listing();
         listing.id = WIDE_LISTING_ID;
         listing.content_id_lower = content_id_lower;
         listing
@@ -865,25 +940,6 @@ mod tests {
         }
     }
 
-    #[test]
-    fn listing_identity_filter_keeps_legacy_rows_non_reconstructable() {
-        let listing = fixture_listing();
-
-        let filter = listing_identity_filter(&listing).expect("filter must serialize");
-
-        assert_eq!(filter.len(), 3);
-        assert_eq!(filter.get(LISTING_ID_FIELD), Some(&to_bson(&listing.id).unwrap()));
-        assert_eq!(
-            filter.get(LISTING_LAST_SERVER_RESTART_FIELD),
-            Some(&to_bson(&listing.last_server_restart).unwrap()),
-        );
-        assert_eq!(
-            filter.get(LISTING_CREATED_WORLD_FIELD),
-            Some(&to_bson(&listing.created_world).unwrap()),
-        );
-        assert!(!filter.contains_key("listing.content_id_lower"));
-    }
-
     #[test]
     fn active_listing_cutoff_bounds_temporary_legacy_row_coexistence_window() {
         let now = Utc::now();
@@ -912,89 +968,680 @@ mod tests {
         assert!(state.get_detail_cache(WIDE_LISTING_ID - 1).await.is_none());
     }
 
-#[test]
-    fn listing_identity_index_keys_has_three_canonical_fields() {
-        let keys = listing_identity_index_keys();
-        assert_eq!(keys.len(), 3);
-        assert!(keys.contains_key(LISTING_ID_FIELD));
-        assert!(keys.contains_key(LISTING_LAST_SERVER_RESTART_FIELD));
-        assert!(keys.contains_key(LISTING_CREATED_WORLD_FIELD));
+    #[tokio::test]
+    async fn contribute_multiple_upserts_wide_duplicate_payload_onto_one_row() {
+        let state = state_for_router_tests().await;
+        let route = contribute_multiple(Arc::clone(&state));
+        let payload = vec![wide_listing_fixture(456), wide_listing_fixture(789)];
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/contribute/multiple")
+            .json(&payload)
+            .reply(&route)
+            .await;
+
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["inserted"], 1);
+        assert_eq!(body["updated"], 1);
+        assert_eq!(body["results"][0]["id"], WIDE_LISTING_ID.to_string());
+        assert_eq!(body["results"][1]["outcome"], "updated");
+
+        let stored = state
+            .store()
+            .listings_by_id(WIDE_LISTING_ID)
+            .await
+            .expect("memory store reads cannot fail");
+        assert_eq!(stored.len(), 1, "same identity must upsert onto one row");
+        assert_eq!(stored[0].listing.content_id_lower, 789);
     }
 
-    #[test]
-    fn listing_id_index_keys_has_single_field() {
-        let keys = listing_id_index_keys();
-        assert_eq!(keys.len(), 1);
-        assert!(keys.contains_key(LISTING_ID_FIELD));
+    fn error_code(response: &warp::http::Response<warp::hyper::body::Bytes>) -> String {
+        let body: serde_json::Value =
+            serde_json::from_slice(response.body()).expect("limit responses must be JSON");
+        body["error"]["code"].as_str().unwrap_or_default().to_string()
     }
 
-    #[test]
-    fn canonical_upsert_filter_is_independent_of_non_key_fields() {
-        let first = wide_listing_fixture(456);
-        let mut second = wide_listing_fixture(789);
-        second.seconds_remaining = first.seconds_remaining - 1;
+    #[tokio::test]
+    async fn stats_routes_warm_up_then_serve_cached_statistics() {
+        let state = state_for_router_tests().await;
+        let router = router(Arc::clone(&state));
+        let get = |path: &'static str| warp::test::request().method("GET").path(path).reply(&router);
+
+        let page = get("/stats/7days").await;
+        assert_eq!(page.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(String::from_utf8(page.body().to_vec()).unwrap().contains("Stats are warming up"));
+        for path in ["/api/v2/stats", "/api/v2/stats/7days"] {
+            let response = get(path).await;
+            assert_eq!(response.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(response.headers()["retry-after"], "30");
+            assert_eq!(error_code(&response), "warming_up");
+        }
+
+        let mut listing = valid_upload_listing();
+        listing.created_world = 1042;
+        validate_and_insert_listings(&state, vec![listing], None).await;
+        *state.stats.write().await = Some(CachedStatistics {
+            all_time: self::stats::get_stats(&state).await.unwrap(),
+            seven_days: self::stats::get_stats_seven_days(&state).await.unwrap(),
+        });
+
+        assert_eq!(get("/stats").await.status(), warp::http::StatusCode::OK);
+        let response = get("/api/v2/stats/7days").await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["data"]["listings"], 1);
+        assert_eq!(
+            body["data"]["duties"],
+            serde_json::json!([{ "duty_type_id": 2, "category_id": 0, "duty_id": 0, "count": 1 }])
+        );
+        assert_eq!(body["data"]["hosts"][0]["created_world_id"], 1042);
+        assert_eq!(body["data"]["hosts"][0]["top_hosts"][0]["player_name"], "Test Name");
+        assert_eq!(body["data"]["hosts"][0]["top_hosts"][0]["home_world_id"], 1001);
+        assert_eq!(body["data"]["hosts"][0]["other_count"], 0);
+    }
+
+    #[tokio::test]
+    async fn concurrent_duty_stats_misses_share_one_computation() {
+        let state = state_for_router_tests().await;
+        let duty = (2, 0, 0);
+        let load = Arc::clone(state.duty_stats_loads.lock().unwrap().entry(duty).or_default());
+        let loading = load.lock().await;
+        let requests = (0..3)
+            .map(|_| {
+                let state = Arc::clone(&state);
+                tokio::spawn(async move { self::stats::get_duty_stats(&state, duty).await.unwrap() })
+            })
+            .collect::<Vec<_>>();
+        // let every request miss the cache and queue up behind the load in progress
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        drop(loading);
+
+        let mut results = Vec::new();
+        for request in requests {
+            results.push(request.await.unwrap());
+        }
+        assert!(results.iter().all(|stats| Arc::ptr_eq(stats, &results[0])));
+        assert!(state.duty_stats_loads.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn duty_stats_are_served_as_html_and_json_and_cached() {
+        let state = state_for_router_tests().await;
+        let router = router(Arc::clone(&state));
+        let upload = |id: u64| {
+            let mut listing = valid_upload_listing();
+            listing.id = id;
+            listing.content_id_lower = id as u32;
+            listing
+        };
+        for id in [1, 2] {
+            validate_and_insert_listings(&state, vec![upload(id)], None).await;
+        }
+
+        let get = |path: &'static str| warp::test::request().method("GET").path(path).reply(&router);
+
+        let page = get("/stats/duty/2/0/0").await;
+        assert_eq!(page.status(), warp::http::StatusCode::OK);
+        let page = String::from_utf8(page.body().to_vec()).unwrap();
+        assert!(page.contains("stats for 2 listings"), "{page}");
+        assert!(page.contains("/stats/7days/duty/2/0/0"));
+        assert_eq!(get("/stats/7days/duty/2/0/0").await.status(), warp::http::StatusCode::OK);
+
+        // cached: a later upload does not show until the entry expires
+        validate_and_insert_listings(&state, vec![upload(3)], None).await;
+        let response = get("/api/v2/stats/duties/2/0/0").await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["data"]["duty_type_id"], 2);
+        assert_eq!(body["data"]["all_time"]["listings"], 2);
+        assert_eq!(body["data"]["seven_days"]["listings"], 2);
+        assert_eq!(body["data"]["all_time"]["median_min_item_level"], 0);
+        assert_eq!(body["data"]["all_time"]["objectives"][0], serde_json::json!({ "id": 1, "count": 2 }));
+
+        assert_eq!(get("/stats/duty/9/0/0").await.status(), warp::http::StatusCode::NOT_FOUND);
+        let response = get("/api/v2/stats/duties/9/0/0").await;
+        assert_eq!(response.status(), warp::http::StatusCode::NOT_FOUND);
+        assert_eq!(error_code(&response), "not_found");
+        let response = get("/api/v2/stats/duties/x/0/0").await;
+        assert_eq!(response.status(), warp::http::StatusCode::BAD_REQUEST);
+        // duty and roulette ids must be in the game data
+        assert_eq!(get("/api/v2/stats/duties/2/4/55").await.status(), warp::http::StatusCode::OK);
+        assert_eq!(get("/api/v2/stats/duties/2/4/65535").await.status(), warp::http::StatusCode::NOT_FOUND);
+        assert_eq!(get("/stats/duty/1/2/65535").await.status(), warp::http::StatusCode::NOT_FOUND);
+        assert_eq!(error_code(&response), "invalid_id");
+    }
+
+    #[tokio::test]
+    async fn listing_page_links_previews_and_expires_like_the_v2_detail() {
+        let state = state_for_router_tests().await;
+        let router = router(Arc::clone(&state));
+        let mut listing = valid_upload_listing();
+        listing.created_world = 1042;
+        // uploads may carry fewer slot entries than jobs; the fixture has one entry for seven slots
+        assert!(listing.slots.len() < listing.jobs_present.len());
+        let mut private = listing.clone();
+        private.id += 1;
+        private.search_area |= crate::listing::SearchAreaFlags::PRIVATE;
+        validate_and_insert_listings(&state, vec![listing.clone(), private.clone()], None).await;
+        let get = |path: String| warp::test::request().method("GET").path(&path).reply(&router);
+
+        let page = get("/listings".into()).await;
+        let page = String::from_utf8(page.body().to_vec()).unwrap();
+        assert!(page.contains(&format!(r#"<a href="/listings/{}">"#, listing.id)));
+
+        let response = get(format!("/listings/{}", listing.id)).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let page = String::from_utf8(response.body().to_vec()).unwrap();
+        let duty = listing.duty_name(&Language::English);
+        assert!(page.contains(&format!(r#"<meta property="og:title" content="{duty}"/>"#)), "{page}");
+        assert!(page.contains(r#"<meta name="twitter:description" content="Test Name @ "#));
+        assert!(page.contains("This is my test description."));
+        assert!(page.contains("<dt>Objective</dt>"));
+        assert!(page.contains("<dd>Duty Completion, Practice</dd>"), "{page}");
+        assert!(page.contains("<dt>Beginners Welcome</dt>"));
+        assert!(page.contains("<span>Open: Unknown</span>"), "{page}");
+
+        for path in [format!("/listings/{}", private.id), "/listings/1".into(), "/listings/nope".into()] {
+            let response = get(path.clone()).await;
+            assert_eq!(response.status(), warp::http::StatusCode::NOT_FOUND, "{path}");
+            let page = String::from_utf8(response.body().to_vec()).unwrap();
+            assert!(page.contains("This listing has expired"), "{path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn listings_page_filters_and_pages_like_the_v2_collection() {
+        let state = state_for_router_tests().await;
+        let router = router(Arc::clone(&state));
+        for (id, created_world) in [(1, 1042), (2, 1042), (3, 2075)] {
+            let mut listing = valid_upload_listing();
+            listing.id = id;
+            listing.content_id_lower = id as u32;
+            listing.created_world = created_world;
+            validate_and_insert_listings(&state, vec![listing], None).await;
+        }
+        let get = |path: String| warp::test::request().method("GET").path(&path).reply(&router);
+        let page_ids = |page: &str| {
+            page.split(r#"data-id=""#)
+                .skip(1)
+                .map(|rest| rest.split('"').next().unwrap().parse::<u32>().unwrap())
+                .collect::<Vec<_>>()
+        };
+        let api_ids = |body: &[u8]| {
+            let body: serde_json::Value = serde_json::from_slice(body).unwrap();
+            body["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|listing| listing["id"].as_str().unwrap().parse::<u32>().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let data_centre = state.store().listings_by_id(1).await.unwrap()[0].listing.data_centre_name().unwrap();
+        let datacenter = serde_urlencoded::to_string([("datacenter", data_centre)]).unwrap();
+        let response = get(format!("/listings?{datacenter}&per_page=1&search=")).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let page = String::from_utf8(response.body().to_vec()).unwrap();
+        let api = get(format!("/api/v2/listings?{datacenter}&per_page=1")).await;
+        assert_eq!(page_ids(&page), api_ids(api.body()));
+        assert_eq!(page_ids(&page).len(), 1);
+        assert!(page.contains(&format!(r#"<option value="{data_centre}" selected>"#)), "{page}");
+        assert!(page.contains(&format!(r#"<a rel="next" href="/listings?{datacenter}&amp;per_page=1&amp;search=&amp;page=2">"#)), "{page}");
+        assert!(page.contains("page 1 of 2 (2 listings)"), "{page}");
+        assert!(!page.contains("/assets/list.js"));
+
+        let page = get(format!("/listings?{datacenter}&per_page=1&page=2")).await;
+        let page = String::from_utf8(page.body().to_vec()).unwrap();
+        assert!(page.contains(r#"<a rel="prev" href="#), "{page}");
+        assert!(!page.contains(r#"<a rel="next" href="#));
+
+        let page = get("/listings?job_ids=19&job_ids=24".into()).await;
+        let page = String::from_utf8(page.body().to_vec()).unwrap();
+        let api = get("/api/v2/listings?job_ids=19,24".into()).await;
+        assert_eq!(page_ids(&page), api_ids(api.body()));
+        assert!(page.contains(r#"<input type="checkbox" name="job_ids" value="24" checked/>"#), "{page}");
+
+        // several categories at once, as the multi-select submits them
+        let page = get("/listings?category_ids=0&category_ids=32".into()).await;
+        let page = String::from_utf8(page.body().to_vec()).unwrap();
+        let api = get("/api/v2/listings?category_ids=0,32".into()).await;
+        assert_eq!(page_ids(&page), api_ids(api.body()));
+        assert!(page_ids(&page).contains(&1) && page_ids(&page).contains(&2), "{page}");
+        assert!(page.contains(r#"<option value="32" selected>"#), "{page}");
+
+        let response = get("/listings?duty=55".into()).await;
+        assert_eq!(response.status(), warp::http::StatusCode::BAD_REQUEST);
+        let page = String::from_utf8(response.body().to_vec()).unwrap();
+        assert!(page.contains(r#"<p class="query-error">"#), "{page}");
+    }
+
+    #[tokio::test]
+    async fn listings_feed_serves_matching_listings_as_atom() {
+        let state = state_for_router_tests().await;
+        let router = router(Arc::clone(&state));
+        for (id, created_world) in [(1, 1042), (2, 1043)] {
+            let mut listing = valid_upload_listing();
+            listing.id = id;
+            listing.content_id_lower = id as u32;
+            listing.created_world = created_world;
+            validate_and_insert_listings(&state, vec![listing], None).await;
+        }
+        let get = |path: &'static str| warp::test::request().method("GET").path(path).reply(&router);
+
+        let response = get("/feeds/listings.atom?created_world_id=1043&lang=ja").await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/atom+xml; charset=utf-8");
+        let feed = String::from_utf8(response.body().to_vec()).unwrap();
+        assert_eq!(feed.matches("<entry>").count(), 1, "{feed}");
+        assert!(feed.contains(r#"xml:lang="ja""#));
+        assert!(feed.contains("<id>urn:rpf:listing:2:1234567890:1043</id>"), "{feed}");
+        let stored = &state.store().listings_by_id(2).await.unwrap()[0];
+        assert!(feed.contains(&format!("<title>{}</title>", stored.listing.duty_name(&Language::Japanese))));
+        assert!(feed.contains(&format!("<updated>{}</updated>", stored.updated_at.to_rfc3339())));
+        let (_, flags) = stored.listing.prepend_flags();
+        assert!(!flags.is_empty());
+        assert!(feed.contains(&format!("{flags} This is my test description.")), "{feed}");
+
+        let first = String::from_utf8(get("/feeds/listings.atom?per_page=1").await.body().to_vec()).unwrap();
+        assert_eq!(first.matches("<entry>").count(), 1);
+        assert!(first.contains(r#"<link rel="next" type="application/atom+xml" href="/feeds/listings.atom?per_page=1&amp;page=2"/>"#), "{first}");
+        let second = String::from_utf8(get("/feeds/listings.atom?page=2&per_page=1").await.body().to_vec()).unwrap();
+        assert!(!second.contains(r#"rel="next""#));
+        let feed_id = |feed: &str| feed.split("<id>").nth(1).unwrap().split("</id>").next().unwrap().to_owned();
+        assert_eq!(feed_id(&first), feed_id(&second));
+
+        let response = get("/feeds/listings.atom?duty=55").await;
+        assert_eq!(response.status(), warp::http::StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(&response), "invalid_query");
+    }
+
+    #[tokio::test]
+    async fn metrics_count_requests_contributions_and_cache_lookups() {
+        let state = state_for_router_tests().await;
+        let router = router(Arc::clone(&state));
+        let mut valid = valid_upload_listing();
+        valid.created_world = 1042;
+        let mut invalid = valid_upload_listing();
+        invalid.id += 1;
+        invalid.created_world = 1;
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/contribute/multiple")
+            .json(&vec![valid.clone(), invalid])
+            .reply(&router)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let detail = format!("/api/listing/{}", valid.id);
+        for _ in 0..2 {
+            let response = warp::test::request().method("GET").path(&detail).reply(&router).await;
+            assert_eq!(response.status(), warp::http::StatusCode::OK);
+        }
+
+        let metrics = &state.metrics;
+        assert_eq!(metrics.contributions.get(&["inserted"]), 1);
+        assert_eq!(metrics.contributions_rejected.get(&["created_world_out_of_range"]), 1);
+        assert_eq!(metrics.contribute_batch_size.count(&[]), 1);
+        assert_eq!(metrics.cache_requests.get(&["detail", "miss"]), 1);
+        assert_eq!(metrics.cache_requests.get(&["detail", "hit"]), 1);
+        assert_eq!(metrics.http_requests.get(&["/api/listing/{id}", "GET", "200"]), 2);
+        assert_eq!(metrics.http_request_duration.count(&["/contribute/multiple"]), 1);
+
+        let response = warp::test::request().method("GET").path("/metrics").reply(&router).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/plain; version=0.0.4"));
+        let body = String::from_utf8(response.body().to_vec()).unwrap();
+        for line in [
+            "rpf_http_requests_total{route=\"/contribute/multiple\",method=\"POST\",status=\"200\"} 1",
+            "rpf_contributions_total{outcome=\"rejected\"} 1",
+            "rpf_contribute_batch_size_bucket{le=\"5\"} 1",
+            "rpf_cache_requests_total{cache=\"detail\",result=\"hit\"} 1",
+            "rpf_contribute_requests_rejected_total{reason=\"rate_limited\"} 0",
+            "rpf_active_listings{datacenter=\"陆行鸟\"} 1",
+        ] {
+            assert!(body.lines().any(|candidate| candidate == line), "missing {line} in\n{body}");
+        }
+    }
+
+    #[tokio::test]
+    async fn readyz_fails_until_stats_run_and_reports_each_check() {
+        let state = state_for_router_tests().await;
+        let router = router(Arc::clone(&state));
+        let get = |path: &'static str| warp::test::request().method("GET").path(path).reply(&router);
+
+        let response = get("/healthz").await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+
+        let response = get("/readyz").await;
+        assert_eq!(response.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["status"], "failing");
+        assert_eq!(body["checks"]["store"]["status"], "ok");
+        assert_eq!(body["checks"]["stats"]["status"], "failing");
+        assert_eq!(body["checks"]["stats"]["last_at"], serde_json::Value::Null);
+        assert_eq!(body["checks"]["contributions"]["status"], "ok");
+
+        state.health.stats_refreshed(Utc::now() - chrono::Duration::hours(1));
+        assert_eq!(get("/readyz").await.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+
+        state.health.stats_refreshed(Utc::now() - chrono::Duration::minutes(1));
+        validate_and_insert_listings(&state, vec![valid_upload_listing()], None).await;
+        let response = get("/readyz").await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["checks"]["stats"]["age_seconds"], 60);
+        assert_eq!(body["checks"]["contributions"]["age_seconds"], 0);
+    }
+
+    #[tokio::test]
+    async fn admin_tasks_report_supervised_jobs_behind_a_token() {
+        let closed = router(state_for_router_tests().await);
+        let response = warp::test::request().method("GET").path("/admin/tasks").reply(&closed).await;
+        assert_eq!(response.status(), warp::http::StatusCode::NOT_FOUND);
+
+        let state = state_for_router_tests_with_admin(crate::config::Admin {
+            token: Some("secret".to_string()),
+        })
+        .await;
+        state.spawn_background_jobs();
+        for _ in 0..100 {
+            // the webhook dispatcher runs until shutdown, so it never records a success
+            let jobs = state.tasks.snapshot();
+            if jobs.iter().all(|(name, job)| *name == "webhooks" || job.last_success_at.is_some()) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let router = router(Arc::clone(&state));
+        let get = |authorization: &'static str| {
+            warp::test::request()
+                .method("GET")
+                .path("/admin/tasks")
+                .header("authorization", authorization)
+                .reply(&router)
+        };
+
+        let response = get("Bearer wrong").await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()["www-authenticate"], "Bearer");
+
+        let response = get("Bearer secret").await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        let jobs = body["jobs"].as_object().unwrap();
+        assert_eq!(
+            jobs.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["cache_sweeper", "listing_expiry", "prepare_store", "stats", "webhooks"]
+        );
+        assert_eq!(jobs["webhooks"]["last_success_at"], serde_json::Value::Null);
+        assert_eq!(jobs["webhooks"]["consecutive_failures"], 0);
+        assert_eq!(jobs["prepare_store"]["next_run_at"], serde_json::Value::Null);
+        assert!(jobs["stats"]["last_success_at"].is_string());
+        assert!(jobs["stats"]["next_run_at"].is_string());
+        assert_eq!(jobs["stats"]["consecutive_failures"], 0);
+        assert!(state.stats.read().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn contribute_multiple_rejects_oversized_batches_with_413() {
+        let state = state_for_router_tests_with_limits(crate::config::Contribute {
+            max_batch_len: 1,
+            ..Default::default()
+        })
+        .await;
+        let route = contribute_multiple(Arc::clone(&state));
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/contribute/multiple")
+            .json(&vec![valid_upload_listing(), valid_upload_listing()])
+            .reply(&route)
+            .await;
+
+        assert_eq!(response.status(), warp::http::StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(error_code(&response), "batch_too_large");
+        assert_eq!(
+            state.contribute_limits.violations(limits::LimitViolation::BatchTooLarge),
+            1
+        );
+        assert!(state
+            .store()
+            .listings_by_id(valid_upload_listing().id)
+            .await
+            .expect("memory store reads cannot fail")
+            .is_empty());
+    }
 
-        let first_filter = listing_identity_filter(&first).expect("first filter must build");
-        let second_filter = listing_identity_filter(&second).expect("second filter must build");
+    #[tokio::test]
+    async fn contribute_rejects_bodies_over_the_byte_limit_with_413() {
+        let state = state_for_router_tests_with_limits(crate::config::Contribute {
+            max_body_bytes: 16,
+            ..Default::default()
+        })
+        .await;
+        let route = contribute(Arc::clone(&state));
 
-        assert_eq!(first_filter, second_filter);
-        assert_eq!(first_filter.len(), 3);
-        assert!(!first_filter.contains_key("listing.content_id_lower"));
+        let response = warp::test::request()
+            .method("POST")
+            .path("/contribute")
+            .json(&valid_upload_listing())
+            .reply(&route)
+            .await;
+
+        assert_eq!(response.status(), warp::http::StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(error_code(&response), "payload_too_large");
+    }
+
+    #[tokio::test]
+    async fn contribute_rate_limits_each_client_with_429() {
+        let state = state_for_router_tests_with_limits(crate::config::Contribute {
+            rate_limit_burst: 1,
+            rate_limit_per_second: 0.5,
+            ..Default::default()
+        })
+        .await;
+        let route = contribute(Arc::clone(&state));
+        let client: std::net::SocketAddr = "192.0.2.10:50000".parse().unwrap();
+        let send = |addr: std::net::SocketAddr| {
+            warp::test::request()
+                .method("POST")
+                .path("/contribute")
+                .remote_addr(addr)
+                .json(&valid_upload_listing())
+                .reply(&route)
+        };
+
+        assert_eq!(send(client).await.status(), warp::http::StatusCode::OK);
+
+        let throttled = send(client).await;
+        assert_eq!(throttled.status(), warp::http::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(error_code(&throttled), "rate_limited");
+        assert_eq!(throttled.headers()["retry-after"], "2");
+
+        let other: std::net::SocketAddr = "192.0.2.11:50000".parse().unwrap();
+        assert_eq!(send(other).await.status(), warp::http::StatusCode::OK);
+        assert_eq!(state.contribute_limits.client_violations(client.ip()), 1);
+        assert_eq!(state.contribute_limits.client_violations(other.ip()), 0);
+    }
+
+    fn uploaders_config(anonymous: crate::config::AnonymousUploads) -> crate::config::Uploaders {
+        crate::config::Uploaders {
+            anonymous,
+            tokens: vec![crate::config::UploaderToken {
+                token: "secret".to_string(),
+                label: "trusted-plugin".to_string(),
+                enabled: true,
+            }],
+        }
     }
 
     #[tokio::test]
-    async fn contribute_multiple_accepts_wide_duplicate_payload_before_insert_io_failure() {
+    async fn contribute_records_the_authenticated_uploader() {
+        let store = Arc::new(MemoryStore::new());
+        let state = state_for_router_tests_with(
+            store.clone(),
+            Default::default(),
+            uploaders_config(crate::config::AnonymousUploads::Tag),
+        )
+        .await;
+        let route = contribute_multiple(Arc::clone(&state));
+        let mut anonymous = valid_upload_listing();
+        anonymous.id += 1;
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/contribute/multiple")
+            .header("authorization", "Bearer secret")
+            .json(&vec![valid_upload_listing()])
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/contribute/multiple")
+            .json(&vec![anonymous.clone()])
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+
+        assert_eq!(
+            store.uploader(crate::store::listing_identity(&valid_upload_listing())),
+            Some(Some("trusted-plugin".to_string())),
+        );
+        assert_eq!(
+            store.uploader(crate::store::listing_identity(&anonymous)),
+            Some(Some(uploaders::ANONYMOUS_UPLOADER.to_string())),
+        );
+    }
+
+    #[tokio::test]
+    async fn contribute_rejects_bad_or_missing_tokens_with_401() {
+        let state = state_for_router_tests_with(
+            Arc::new(MemoryStore::new()),
+            Default::default(),
+            uploaders_config(crate::config::AnonymousUploads::Reject),
+        )
+        .await;
+        let route = contribute(Arc::clone(&state));
+
+        for authorization in [None, Some("Bearer wrong")] {
+            let mut request = warp::test::request()
+                .method("POST")
+                .path("/contribute")
+                .json(&valid_upload_listing());
+            if let Some(authorization) = authorization {
+                request = request.header("authorization", authorization);
+            }
+            let response = request.reply(&route).await;
+
+            assert_eq!(response.status(), warp::http::StatusCode::UNAUTHORIZED);
+            assert_eq!(error_code(&response), "unauthorized");
+            assert_eq!(response.headers()["www-authenticate"], "Bearer");
+        }
+
+        assert_eq!(
+            state.contribute_limits.violations(limits::LimitViolation::Unauthorized),
+            2
+        );
+        assert!(state
+            .store()
+            .listings_by_id(valid_upload_listing().id)
+            .await
+            .expect("memory store reads cannot fail")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn contribute_multiple_reports_a_rejection_code_per_listing() {
         let state = state_for_router_tests().await;
         let route = contribute_multiple(Arc::clone(&state));
-        let payload = vec![wide_listing_fixture(456), wide_listing_fixture(789)];
+
+        let mut too_long = valid_upload_listing();
+        too_long.seconds_remaining = 60 * 60 + 1;
+        let mut bad_restart = valid_upload_listing();
+        bad_restart.last_server_restart = i64::from(i32::MAX) + 1;
+        let mut bad_world = valid_upload_listing();
+        bad_world.created_world = 42;
+        let bad_duty = fixture_listing();
 
         let response = warp::test::request()
             .method("POST")
             .path("/contribute/multiple")
-            .json(&payload)
+            .json(&vec![valid_upload_listing(), too_long, bad_restart, bad_world, bad_duty])
             .reply(&route)
             .await;
 
         assert_eq!(response.status(), warp::http::StatusCode::OK);
-        assert_eq!(std::str::from_utf8(response.body()).unwrap(), "0/2 updated");
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["inserted"], 1);
+        assert_eq!(body["rejected"], 4);
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results[0]["outcome"], "inserted");
+        assert!(results[0].get("code").is_none());
+        assert_eq!(
+            results[1..]
+                .iter()
+                .map(|result| (result["outcome"].as_str().unwrap(), result["code"].as_str().unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("rejected", "seconds_remaining_too_large"),
+                ("rejected", "last_server_restart_out_of_range"),
+                ("rejected", "created_world_out_of_range"),
+                ("rejected", "invalid_duty_combination"),
+            ],
+        );
     }
 
     #[tokio::test]
-    async fn validate_and_insert_listing_accepts_signed_i32_boundary_values() {
+    async fn validate_and_insert_listings_accepts_signed_i32_boundary_values() {
         let state = state_for_router_tests().await;
 
         for boundary in [i64::from(i32::MIN), i64::from(i32::MAX)] {
             let mut listing = valid_upload_listing();
             listing.last_server_restart = boundary;
 
-            let error = validate_and_insert_listing(&state, listing)
-                .await
-                .expect_err("fixture should reach insert path and fail on io");
-
-            assert!(
-                error.to_string().contains("could not insert record"),
-                "expected insert io failure for boundary {boundary}, got: {error:#}"
+            let result = validate_and_insert_listings(&state, vec![listing], None).await.results.remove(0);
+            assert_eq!(
+                result.outcome,
+                contribute::ContributionOutcome::Inserted,
+                "boundary {boundary} must be stored, got: {:?}",
+                result.message,
             );
         }
+
+        let mut restarts = state
+            .store()
+            .listings_by_id(valid_upload_listing().id)
+            .await
+            .expect("memory store reads cannot fail")
+            .into_iter()
+            .map(|document| document.listing.last_server_restart)
+            .collect::<Vec<_>>();
+        restarts.sort_unstable();
+        assert_eq!(restarts, vec![i64::from(i32::MIN), i64::from(i32::MAX)]);
     }
 
     #[tokio::test]
-    async fn validate_and_insert_listing_rejects_values_outside_signed_i32_range() {
+    async fn validate_and_insert_listings_rejects_values_outside_signed_i32_range() {
         let state = state_for_router_tests().await;
 
         for out_of_range in [i64::from(i32::MIN) - 1, i64::from(i32::MAX) + 1] {
             let mut listing = valid_upload_listing();
             listing.last_server_restart = out_of_range;
 
-            let error = validate_and_insert_listing(&state, listing)
-                .await
-                .expect_err("out-of-range restart timestamp must be rejected");
+            let result = validate_and_insert_listings(&state, vec![listing], None).await.results.remove(0);
 
-            assert!(
-                error
-                    .to_string()
-                    .contains("invalid listing: last_server_restart"),
-                "expected validation failure for {out_of_range}, got: {error:#}"
+            assert_eq!(
+                result.code,
+                Some(contribute::RejectionCode::LastServerRestartOutOfRange),
+                "expected validation failure for {out_of_range}",
             );
         }
     }
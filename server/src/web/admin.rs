@@ -0,0 +1,63 @@
+use std::{collections::BTreeMap, convert::Infallible, sync::Arc};
+
+use serde::Serialize;
+use warp::{
+    filters::BoxedFilter,
+    http::{header::WWW_AUTHENTICATE, StatusCode},
+    reply::Response,
+    Filter, Reply,
+};
+
+use crate::{
+    config::Admin,
+    tasks::JobStatus,
+    web::{uploaders::bearer_token, State},
+};
+
+/// Whether a request may use the operator-only routes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Access {
+    Granted,
+    /// No token is configured, so the routes do not exist.
+    Disabled,
+    Denied,
+}
+
+pub(crate) fn access(admin: &Admin, authorization: Option<&str>) -> Access {
+    match admin.token.as_deref() {
+        None => Access::Disabled,
+        Some(expected) if authorization.and_then(bearer_token) == Some(expected) => Access::Granted,
+        Some(_) => Access::Denied,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TasksResponse {
+    pub jobs: BTreeMap<&'static str, JobStatus>,
+}
+
+pub fn routes(state: Arc<State>) -> BoxedFilter<(Response,)> {
+    warp::path!("admin" / "tasks")
+        .and(warp::get())
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(move |authorization: Option<String>| tasks(Arc::clone(&state), authorization))
+        .boxed()
+}
+
+async fn tasks(state: Arc<State>, authorization: Option<String>) -> Result<Response, Infallible> {
+    match access(&state.admin, authorization.as_deref()) {
+        Access::Granted => {}
+        Access::Disabled => {
+            return Ok(warp::reply::with_status("not found", StatusCode::NOT_FOUND).into_response());
+        }
+        Access::Denied => {
+            let reply = warp::reply::with_status("unauthorized", StatusCode::UNAUTHORIZED);
+            return Ok(warp::reply::with_header(reply, WWW_AUTHENTICATE, "Bearer").into_response());
+        }
+    }
+
+    let response = TasksResponse {
+        jobs: state.tasks.snapshot(),
+    };
+    Ok(warp::reply::json(&response).into_response())
+}
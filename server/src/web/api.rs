@@ -1,9 +1,6 @@
 use std::{collections::HashSet, convert::Infallible, sync::Arc};
 
-use chrono::Utc;
-use mongodb::bson::{doc, to_bson};
 use serde::{Serialize, Deserialize};
-use tokio_stream::StreamExt;
 use warp::{
     Filter,
     filters::BoxedFilter,
@@ -12,7 +9,7 @@ use warp::{
 };
 
 use crate::{
-    ffxiv::Language, listing::{DutyCategory, JobFlags}, listing_container::QueriedListing, sestring_ext::SeStringExt, web::State
+    ffxiv::Language, listing::{DutyCategory, JobFlags}, search::SearchQuery, sestring_ext::SeStringExt, store::ListingFilter, web::State
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,35 +111,6 @@ fn canonical_job_ids_for_codes(codes: &str) -> Vec<u32> {
 // Slot job-code strings are public v1 contract data, so keep canonical ID coverage current
 // through Beastmaster instead of treating PCT as the last renderable job token.
 
-fn slot_matches_any_accepted_slot_bit(
-    slot: &crate::listing::PartyFinderSlot,
-    accepted_slot_bits: &[u64],
-) -> bool {
-    accepted_slot_bits
-        .iter()
-        .any(|accepted_slot_bit| u64::from(slot.accepting.bits()) & accepted_slot_bit != 0)
-}
-
-fn build_job_match_conditions(accepted_slot_bits: &[u64]) -> Vec<mongodb::bson::Document> {
-    accepted_slot_bits
-        .iter()
-        .map(|&accepted_slot_bit| {
-            let accepted_slot_bit =
-                i64::try_from(accepted_slot_bit).expect("accepted slot bit must fit Mongo bit query");
-
-            doc! {
-                "listing.slots": {
-                    "$elemMatch": {
-                        "accepting": {
-                            "$bitsAllSet": accepted_slot_bit
-                        }
-                    }
-                }
-            }
-        })
-        .collect()
-}
-
 fn slot_info_from_listing_slot(
     slot_result: &std::result::Result<ffxiv_types_cn::jobs::ClassJob, (String, String)>,
 ) -> SlotInfo {
@@ -196,7 +164,7 @@ fn slot_info_from_listing_slot(
 /// - per_page: 每页数量，默认为20，最大为100
 /// - category: 分类过滤
 /// - world: 世界过滤
-/// - search: 搜索关键词，会匹配名称和描述
+/// - search: 搜索关键词，会匹配名称和描述（各语言的定型文、繁简体都能搜到），支持 "短语" 和 -排除词
 /// - datacenter: 数据中心过滤，支持多个数据中心，用逗号分隔，如"猫小胖,豆豆柴"
 /// - jobs: 职业过滤，支持多个职业ID，用逗号分隔，如"1,2,43"
 /// - duty: 副本过滤，支持多个副本ID，用逗号分隔，如"1,2,8"
@@ -399,415 +367,30 @@ pub fn listings_api(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
         
         let lang = Language::ChineseSimplified;
 
-        let two_hours_ago = Utc::now() - chrono::Duration::hours(2);
-        
-        let mut pipeline = vec![
-            // 1. 首先进行基础过滤，尽早减少数据量
-            doc! {
-                "$match": {
-                    "updated_at": { "$gte": two_hours_ago },
-                    // 过滤私有PF
-                    "listing.search_area": { "$bitsAllClear": 2 },
-                }
-            },
-        ];
-
-        // 2. 添加分类过滤条件 - 提前过滤
-        if let Some(cat) = &category {
-            pipeline.push(doc! {
-                "$match": {
-                    "listing.category": *cat as u32,
-                }
-            });
-        }
-
-        // 3. 添加世界/数据中心过滤条件 - 提前过滤
-        if let Some(w) = &world {
-            let world_id = crate::ffxiv::WORLDS.iter().find(|(_, world)| world.name() == w.name()).map(|(id, _)| *id as u32);
-            
-            if let Some(id) = world_id {
-                pipeline.push(doc! {
-                    "$match": {
-                        "listing.created_world": id
-                    }
-                });
-            }
-        } else if !datacenter_list.is_empty() {
-            let world_ids: Vec<u32> = crate::ffxiv::WORLDS.iter()
+        // 分类、世界/数据中心、副本和职业过滤交给存储层，并只保留每个玩家最新的招募
+        let created_world_ids: Vec<u32> = if let Some(w) = &world {
+            crate::ffxiv::WORLDS.iter()
+                .find(|(_, world)| world.name() == w.name())
+                .map(|(id, _)| vec![*id])
+                .unwrap_or_default()
+        } else {
+            crate::ffxiv::WORLDS.iter()
                 .filter(|(_, world)| datacenter_list.contains(&world.data_center().name().to_string()))
                 .map(|(id, _)| *id)
-                .collect();
-
-            if !world_ids.is_empty() {
-                let world_ids_i32: Vec<i32> = world_ids.iter().map(|&id| id as i32).collect();
-                pipeline.push(doc! {
-                    "$match": {
-                        "listing.created_world": { "$in": world_ids_i32 }
-                    }
-                });
-            }
-        }
-
-        // 3.5 添加副本ID过滤条件 - 提前过滤
-        if !duty_list.is_empty() {
-            let duty_ids_i32: Vec<i32> = duty_list.iter().map(|&id| id as i32).collect();
-            pipeline.push(doc! {
-                "$match": {
-                    "listing.duty": { "$in": duty_ids_i32 }
-                }
-            });
-        }
-
-        // 4. 添加职业过滤条件 - 提前过滤
-        if !accepted_slot_bits.is_empty() {
-            let job_conditions = build_job_match_conditions(&accepted_slot_bits);
-
-            if !job_conditions.is_empty() {
-                pipeline.push(doc! {
-                    "$match": {
-                        "$or": job_conditions
-                    }
-                });
-            }
-        }
-
-        // 5. 计算时间相关字段
-        pipeline.push(doc! {
-            "$set": {
-                "time_left": {
-                    "$divide": [
-                        {
-                            "$subtract": [
-                                { "$multiply": ["$listing.seconds_remaining", 1000] },
-                                { "$subtract": ["$$NOW", "$updated_at"] },
-                            ]
-                        },
-                        1000,
-                    ]
-                },
-                "minutes_since_update": {
-                    "$divide": [
-                        { "$subtract": ["$$NOW", "$updated_at"] },
-                        60000
-                    ]
-                }
-            }
-        });
-
-        // 6. 过滤过期招募（基于剩余时间或超过5分钟未更新）
-        pipeline.push(doc! {
-            "$match": {
-                "$and": [
-                    { "time_left": { "$gte": 0 } },
-                    { "minutes_since_update": { "$lt": 5.0 } }
-                ]
-            }
-        });
-
-        // 7. 按content_id_lower分组前先排序，确保获取最新的招募
-        pipeline.push(doc! {
-            "$sort": {
-                "updated_at": -1
-            }
-        });
-
-        // 8. 分组获取每个玩家最新的招募
-        pipeline.push(doc! {
-            "$group": {
-                "_id": "$listing.content_id_lower",
-                "doc": { "$first": "$$ROOT" }
-            }
-        });
-
-        // 9. 恢复文档结构
-        pipeline.push(doc! {
-            "$replaceRoot": { "newRoot": "$doc" }
-        });
-
-        // 10. 最后添加分页相关的排序和时间分组
-        pipeline.push(doc! {
-            "$set": {
-                "updated_minute": {
-                    "$dateTrunc": {
-                        "date": "$updated_at",
-                        "unit": "minute",
-                        "binSize": 5,
-                    },
-                }
-            }
-        });
-
-        // 11. 最终排序
-        pipeline.push(doc! {
-            "$sort": {
-                "updated_minute": -1,
-                "listing.pf_category": -1,
-                "time_left": 1,
-            }
-        });
-        
-        // 如果有搜索条件，我们需要在Rust端处理
-        // 但如果没有搜索条件，可以直接在MongoDB中分页
-        if search.is_none() {
-            // 添加计数阶段以获取总数
-            pipeline.push(doc! {
-                "$facet": {
-                    "metadata": [{ "$count": "total" }],
-                    "data": [
-                        { "$skip": ((page - 1) * per_page) as i64 },
-                        { "$limit": per_page as i64 }
-                    ]
-                }
-            });
-        }
-        
-        // 执行主查询
-        let res = state
-            .collection()
-            .aggregate(pipeline, None)
-            .await;
-            
-        let reply = match res {
-            Ok(mut cursor) => {
-                // 如果没有搜索条件，直接使用MongoDB的分页结果
-                if search.is_none() {
-                    if let Ok(Some(result)) = cursor.try_next().await {
-                        // 将结果转换为Document
-                        let facet_result = match mongodb::bson::from_document::<mongodb::bson::Document>(result) {
-                            Ok(doc) => doc,
-                            Err(_) => {
-                                // 返回空结果
-                                let pagination = Pagination {
-                                    total: 0,
-                                    page,
-                                    per_page,
-                                    total_pages: 0,
-                                };
-                                
-                                let response = ApiResponse {
-                                    data: Vec::<ApiListing>::new(),
-                                    pagination,
-                                };
-                                
-                                return Ok(warp::reply::with_status(
-                                    warp::reply::json(&response),
-                                    StatusCode::OK,
-                                ));
-                            }
-                        };
-                        
-                        // 提取元数据
-                        let total = match facet_result.get_array("metadata") {
-                            Ok(metadata) if !metadata.is_empty() => {
-                                match metadata[0].as_document() {
-                                    Some(doc) => match doc.get_i32("total") {
-                                        Ok(count) => count as usize,
-                                        Err(_) => 0
-                                    },
-                                    None => 0
-                                }
-                            },
-                            _ => 0
-                        };
-                        
-                        let total_pages = (total + per_page - 1) / per_page;
-                        
-                        // 提取数据
-                        let mut api_listings = Vec::new();
-                        if let Ok(data) = facet_result.get_array("data") {
-                            for item in data {
-                                if let Some(doc) = item.as_document() {
-                                    if let Ok(container) = mongodb::bson::from_document::<QueriedListing>(doc.clone()) {
-                                        let listing = &container.listing;
-                                        api_listings.push(ApiListing {
-                                            id: listing.id,
-                                            name: listing.name.full_text(&lang).to_string(),
-                                            description: listing.description.full_text(&lang).to_string(),
-                                            created_world: listing.created_world_string().to_string(),
-                                            created_world_id: u32::from(listing.created_world),
-                                            home_world: listing.home_world_string().to_string(),
-                                            home_world_id: u32::from(listing.home_world),
-                                            category: listing.pf_category().as_str().to_string(),
-                                            category_id: listing.category.as_u32(),
-                                            duty: listing.duty_name(&lang).to_string(),
-                                            min_item_level: listing.min_item_level,
-                                            slots_filled: listing.slots_filled(),
-                                            slots_available: listing.slots_available,
-                                            time_left: container.time_left,
-                                            updated_at: container.updated_at.to_rfc3339(),
-                                            is_cross_world: listing.is_cross_world(),
-                                            datacenter: listing.data_centre_name().map(|dc| dc.to_string()),
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                        
-                        let pagination = Pagination {
-                            total,
-                            page,
-                            per_page,
-                            total_pages,
-                        };
-                        
-                        let response = ApiResponse {
-                            data: api_listings,
-                            pagination,
-                        };
-                        
-                        // 缓存结果 - 设置30秒的TTL
-                        state.set_listings_cache(cache_key, response.clone(), 30).await;
-
-                        return Ok(warp::reply::with_status(
-                            warp::reply::json(&response),
-                            StatusCode::OK,
-                        ));
-                    }
-                    
-                    // 如果没有结果，返回空数组
-                    let pagination = Pagination {
-                        total: 0,
-                        page,
-                        per_page,
-                        total_pages: 0,
-                    };
-                    
-                    let response = ApiResponse {
-                        data: Vec::<ApiListing>::new(),
-                        pagination,
-                    };
-                    
-                    return Ok(warp::reply::with_status(
-                        warp::reply::json(&response),
-                        StatusCode::OK,
-                    ));
-                }
-                
-                // 如果有搜索条件，需要在Rust端处理
-                let mut containers = Vec::new();
-
-                while let Ok(Some(container)) = cursor.try_next().await {
-                    let res: anyhow::Result<QueriedListing> = try {
-                        let result: QueriedListing = mongodb::bson::from_document(container)?;
-                        result
-                    };
-                    if let Ok(listing) = res {
-                        containers.push(listing);
-                    }
-                }
-
-                // 在Rust端进行搜索过滤
-                let mut filtered_containers = Vec::new();
-                
-                // 应用搜索过滤条件
-                if let Some(s) = &search {
-                    let search_lower = s.to_lowercase();
-                    for container in containers {
-                        let listing = &container.listing;
-                        let name = listing.name.full_text(&lang).to_string().to_lowercase();
-                        let description = listing.description.full_text(&lang).to_string().to_lowercase();
-                        if name.contains(&search_lower) || description.contains(&search_lower) {
-                            // 如果有职业过滤，再次检查（以防MongoDB查询不完整）
-                            if !job_list.is_empty() {
-                                let has_job = listing
-                                    .slots
-                                    .iter()
-                                    .any(|slot| slot_matches_any_accepted_slot_bit(slot, &accepted_slot_bits));
-
-                                if has_job {
-                                    filtered_containers.push(container);
-                                }
-                            } else {
-                                filtered_containers.push(container);
-                            }
-                        }
-                    }
-                } else {
-                    // 如果没有搜索条件，但有职业过滤
-                    if !job_list.is_empty() {
-                        for container in containers {
-                            let listing = &container.listing;
-                            let has_job = listing
-                                .slots
-                                .iter()
-                                .any(|slot| slot_matches_any_accepted_slot_bit(slot, &accepted_slot_bits));
-                            
-                            if has_job {
-                                filtered_containers.push(container);
-                            }
-                        }
-                    } else {
-                        filtered_containers = containers;
-                    }
-                }
-                
-                // 计算总数和分页
-                let total = filtered_containers.len();
-                let total_pages = (total + per_page - 1) / per_page;
-                
-                // 手动分页
-                let start = (page - 1) * per_page;
-                
-                // 检查页码是否有效
-                let api_listings = if page > total_pages && total > 0 {
-                    // 如果请求的页码超出范围且有数据，返回空数组
-                    Vec::new()
-                } else {
-                    // 正常分页逻辑
-                    let start = if start >= total { 0 } else { start };
-                    let items_left = if total > start { total - start } else { 0 };
-                    let end = start + per_page.min(items_left);
-                    
-                    // 转换为API响应格式
-                    filtered_containers
-                        .iter()
-                        .skip(start)
-                        .take(end - start)
-                        .map(|container| {
-                            let listing = &container.listing;
-                            ApiListing {
-                                id: listing.id,
-                                name: listing.name.full_text(&lang).to_string(),
-                                description: listing.description.full_text(&lang).to_string(),
-                                created_world: listing.created_world_string().to_string(),
-                                created_world_id: u32::from(listing.created_world),
-                                home_world: listing.home_world_string().to_string(),
-                                home_world_id: u32::from(listing.home_world),
-                                category: listing.pf_category().as_str().to_string(),
-                                category_id: listing.category.as_u32(),
-                                duty: listing.duty_name(&lang).to_string(),
-                                min_item_level: listing.min_item_level,
-                                slots_filled: listing.slots_filled(),
-                                slots_available: listing.slots_available,
-                                time_left: container.time_left,
-                                updated_at: container.updated_at.to_rfc3339(),
-                                is_cross_world: listing.is_cross_world(),
-                                datacenter: listing.data_centre_name().map(|dc| dc.to_string()),
-                            }
-                        }).collect()
-                };
-
-                // 使用请求的原始页码
-                let pagination = Pagination {
-                    total,
-                    page,
-                    per_page,
-                    total_pages,
-                };
-
-                let response = ApiResponse {
-                    data: api_listings,
-                    pagination,
-                };
+                .collect()
+        };
 
-                // 缓存结果 - 设置30秒的TTL
-                state.set_listings_cache(cache_key, response.clone(), 30).await;
+        let filter = ListingFilter {
+            created_world_ids,
+            category_ids: category.map(|cat| vec![cat as u32]).unwrap_or_default(),
+            duty_ids: duty_list.iter().map(|&id| u32::from(id)).collect(),
+            accepted_slot_bits,
+            latest_per_player: true,
+            ..Default::default()
+        };
 
-                return Ok(warp::reply::with_status(
-                    warp::reply::json(&response),
-                    StatusCode::OK,
-                ));
-            },
+        let mut containers = match state.store().active_listings(&filter).await {
+            Ok(containers) => containers,
             Err(e) => {
                 eprintln!("{:#?}", e);
                 let pagination = Pagination {
@@ -816,20 +399,98 @@ pub fn listings_api(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
                     per_page,
                     total_pages: 0,
                 };
-                
+
                 let response = ApiResponse {
                     data: Vec::<ApiListing>::new(),
                     pagination,
                 };
-                
-                warp::reply::with_status(
+
+                return Ok(warp::reply::with_status(
                     warp::reply::json(&response),
                     StatusCode::INTERNAL_SERVER_ERROR,
-                )
+                ));
             }
         };
-        
-        Ok(reply)
+
+        // v1 的排序只看更新时间分组和剩余时间
+        containers.sort_by(|a, b| {
+            b.updated_minute
+                .cmp(&a.updated_minute)
+                .then_with(|| a.time_left.total_cmp(&b.time_left))
+        });
+
+        // 搜索在Rust端处理
+        if let Some(s) = &search {
+            let query = SearchQuery::parse(s);
+            containers.retain(|container| query.matches(&container.listing));
+        }
+
+        // 计算总数和分页
+        let total = containers.len();
+        let total_pages = (total + per_page - 1) / per_page;
+
+        // 手动分页
+        let start = (page - 1) * per_page;
+
+        // 检查页码是否有效
+        let api_listings = if page > total_pages && total > 0 {
+            // 如果请求的页码超出范围且有数据，返回空数组
+            Vec::new()
+        } else {
+            // 正常分页逻辑
+            let start = if start >= total { 0 } else { start };
+            let items_left = if total > start { total - start } else { 0 };
+            let end = start + per_page.min(items_left);
+
+            // 转换为API响应格式
+            containers
+                .iter()
+                .skip(start)
+                .take(end - start)
+                .map(|container| {
+                    let listing = &container.listing;
+                    ApiListing {
+                        id: listing.id,
+                        name: listing.name.full_text(&lang).to_string(),
+                        description: listing.description.full_text(&lang).to_string(),
+                        created_world: listing.created_world_string().to_string(),
+                        created_world_id: u32::from(listing.created_world),
+                        home_world: listing.home_world_string().to_string(),
+                        home_world_id: u32::from(listing.home_world),
+                        category: listing.pf_category().as_str().to_string(),
+                        category_id: listing.category.as_u32(),
+                        duty: listing.duty_name(&lang).to_string(),
+                        min_item_level: listing.min_item_level,
+                        slots_filled: listing.slots_filled(),
+                        slots_available: listing.slots_available,
+                        time_left: container.time_left,
+                        updated_at: container.updated_at.to_rfc3339(),
+                        is_cross_world: listing.is_cross_world(),
+                        datacenter: listing.data_centre_name().map(|dc| dc.to_string()),
+                    }
+                }).collect()
+        };
+
+        // 使用请求的原始页码
+        let pagination = Pagination {
+            total,
+            page,
+            per_page,
+            total_pages,
+        };
+
+        let response = ApiResponse {
+            data: api_listings,
+            pagination,
+        };
+
+        // 缓存结果 - 设置30秒的TTL
+        state.set_listings_cache(cache_key, response.clone(), 30).await;
+
+        Ok(warp::reply::with_status(
+            warp::reply::json(&response),
+            StatusCode::OK,
+        ))
     }
 
     // 使用原始的路由实现方式，避免复杂的类型问题
@@ -873,144 +534,53 @@ pub fn listing_detail_api(state: Arc<State>) -> BoxedFilter<(impl Reply, )> {
         }
         
         let lang = Language::ChineseSimplified;
-        let two_hours_ago = Utc::now() - chrono::Duration::hours(2);
-        
-        // 简化查询 - 合并多个$match阶段
-        let listing_id = match to_bson(&id) {
-            Ok(listing_id) => listing_id,
-            Err(error) => {
-                eprintln!("序列化招募ID错误: {:#?}", error);
-                return Ok(warp::reply::with_status(
-                    warp::reply::json(&serde_json::json!({
-                        "error": "数据库查询错误"
-                    })),
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                ));
-            }
-        };
-        let pipeline = vec![
-            doc! {
-                "$match": {
-                    "updated_at": { "$gte": two_hours_ago },
-                    "listing.id": listing_id,
-                    // 过滤私有PF
-                    "listing.search_area": { "$bitsAllClear": 2 },
-                }
-            },
-            doc! {
-                "$set": {
-                    "time_left": {
-                        "$divide": [
-                            {
-                                "$subtract": [
-                                    { "$multiply": ["$listing.seconds_remaining", 1000] },
-                                    { "$subtract": ["$$NOW", "$updated_at"] },
-                                ]
-                            },
-                            1000,
-                        ]
-                    },
-                    "minutes_since_update": {
-                        "$divide": [
-                            { "$subtract": ["$$NOW", "$updated_at"] },
-                            60000
-                        ]
-                    },
-                    "updated_minute": {
-                        "$dateTrunc": {
-                            "date": "$updated_at",
-                            "unit": "minute",
-                            "binSize": 5,
-                        },
-                    },
-                }
-            },
-            doc! {
-                "$match": {
-                    "$and": [
-                        { "time_left": { "$gte": 0 } },
-                        { "minutes_since_update": { "$lt": 5.0 } }
-                    ]
-                }
-            },
-            doc! {
-                "$sort": {
-                    "updated_at": -1,
-                }
-            },
-            doc! {
-                "$limit": 1
-            },
-        ];
-        
-        // 执行查询
-        let res = state
-            .collection()
-            .aggregate(pipeline, None)
-            .await;
-            
+
+        // 同一ID可能有多条记录，取最近更新的那条
+        let res = state.store().listings_by_id(id).await;
+
         Ok(match res {
-            Ok(mut cursor) => {
-                if let Ok(Some(container)) = cursor.try_next().await {
-                    let res: anyhow::Result<QueriedListing> = try {
-                        let result: QueriedListing = mongodb::bson::from_document(container)?;
-                        result
-                    };
-                    
-                    if let Ok(container) = res {
-                        let listing = &container.listing;
-                        
-                        // 构建槽位信息 - 使用迭代器而不是collect以提高性能
-                        let mut slots = Vec::with_capacity(listing.slots().len());
-                        for (_i, slot_result) in listing.slots().iter().enumerate() {
-                            slots.push(slot_info_from_listing_slot(slot_result));
-                        }
-                        
-                        // 构建详细信息
-                        let detailed = DetailedApiListing {
-                            id: listing.id,
-                            name: listing.name.full_text(&lang).to_string(),
-                            description: listing.description.full_text(&lang).to_string(),
-                            created_world: listing.created_world_string().to_string(),
-                            home_world: listing.home_world_string().to_string(),
-                            category: listing.pf_category().as_str().to_string(),
-                            duty: listing.duty_name(&lang).to_string(),
-                            min_item_level: listing.min_item_level,
-                            slots_filled: listing.slots_filled(),
-                            slots_available: listing.slots_available,
-                            time_left: container.time_left,
-                            updated_at: container.updated_at.to_rfc3339(),
-                            is_cross_world: listing.is_cross_world(),
-                            // 添加更多详细信息
-                            beginners_welcome: listing.beginners_welcome,
-                            duty_type: format!("{:?}", listing.duty_type),
-                            objective: format!("{:?}", listing.objective),
-                            conditions: format!("{:?}", listing.conditions),
-                            loot_rules: format!("{:?}", listing.loot_rules),
-                            slots,
-                            datacenter: listing.data_centre_name().map(|dc| dc.to_string()),
-                        };
-                        
-                        // 缓存结果 - 设置60秒的TTL
-                        state.set_detail_cache(id, detailed.clone(), 60).await;
-                        
-                        warp::reply::with_status(
-                            warp::reply::json(&detailed),
-                            StatusCode::OK,
-                        )
-                    } else {
-                        // 添加错误日志
-                        if let Err(e) = res {
-                            eprintln!("解析招募信息错误: {:#?}", e);
-                        }
-                        
-                        warp::reply::with_status(
-                            warp::reply::json(&serde_json::json!({
-                                "error": "无法解析招募信息"
-                            })),
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                        )
+            Ok(containers) => {
+                if let Some(container) = containers.into_iter().next() {
+                    let listing = &container.listing;
+
+                    // 构建槽位信息 - 使用迭代器而不是collect以提高性能
+                    let mut slots = Vec::with_capacity(listing.slots().len());
+                    for (_i, slot_result) in listing.slots().iter().enumerate() {
+                        slots.push(slot_info_from_listing_slot(slot_result));
                     }
+
+                    // 构建详细信息
+                    let detailed = DetailedApiListing {
+                        id: listing.id,
+                        name: listing.name.full_text(&lang).to_string(),
+                        description: listing.description.full_text(&lang).to_string(),
+                        created_world: listing.created_world_string().to_string(),
+                        home_world: listing.home_world_string().to_string(),
+                        category: listing.pf_category().as_str().to_string(),
+                        duty: listing.duty_name(&lang).to_string(),
+                        min_item_level: listing.min_item_level,
+                        slots_filled: listing.slots_filled(),
+                        slots_available: listing.slots_available,
+                        time_left: container.time_left,
+                        updated_at: container.updated_at.to_rfc3339(),
+                        is_cross_world: listing.is_cross_world(),
+                        // 添加更多详细信息
+                        beginners_welcome: listing.beginners_welcome,
+                        duty_type: format!("{:?}", listing.duty_type),
+                        objective: format!("{:?}", listing.objective),
+                        conditions: format!("{:?}", listing.conditions),
+                        loot_rules: format!("{:?}", listing.loot_rules),
+                        slots,
+                        datacenter: listing.data_centre_name().map(|dc| dc.to_string()),
+                    };
+
+                    // 缓存结果 - 设置60秒的TTL
+                    state.set_detail_cache(id, detailed.clone(), 60).await;
+
+                    warp::reply::with_status(
+                        warp::reply::json(&detailed),
+                        StatusCode::OK,
+                    )
                 } else {
                     warp::reply::with_status(
                         warp::reply::json(&serde_json::json!({
@@ -1055,7 +625,6 @@ pub(crate) async fn listing_detail_api_with_cache(
 mod tests {
     use super::*;
     use ffxiv_types_cn::jobs::{ClassJob, Job};
-    use mongodb::bson::doc;
     use warp::http::StatusCode;
 
     #[tokio::test]
@@ -1103,24 +672,95 @@ mod tests {
         assert_eq!(body["id"], wide_id, "v1 should return exact widened numeric ID");
     }
 
+    fn uploadable_listing(id: u64, content_id_lower: u32) -> crate::listing::PartyFinderListing {
+        let mut listing: crate::listing::PartyFinderListing =
+            serde_json::from_str(&crate::test::listing_json_with_id(id)).unwrap();
+        listing.content_id_lower = content_id_lower;
+        listing.created_world = 1042;
+        listing.home_world = 1042;
+        listing.duty = 0;
+        // the fixture only carries one slot; real uploads always send all eight
+        listing.slots = vec![listing.slots[0].clone(); 8];
+        listing
+    }
+
+    #[tokio::test]
+    async fn v1_routes_round_trip_contributed_listings() {
+        let router = crate::web::router(crate::web::state_for_router_tests().await);
+
+        // the same player re-listing only shows their newest listing in v1
+        let older = uploadable_listing(4_294_967_296, 456);
+        let newer = uploadable_listing(4_294_967_297, 456);
+        let other = uploadable_listing(4_294_967_298, 789);
+        for listing in [&older, &newer, &other] {
+            warp::test::request()
+                .method("POST")
+                .path("/contribute")
+                .json(listing)
+                .reply(&router)
+                .await;
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        let response = warp::test::request()
+            .method("GET")
+            .path("/api/listings?per_page=1")
+            .reply(&router)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["pagination"]["total"], 2);
+        assert_eq!(body["pagination"]["total_pages"], 2);
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+        let response = warp::test::request()
+            .method("GET")
+            .path("/api/listings?duty=0")
+            .reply(&router)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        let mut ids = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|listing| listing["id"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![4_294_967_297, 4_294_967_298]);
+        assert_eq!(body["data"][0]["created_world_id"], 1042);
+
+        let response = warp::test::request()
+            .method("GET")
+            .path("/api/listing/4294967296")
+            .reply(&router)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["id"], 4_294_967_296u64);
+
+        let response = warp::test::request()
+            .method("GET")
+            .path("/api/listing/1")
+            .reply(&router)
+            .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
     #[test]
     fn beastmaster_jobs_query_uses_canonical_slot_bit() {
         assert_eq!(accepted_slot_bits_for_job_ids(&[43]), vec![1u64 << 32]);
 
-        let job_conditions = build_job_match_conditions(&accepted_slot_bits_for_job_ids(&[43]));
+        let filter = ListingFilter {
+            accepted_slot_bits: accepted_slot_bits_for_job_ids(&[43]),
+            ..Default::default()
+        };
+        let mut listing: crate::listing::PartyFinderListing =
+            serde_json::from_str(&crate::test::listing_json_with_id(1)).unwrap();
+        listing.slots[0].accepting = JobFlags::BEASTMASTER;
+        assert!(filter.matches(&listing));
 
-        assert_eq!(
-            job_conditions,
-            vec![doc! {
-                "listing.slots": {
-                    "$elemMatch": {
-                        "accepting": {
-                            "$bitsAllSet": 4_294_967_296i64,
-                        }
-                    }
-                }
-            }]
-        );
+        listing.slots[0].accepting = JobFlags::PICTOMANCER;
+        assert!(!filter.matches(&listing));
     }
 
     #[test]
@@ -0,0 +1,209 @@
+use serde::Serialize;
+
+use crate::{
+    ffxiv::Language,
+    listing::PartyFinderListing,
+    sestring_ext::SeStringExt,
+    store::UpsertOutcome,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContributionOutcome {
+    Inserted,
+    Updated,
+    /// The stored listing already matched the upload; only `updated_at` was refreshed.
+    Unchanged,
+    Rejected,
+}
+
+impl From<UpsertOutcome> for ContributionOutcome {
+    fn from(outcome: UpsertOutcome) -> Self {
+        match outcome {
+            UpsertOutcome::Inserted => Self::Inserted,
+            UpsertOutcome::Updated => Self::Updated,
+            UpsertOutcome::Unchanged => Self::Unchanged,
+        }
+    }
+}
+
+impl ContributionOutcome {
+    /// The serialized name, also used as a metrics label.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Inserted => "inserted",
+            Self::Updated => "updated",
+            Self::Unchanged => "unchanged",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionCode {
+    SecondsRemainingTooLarge,
+    LastServerRestartOutOfRange,
+    CreatedWorldOutOfRange,
+    InvalidDutyCombination,
+    /// The listing was valid but could not be written.
+    StorageError,
+}
+
+impl RejectionCode {
+    /// The serialized name, also used as a metrics label.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SecondsRemainingTooLarge => "seconds_remaining_too_large",
+            Self::LastServerRestartOutOfRange => "last_server_restart_out_of_range",
+            Self::CreatedWorldOutOfRange => "created_world_out_of_range",
+            Self::InvalidDutyCombination => "invalid_duty_combination",
+            Self::StorageError => "storage_error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListingRejection {
+    pub code: RejectionCode,
+    pub message: String,
+}
+
+impl ListingRejection {
+    fn new(code: RejectionCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn storage_error() -> Self {
+        Self::new(RejectionCode::StorageError, "could not store listing")
+    }
+}
+
+/// What happened to one submitted listing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ListingResult {
+    /// Decimal string, like the v2 API, so wide ids survive JavaScript clients.
+    pub id: String,
+    pub outcome: ContributionOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<RejectionCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ListingResult {
+    pub fn accepted(id: u64, outcome: UpsertOutcome) -> Self {
+        Self {
+            id: id.to_string(),
+            outcome: outcome.into(),
+            code: None,
+            message: None,
+        }
+    }
+
+    pub fn rejected(id: u64, rejection: ListingRejection) -> Self {
+        Self {
+            id: id.to_string(),
+            outcome: ContributionOutcome::Rejected,
+            code: Some(rejection.code),
+            message: Some(rejection.message),
+        }
+    }
+}
+
+/// Body returned by `/contribute` and `/contribute/multiple`, with one result per submitted
+/// listing in submission order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ContributeResponse {
+    pub inserted: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub rejected: usize,
+    pub results: Vec<ListingResult>,
+}
+
+impl FromIterator<ListingResult> for ContributeResponse {
+    fn from_iter<I: IntoIterator<Item = ListingResult>>(results: I) -> Self {
+        let mut response = Self::default();
+        for result in results {
+            match result.outcome {
+                ContributionOutcome::Inserted => response.inserted += 1,
+                ContributionOutcome::Updated => response.updated += 1,
+                ContributionOutcome::Unchanged => response.unchanged += 1,
+                ContributionOutcome::Rejected => response.rejected += 1,
+            }
+            response.results.push(result);
+        }
+
+        response
+    }
+}
+
+pub fn validate_listing(listing: &PartyFinderListing) -> Result<(), ListingRejection> {
+    // Validate remaining time
+    if listing.seconds_remaining > 60 * 60 {
+        return Err(ListingRejection::new(
+            RejectionCode::SecondsRemainingTooLarge,
+            "invalid listing: remaining time greater than 1 hour",
+        ));
+    }
+
+    if listing.last_server_restart < i64::from(i32::MIN)
+        || listing.last_server_restart > i64::from(i32::MAX)
+    {
+        return Err(ListingRejection::new(
+            RejectionCode::LastServerRestartOutOfRange,
+            format!(
+                "invalid listing: last_server_restart {} out of range (expected signed 32-bit integer)",
+                listing.last_server_restart
+            ),
+        ));
+    }
+
+    if !matches!(listing.created_world, 1000..=1999 | 4000..=4999) {
+        return Err(ListingRejection::new(
+            RejectionCode::CreatedWorldOutOfRange,
+            format!(
+                "invalid listing: created_world {} out of range (expected 1000-1999 or 4000-4999)",
+                listing.created_world
+            ),
+        ));
+    }
+
+    // Validate duty/category/duty_type combination (fast path, no allocation)
+    if let Err(base_error) = crate::ffxiv::is_valid_duty_combination(listing.duty_type, listing.category, listing.duty) {
+        // Only extract strings when validation fails (lazy evaluation)
+        let player_name = listing.name.full_text(&Language::ChineseSimplified);
+        let player_name = if player_name.is_empty() { None } else { Some(player_name.as_str()) };
+
+        let world = listing.created_world();
+        let world_str = world.map(|w| w.name());
+
+        let description = listing.description.full_text(&Language::ChineseSimplified);
+        let description = if description.is_empty() { None } else { Some(description.as_str()) };
+
+        // Build full error message with context
+        let mut full_error = base_error;
+        if let Some(name) = player_name {
+            full_error.push_str(&format!(" | player: {}", name));
+        }
+        if let Some(w) = world_str {
+            full_error.push_str(&format!(" | world: {}", w));
+        }
+        if let Some(desc) = description {
+            let desc_preview: String = desc.chars().take(5).collect();
+            full_error.push_str(&format!(" | desc: {}", desc_preview));
+        }
+
+        eprintln!("未插入: {}", full_error);
+        return Err(ListingRejection::new(
+            RejectionCode::InvalidDutyCombination,
+            format!("invalid listing: {}", full_error),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Run with `cargo +nightly bench contribute`.
+//!
+//! Writes a large synthetic batch once listing by listing, as `/contribute/multiple` used to, and
+//! once through [`validate_and_insert_listings`], which makes a single
+//! [`ListingStore::upsert_many`] call. The writes go to [`MemoryStore`], or to the MongoDB at
+//! `RPF_TEST_MONGODB_URL` when it is set, where the saved round-trips show up.
+
+extern crate test;
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use test::Bencher;
+
+use crate::{
+    listing::PartyFinderListing,
+    store::{ListingStore, MemoryStore, MongoStore},
+};
+
+use super::{contribute::validate_listing, state_for_router_tests_with, validate_and_insert_listings};
+
+const BATCH_LEN: usize = 200;
+
+fn bench_store(runtime: &tokio::runtime::Runtime) -> Arc<dyn ListingStore> {
+    let Ok(url) = std::env::var("RPF_TEST_MONGODB_URL") else {
+        return Arc::new(MemoryStore::new());
+    };
+
+    runtime.block_on(async {
+        let store = MongoStore::connect_to(&url, "rpf_bench").await.expect("could not connect");
+        store.prepare().await.expect("could not prepare the bench database");
+        Arc::new(store) as Arc<dyn ListingStore>
+    })
+}
+
+fn synthetic_batch() -> Vec<PartyFinderListing> {
+    (0..BATCH_LEN as u64)
+        .map(|offset| {
+            let mut listing: PartyFinderListing =
+                serde_json::from_str(&crate::test::listing_json_with_id(4_294_967_296 + offset))
+                    .expect("listing fixture must deserialize");
+            listing.content_id_lower = offset as u32;
+            listing.created_world = 1042;
+            listing.home_world = 1042;
+            listing.current_world = 1042;
+            listing.duty = 0;
+            validate_listing(&listing).expect("synthetic listings must be valid");
+            listing
+        })
+        .collect()
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("could not build runtime")
+}
+
+#[bench]
+fn contribute_batch_one_upsert_per_listing(b: &mut Bencher) {
+    let runtime = runtime();
+    let store = bench_store(&runtime);
+    let batch = synthetic_batch();
+
+    b.iter(|| {
+        runtime.block_on(async {
+            let now = Utc::now();
+            for listing in &batch {
+                if validate_listing(listing).is_ok() {
+                    store.upsert(listing, None, now).await.unwrap();
+                }
+            }
+        })
+    });
+}
+
+#[bench]
+fn contribute_batch_bulk_upsert(b: &mut Bencher) {
+    let runtime = runtime();
+    let state = runtime.block_on(state_for_router_tests_with(
+        bench_store(&runtime),
+        Default::default(),
+        Default::default(),
+    ));
+    let batch = synthetic_batch();
+
+    b.iter(|| {
+        let response = runtime.block_on(validate_and_insert_listings(&state, batch.clone(), None));
+        assert_eq!(response.rejected, 0);
+        response
+    });
+}
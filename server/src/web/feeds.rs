@@ -0,0 +1,161 @@
+use std::{collections::HashMap, convert::Infallible, sync::Arc};
+
+use askama::Template;
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use warp::{
+    filters::BoxedFilter,
+    http::{header::CONTENT_TYPE, StatusCode},
+    reply::Response,
+    Filter, Reply,
+};
+
+use crate::{
+    ffxiv::Language,
+    listing_container::QueriedListing,
+    sestring_ext::SeStringExt,
+    template::feeds::{ListingsFeedEntry, ListingsFeedTemplate},
+    web::{
+        page_href,
+        v2::{
+            filters::parse_listings_query,
+            listings::{invalid_query_reply, matching_listings, paginated_collection_response},
+        },
+        State,
+    },
+};
+
+const FEED_PATH: &str = "/feeds/listings.atom";
+const ATOM_CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+/// Feed readers rarely send a cookie or a useful `Accept-Language`, so the feed URL can carry one.
+const LANG_PARAM: &str = "lang";
+
+/// `GET /feeds/listings.atom`: the page of active listings that `GET /api/v2/listings` would
+/// return for the same parameters, as an Atom feed.
+pub fn routes(state: Arc<State>) -> BoxedFilter<(Response,)> {
+    warp::path!("feeds" / "listings.atom")
+        .and(warp::get())
+        .and(
+            warp::query::<HashMap<String, String>>()
+                .or(warp::any().map(HashMap::new))
+                .unify(),
+        )
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+        .and(
+            warp::cookie::<String>("lang")
+                .or(warp::header::<String>("accept-language"))
+                .unify()
+                .map(Some)
+                .or(warp::any().map(|| None))
+                .unify(),
+        )
+        .and(warp::any().map(move || Arc::clone(&state)))
+        .and_then(listings_feed)
+        .boxed()
+}
+
+async fn listings_feed(
+    mut params: HashMap<String, String>,
+    raw_query: String,
+    codes: Option<String>,
+    state: Arc<State>,
+) -> Result<Response, Infallible> {
+    let lang = Language::from_codes(params.remove(LANG_PARAM).as_deref().or(codes.as_deref()));
+    let query = match parse_listings_query(&params) {
+        Ok(query) => query,
+        Err(error) => return Ok(invalid_query_reply(error).into_response()),
+    };
+
+    let listings = match matching_listings(&state, &query).await {
+        Ok(listings) => listings,
+        Err(error) => {
+            eprintln!("{error:#?}");
+            return Ok(warp::reply::with_status(
+                "could not load listings",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+            .into_response());
+        }
+    };
+    let page = paginated_collection_response(query, listings);
+
+    let updated = page
+        .data
+        .iter()
+        .map(|listing| listing.updated_at)
+        .max()
+        .unwrap_or_else(Utc::now);
+    let next_href = (page.pagination.page < page.pagination.total_pages)
+        .then(|| page_href(FEED_PATH, &raw_query, Some(page.pagination.page + 1)));
+    let template = ListingsFeedTemplate {
+        id: feed_id(&params),
+        updated: updated.to_rfc3339(),
+        self_href: page_href(FEED_PATH, &raw_query, None),
+        next_href,
+        entries: page.data.iter().map(|listing| feed_entry(listing, &lang)).collect(),
+        lang,
+    };
+
+    Ok(match template.render() {
+        Ok(body) => warp::reply::with_header(body, CONTENT_TYPE, ATOM_CONTENT_TYPE).into_response(),
+        Err(error) => {
+            eprintln!("{error:#?}");
+            warp::reply::with_status("could not render feed", StatusCode::INTERNAL_SERVER_ERROR)
+                .into_response()
+        }
+    })
+}
+
+/// The same filters give the same feed id, whatever their order, page or language.
+fn feed_id(params: &HashMap<String, String>) -> String {
+    let mut filters = params
+        .iter()
+        .filter(|(field, _)| !matches!(field.as_str(), "page" | "per_page"))
+        .map(|(field, value)| format!("{field}={value}"))
+        .collect::<Vec<_>>();
+    if filters.is_empty() {
+        return "urn:rpf:feed:listings".into();
+    }
+
+    filters.sort();
+    let digest = Sha256::digest(filters.join("&").as_bytes());
+    format!("urn:rpf:feed:listings:{}", &hex::encode(digest)[..16])
+}
+
+/// Entry ids follow the listing identity the store upserts by, so a listing keeps its entry
+/// while it is refreshed and a new listing reusing the id after a restart gets a new one.
+fn entry_id(listing: &QueriedListing) -> String {
+    format!(
+        "urn:rpf:listing:{}:{}:{}",
+        listing.listing.id, listing.listing.last_server_restart, listing.listing.created_world,
+    )
+}
+
+fn feed_entry(document: &QueriedListing, lang: &Language) -> ListingsFeedEntry {
+    let listing = &document.listing;
+    let (_, flags) = listing.prepend_flags();
+    let description = listing.description.full_text(lang);
+    // the listings page separates the flags from the description with a space, too
+    let mut content = vec![match (flags.is_empty(), description.is_empty()) {
+        (true, _) => description,
+        (false, true) => flags,
+        (false, false) => format!("{flags} {description}"),
+    }];
+    content.push(match listing.data_centre_name() {
+        Some(data_centre) => format!("{} ({data_centre})", listing.created_world_string()),
+        None => listing.created_world_string().into_owned(),
+    });
+    content.push(format!("{}/{}", listing.slots_filled(), listing.slots_available));
+    content.retain(|line| !line.is_empty());
+
+    ListingsFeedEntry {
+        id: entry_id(document),
+        title: listing.duty_name(lang).into_owned(),
+        author: format!("{} @ {}", listing.name.full_text(lang), listing.home_world_string()),
+        published: document.created_at.to_rfc3339(),
+        updated: document.updated_at.to_rfc3339(),
+        category_term: listing.html_pf_category(),
+        category_label: listing.pf_category().name().text(lang).to_owned(),
+        content: content.join("\n"),
+    }
+}
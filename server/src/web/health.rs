@@ -0,0 +1,143 @@
+use std::{convert::Infallible, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use warp::{filters::BoxedFilter, http::StatusCode, reply::Response, Filter, Reply};
+
+use crate::web::{State, STATS_REFRESH_INTERVAL};
+
+/// How long `/readyz` waits for the store to answer a ping.
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+/// Stats older than this many refresh intervals mean the refresher is stuck or failing.
+const STATS_MAX_MISSED_REFRESHES: u32 = 3;
+
+/// When the background work last succeeded, for `/readyz`.
+#[derive(Default)]
+pub struct Health {
+    last_stats_run: Mutex<Option<DateTime<Utc>>>,
+    last_contribution: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl Health {
+    pub fn stats_refreshed(&self, at: DateTime<Utc>) {
+        *self.last_stats_run.lock().unwrap() = Some(at);
+    }
+
+    pub fn contribution_accepted(&self, at: DateTime<Utc>) {
+        *self.last_contribution.lock().unwrap() = Some(at);
+    }
+
+    fn last_stats_run(&self) -> Option<DateTime<Utc>> {
+        *self.last_stats_run.lock().unwrap()
+    }
+
+    fn last_contribution(&self) -> Option<DateTime<Utc>> {
+        *self.last_contribution.lock().unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Failing,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub status: CheckStatus,
+    pub checks: ReadinessChecks,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessChecks {
+    pub store: StoreCheck,
+    pub stats: TimestampCheck,
+    /// Informational only: a node nobody uploads to can still serve reads.
+    pub contributions: TimestampCheck,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StoreCheck {
+    pub status: CheckStatus,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimestampCheck {
+    pub status: CheckStatus,
+    pub last_at: Option<String>,
+    pub age_seconds: Option<i64>,
+}
+
+impl TimestampCheck {
+    fn new(last_at: Option<DateTime<Utc>>, now: DateTime<Utc>, max_age: Option<chrono::Duration>) -> Self {
+        let age = last_at.map(|last_at| now - last_at);
+        let healthy = match max_age {
+            Some(max_age) => age.is_some_and(|age| age <= max_age),
+            None => true,
+        };
+
+        Self {
+            status: if healthy { CheckStatus::Ok } else { CheckStatus::Failing },
+            last_at: last_at.map(|last_at| last_at.to_rfc3339()),
+            age_seconds: age.map(|age| age.num_seconds()),
+        }
+    }
+}
+
+pub fn routes(state: Arc<State>) -> BoxedFilter<(Response,)> {
+    let healthz = warp::path("healthz")
+        .and(warp::path::end())
+        .and(warp::get())
+        .map(|| warp::reply::json(&serde_json::json!({ "status": CheckStatus::Ok })).into_response());
+
+    let readyz = warp::path("readyz")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || readyz(Arc::clone(&state)));
+
+    healthz.or(readyz).unify().boxed()
+}
+
+async fn readyz(state: Arc<State>) -> Result<Response, Infallible> {
+    let readiness = readiness(&state, Utc::now()).await;
+    let status = match readiness.status {
+        CheckStatus::Ok => StatusCode::OK,
+        CheckStatus::Failing => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    Ok(warp::reply::with_status(warp::reply::json(&readiness), status).into_response())
+}
+
+pub async fn readiness(state: &State, now: DateTime<Utc>) -> Readiness {
+    let started = Instant::now();
+    let ping = match tokio::time::timeout(PING_TIMEOUT, state.store().ping()).await {
+        Ok(result) => result.map_err(|error| format!("{error:#}")),
+        Err(_) => Err(format!("no answer within {}s", PING_TIMEOUT.as_secs())),
+    };
+    let store = StoreCheck {
+        status: if ping.is_ok() { CheckStatus::Ok } else { CheckStatus::Failing },
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: ping.err(),
+    };
+
+    let stats_max_age = chrono::Duration::from_std(STATS_REFRESH_INTERVAL * STATS_MAX_MISSED_REFRESHES)
+        .expect("stats refresh interval fits a chrono duration");
+    let checks = ReadinessChecks {
+        store,
+        stats: TimestampCheck::new(state.health.last_stats_run(), now, Some(stats_max_age)),
+        contributions: TimestampCheck::new(state.health.last_contribution(), now, None),
+    };
+
+    let failing = checks.store.status == CheckStatus::Failing
+        || checks.stats.status == CheckStatus::Failing
+        || checks.contributions.status == CheckStatus::Failing;
+
+    Readiness {
+        status: if failing { CheckStatus::Failing } else { CheckStatus::Ok },
+        checks,
+    }
+}
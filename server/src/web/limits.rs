@@ -0,0 +1,518 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde_json::{Map, Value};
+use warp::{
+    http::{
+        header::{RETRY_AFTER, WWW_AUTHENTICATE},
+        HeaderValue, StatusCode,
+    },
+    reject::Reject,
+    reply::Response,
+    Filter, Rejection, Reply,
+};
+
+use crate::{config::Contribute, web::v2::contracts::ErrorEnvelope};
+
+use super::{uploaders::AuthFailure, State};
+
+/// Longest `Retry-After` we hand out, so a zero refill rate does not produce an absurd value.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitViolation {
+    BodyTooLarge,
+    LengthRequired,
+    BatchTooLarge,
+    RateLimited,
+    Unauthorized,
+}
+
+impl LimitViolation {
+    pub const ALL: [Self; 5] = [
+        Self::BodyTooLarge,
+        Self::LengthRequired,
+        Self::BatchTooLarge,
+        Self::RateLimited,
+        Self::Unauthorized,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::BodyTooLarge => "payload_too_large",
+            Self::LengthRequired => "length_required",
+            Self::BatchTooLarge => "batch_too_large",
+            Self::RateLimited => "rate_limited",
+            Self::Unauthorized => "unauthorized",
+        }
+    }
+
+    fn status(self) -> StatusCode {
+        match self {
+            Self::BodyTooLarge | Self::BatchTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::LengthRequired => StatusCode::LENGTH_REQUIRED,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Who sent a contribution that made it past [`contribute_guard`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contributor {
+    pub client: IpAddr,
+    /// Label recorded on the listings this contribution writes.
+    pub uploader: Option<String>,
+}
+
+/// A contribution refused by [`ContributeLimits`], rendered as an [`ErrorEnvelope`].
+#[derive(Debug)]
+pub struct ContributeRejection {
+    violation: LimitViolation,
+    message: String,
+    details: Map<String, Value>,
+    retry_after: Option<Duration>,
+}
+
+impl Reject for ContributeRejection {}
+
+impl ContributeRejection {
+    pub fn to_response(&self) -> Response {
+        let status = self.violation.status();
+        let envelope = ErrorEnvelope::new(
+            self.violation.code(),
+            self.message.clone(),
+            self.details.clone(),
+        );
+        let mut response = warp::reply::with_status(warp::reply::json(&envelope), status).into_response();
+
+        if let Some(retry_after) = self.retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, retry_after_seconds(retry_after).into());
+        }
+
+        if self.violation == LimitViolation::Unauthorized {
+            response
+                .headers_mut()
+                .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+
+        response
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Per-client token buckets and violation counters for the contribute routes.
+pub struct ContributeLimits {
+    config: Contribute,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+    totals: [AtomicU64; LimitViolation::ALL.len()],
+    by_client: Mutex<HashMap<IpAddr, u64>>,
+    reported_total: AtomicU64,
+}
+
+impl ContributeLimits {
+    pub fn new(config: Contribute) -> Self {
+        Self {
+            config,
+            buckets: Default::default(),
+            totals: Default::default(),
+            by_client: Default::default(),
+            reported_total: Default::default(),
+        }
+    }
+
+    /// Resolves the client address, honouring `X-Forwarded-For` only when configured to. The
+    /// last entry is the one the trusted proxy appended; anything before it came from the client.
+    pub fn client_ip(&self, remote: Option<SocketAddr>, forwarded_for: Option<&str>) -> IpAddr {
+        let forwarded = forwarded_for
+            .filter(|_| self.config.trust_forwarded_for)
+            .and_then(|header| header.rsplit(',').next())
+            .and_then(|first| first.trim().parse().ok());
+
+        forwarded
+            .or_else(|| remote.map(|addr| addr.ip()))
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+    }
+
+    /// Charges one request to `client`'s bucket and checks the declared body size. Runs before
+    /// the body is read so oversized or throttled uploads never get deserialized.
+    pub fn admit(
+        &self,
+        client: IpAddr,
+        content_length: Option<u64>,
+        now: Instant,
+    ) -> Result<(), ContributeRejection> {
+        if let Err(retry_after) = self.take_token(client, now) {
+            let mut details = Map::new();
+            details.insert("retry_after_seconds".into(), retry_after_seconds(retry_after).into());
+
+            return Err(self.reject(
+                client,
+                LimitViolation::RateLimited,
+                "too many contributions from this client, slow down".to_string(),
+                details,
+                Some(retry_after),
+            ));
+        }
+
+        match content_length {
+            None => Err(self.reject(
+                client,
+                LimitViolation::LengthRequired,
+                "a Content-Length header is required".to_string(),
+                Map::new(),
+                None,
+            )),
+            Some(length) if length > self.config.max_body_bytes => {
+                let mut details = Map::new();
+                details.insert("max_body_bytes".into(), self.config.max_body_bytes.into());
+                details.insert("received_bytes".into(), length.into());
+
+                Err(self.reject(
+                    client,
+                    LimitViolation::BodyTooLarge,
+                    format!("request body exceeds {} bytes", self.config.max_body_bytes),
+                    details,
+                    None,
+                ))
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    pub fn check_batch_len(&self, client: IpAddr, len: usize) -> Result<(), ContributeRejection> {
+        if len <= self.config.max_batch_len {
+            return Ok(());
+        }
+
+        let mut details = Map::new();
+        details.insert("max_batch_len".into(), self.config.max_batch_len.into());
+        details.insert("received".into(), len.into());
+
+        Err(self.reject(
+            client,
+            LimitViolation::BatchTooLarge,
+            format!("batch exceeds {} listings", self.config.max_batch_len),
+            details,
+            None,
+        ))
+    }
+
+    pub fn unauthorized(&self, client: IpAddr, failure: AuthFailure) -> ContributeRejection {
+        self.reject(
+            client,
+            LimitViolation::Unauthorized,
+            failure.message().to_string(),
+            Map::new(),
+            None,
+        )
+    }
+
+    /// Total violations of `violation` since startup.
+    pub fn violations(&self, violation: LimitViolation) -> u64 {
+        self.totals[violation.index()].load(Ordering::Relaxed)
+    }
+
+    /// Violations of any kind by `client` since it was last swept.
+    #[cfg(test)]
+    pub fn client_violations(&self, client: IpAddr) -> u64 {
+        self.by_client
+            .lock()
+            .unwrap()
+            .get(&client)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Drops buckets that have refilled completely, since they are indistinguishable from new
+    /// ones, along with the violation counts of clients left without a bucket, and logs the
+    /// running totals whenever they changed since the previous sweep.
+    pub fn sweep(&self, now: Instant) {
+        let burst = f64::from(self.config.rate_limit_burst);
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets.retain(|_, bucket| self.refilled(bucket, now) < burst);
+        // per-client counts only throttle the rejection log, so a client that went quiet long
+        // enough to refill starts over rather than being remembered forever
+        self.by_client
+            .lock()
+            .unwrap()
+            .retain(|client, _| buckets.contains_key(client));
+        drop(buckets);
+
+        let total = LimitViolation::ALL
+            .iter()
+            .map(|&violation| self.violations(violation))
+            .sum::<u64>();
+        if self.reported_total.swap(total, Ordering::Relaxed) != total {
+            let counts = LimitViolation::ALL
+                .iter()
+                .map(|&violation| format!("{}={}", violation.code(), self.violations(violation)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            eprintln!("contribute limit violations since startup: {}", counts);
+        }
+    }
+
+    fn take_token(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        if self.config.rate_limit_burst == 0 {
+            return Ok(());
+        }
+
+        let burst = f64::from(self.config.rate_limit_burst);
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(client).or_insert(Bucket {
+            tokens: burst,
+            refilled_at: now,
+        });
+
+        bucket.tokens = self.refilled(bucket, now).min(burst);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        let rate = self.config.rate_limit_per_second;
+        let wait = if rate > 0.0 {
+            Duration::from_secs_f64((1.0 - bucket.tokens) / rate).min(MAX_RETRY_AFTER)
+        } else {
+            MAX_RETRY_AFTER
+        };
+        Err(wait)
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens + elapsed * self.config.rate_limit_per_second.max(0.0)
+    }
+
+    fn reject(
+        &self,
+        client: IpAddr,
+        violation: LimitViolation,
+        message: String,
+        details: Map<String, Value>,
+        retry_after: Option<Duration>,
+    ) -> ContributeRejection {
+        self.totals[violation.index()].fetch_add(1, Ordering::Relaxed);
+        let count = {
+            let mut by_client = self.by_client.lock().unwrap();
+            let count = by_client.entry(client).or_default();
+            *count += 1;
+            *count
+        };
+
+        // A throttled client can hit this many times a second; only log on powers of two so the
+        // offender still stands out without flooding the log.
+        if count.is_power_of_two() {
+            eprintln!(
+                "contribute limit {} for {} ({} violations so far)",
+                violation.code(),
+                client,
+                count
+            );
+        }
+
+        ContributeRejection {
+            violation,
+            message,
+            details,
+            retry_after,
+        }
+    }
+}
+
+fn retry_after_seconds(retry_after: Duration) -> u64 {
+    retry_after.as_secs_f64().ceil().max(1.0) as u64
+}
+
+/// Resolves the client, applies [`ContributeLimits::admit`] and authenticates the uploader
+/// ahead of the body filter.
+pub(super) fn contribute_guard(
+    state: Arc<State>,
+) -> impl Filter<Extract = (Contributor,), Error = Rejection> + Clone {
+    warp::addr::remote()
+        .and(warp::header::optional::<String>("x-forwarded-for"))
+        .and(warp::header::optional::<u64>("content-length"))
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(
+            move |remote: Option<SocketAddr>,
+                  forwarded_for: Option<String>,
+                  content_length: Option<u64>,
+                  authorization: Option<String>| {
+                let state = Arc::clone(&state);
+                async move {
+                    let limits = &state.contribute_limits;
+                    let client = limits.client_ip(remote, forwarded_for.as_deref());
+                    limits
+                        .admit(client, content_length, Instant::now())
+                        .map_err(warp::reject::custom)?;
+
+                    let uploader = state
+                        .uploaders
+                        .authenticate(state.store(), authorization.as_deref(), Instant::now())
+                        .await
+                        .map_err(|failure| warp::reject::custom(limits.unauthorized(client, failure)))?;
+
+                    Ok::<_, Rejection>(Contributor { client, uploader })
+                }
+            },
+        )
+}
+
+/// Turns a [`ContributeRejection`] into its response and passes every other rejection through,
+/// so routes after the contribute routes still get a chance to match.
+pub(super) async fn recover_contribute_rejection(rejection: Rejection) -> Result<Response, Rejection> {
+    match rejection.find::<ContributeRejection>() {
+        Some(rejected) => Ok(rejected.to_response()),
+        None => Err(rejection),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    const OTHER_CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+
+    fn limits(burst: u32, per_second: f64) -> ContributeLimits {
+        ContributeLimits::new(Contribute {
+            rate_limit_burst: burst,
+            rate_limit_per_second: per_second,
+            ..Contribute::default()
+        })
+    }
+
+    #[test]
+    fn bucket_allows_burst_then_refills_over_time() {
+        let limits = limits(2, 1.0);
+        let start = Instant::now();
+
+        assert!(limits.take_token(CLIENT, start).is_ok());
+        assert!(limits.take_token(CLIENT, start).is_ok());
+        let retry_after = limits
+            .take_token(CLIENT, start)
+            .expect_err("burst must be exhausted");
+        assert_eq!(retry_after, Duration::from_secs(1));
+
+        assert!(limits.take_token(OTHER_CLIENT, start).is_ok(), "buckets are per client");
+        assert!(limits.take_token(CLIENT, start + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn zero_burst_disables_rate_limiting() {
+        let limits = limits(0, 0.0);
+        let now = Instant::now();
+
+        for _ in 0..100 {
+            assert!(limits.take_token(CLIENT, now).is_ok());
+        }
+    }
+
+    #[test]
+    fn admit_rejects_missing_and_oversized_content_length() {
+        let limits = ContributeLimits::new(Contribute {
+            max_body_bytes: 10,
+            ..Contribute::default()
+        });
+        let now = Instant::now();
+
+        assert!(limits.admit(CLIENT, Some(10), now).is_ok());
+        let missing = limits.admit(CLIENT, None, now).expect_err("length is required");
+        assert_eq!(missing.violation, LimitViolation::LengthRequired);
+        let oversized = limits.admit(CLIENT, Some(11), now).expect_err("body is too large");
+        assert_eq!(oversized.violation, LimitViolation::BodyTooLarge);
+
+        assert_eq!(limits.violations(LimitViolation::LengthRequired), 1);
+        assert_eq!(limits.violations(LimitViolation::BodyTooLarge), 1);
+        assert_eq!(limits.client_violations(CLIENT), 2);
+        assert_eq!(limits.client_violations(OTHER_CLIENT), 0);
+    }
+
+    #[test]
+    fn forwarded_for_is_only_trusted_when_configured() {
+        let remote = Some(SocketAddr::new(CLIENT, 443));
+        let forwarded = Some("10.0.0.1, 198.51.100.7");
+
+        assert_eq!(limits(1, 1.0).client_ip(remote, forwarded), CLIENT);
+
+        let trusting = ContributeLimits::new(Contribute {
+            trust_forwarded_for: true,
+            ..Contribute::default()
+        });
+        assert_eq!(
+            trusting.client_ip(remote, forwarded),
+            "198.51.100.7".parse::<IpAddr>().unwrap(),
+        );
+        assert_eq!(trusting.client_ip(remote, Some("garbage")), CLIENT);
+    }
+
+    #[test]
+    fn spoofed_forwarded_for_entries_share_the_proxy_reported_bucket() {
+        let limits = ContributeLimits::new(Contribute {
+            rate_limit_burst: 1,
+            rate_limit_per_second: 1.0,
+            trust_forwarded_for: true,
+            ..Contribute::default()
+        });
+        let remote = Some(SocketAddr::new(CLIENT, 443));
+        let now = Instant::now();
+
+        let first = limits.client_ip(remote, Some("203.0.113.1, 198.51.100.7"));
+        let spoofed = limits.client_ip(remote, Some("203.0.113.2, 198.51.100.7"));
+        assert_eq!(first, spoofed);
+        limits.admit(first, Some(1), now).unwrap();
+        let rejected = limits.admit(spoofed, Some(1), now).expect_err("the bucket is shared");
+        assert_eq!(rejected.violation, LimitViolation::RateLimited);
+    }
+
+    #[test]
+    fn sweep_drops_only_refilled_buckets() {
+        let limits = limits(2, 1.0);
+        let start = Instant::now();
+
+        limits.take_token(CLIENT, start).unwrap();
+        limits.take_token(OTHER_CLIENT, start + Duration::from_secs(5)).unwrap();
+        limits.sweep(start + Duration::from_secs(5));
+
+        let buckets = limits.buckets.lock().unwrap();
+        assert!(!buckets.contains_key(&CLIENT));
+        assert!(buckets.contains_key(&OTHER_CLIENT));
+    }
+
+    #[test]
+    fn sweep_forgets_violations_of_clients_whose_bucket_refilled() {
+        let limits = limits(1, 1.0);
+        let start = Instant::now();
+
+        for client in [CLIENT, OTHER_CLIENT] {
+            limits.admit(client, Some(1), start).unwrap();
+            limits.admit(client, Some(1), start).expect_err("burst must be exhausted");
+        }
+        limits.admit(OTHER_CLIENT, Some(1), start + Duration::from_secs(5)).unwrap();
+        limits.sweep(start + Duration::from_secs(5));
+
+        assert_eq!(limits.client_violations(CLIENT), 0);
+        assert_eq!(limits.client_violations(OTHER_CLIENT), 1);
+        assert_eq!(limits.violations(LimitViolation::RateLimited), 2, "totals are kept");
+    }
+}
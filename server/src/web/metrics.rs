@@ -0,0 +1,124 @@
+use std::{convert::Infallible, sync::Arc};
+
+use chrono::Utc;
+
+use warp::{
+    filters::BoxedFilter,
+    http::header::CONTENT_TYPE,
+    log::Info,
+    reply::Response,
+    Filter, Reply,
+};
+
+use crate::{
+    metrics::{render_counter, render_gauge},
+    web::{limits::LimitViolation, State},
+};
+
+const CONTENT_TYPE_TEXT: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// The route template a request path was served by, so listing ids and duty keys do not become
+/// label values.
+pub fn route_label(path: &str) -> &'static str {
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+    match segments.as_slice() {
+        [""] => "/",
+        ["listings"] => "/listings",
+        ["listings", _] => "/listings/{id}",
+        ["feeds", "listings.atom"] => "/feeds/listings.atom",
+        ["stats"] => "/stats",
+        ["stats", "7days"] => "/stats/7days",
+        ["stats", "duty", ..] => "/stats/duty/{duty}",
+        ["stats", "7days", "duty", ..] => "/stats/7days/duty/{duty}",
+        ["contribute"] => "/contribute",
+        ["contribute", "multiple"] => "/contribute/multiple",
+        ["metrics"] => "/metrics",
+        ["healthz"] => "/healthz",
+        ["readyz"] => "/readyz",
+        ["admin", "tasks"] => "/admin/tasks",
+        ["assets", ..] => "/assets",
+        ["api", "listings"] => "/api/listings",
+        ["api", "listing", _] => "/api/listing/{id}",
+        ["api", "v2", "listings"] => "/api/v2/listings",
+        ["api", "v2", "listings", "stream"] => "/api/v2/listings/stream",
+        ["api", "v2", "listings", "fit"] => "/api/v2/listings/fit",
+        ["api", "v2", "listings", "eligible"] => "/api/v2/listings/eligible",
+        ["api", "v2", "listings", _] => "/api/v2/listings/{id}",
+        ["api", "v2", "history", "listings"] => "/api/v2/history/listings",
+        ["api", "v2", "stats"] => "/api/v2/stats",
+        ["api", "v2", "stats", "7days"] => "/api/v2/stats/7days",
+        ["api", "v2", "stats", "duties", ..] => "/api/v2/stats/duties/{duty}",
+        ["api", "v2", "subscriptions"] => "/api/v2/subscriptions",
+        ["api", "v2", "subscriptions", _] => "/api/v2/subscriptions/{id}",
+        ["api", "v2", "subscriptions", _, "deliveries"] => "/api/v2/subscriptions/{id}/deliveries",
+        ["api", "v2", "lookups", "duties", "search"] => "/api/v2/lookups/duties/search",
+        ["api", "v2", "lookups", _] => "/api/v2/lookups/{kind}",
+        _ => "other",
+    }
+}
+
+/// Records one served request; installed on the whole router with `warp::log::custom`.
+pub fn observe_request(state: &State, info: Info<'_>) {
+    let route = route_label(info.path());
+    state.metrics.http_requests.inc(&[route, info.method().as_str(), info.status().as_str()]);
+    state.metrics.http_request_duration.observe_duration(&[route], info.elapsed());
+}
+
+pub fn route(state: Arc<State>) -> BoxedFilter<(Response,)> {
+    warp::path("metrics")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(move || scrape(Arc::clone(&state)))
+        .boxed()
+}
+
+async fn scrape(state: Arc<State>) -> Result<Response, Infallible> {
+    let mut out = String::new();
+    state.metrics.render(&mut out);
+
+    render_counter(
+        &mut out,
+        "rpf_contribute_requests_rejected_total",
+        "Contribute requests refused before their listings were read, by limit.",
+        "reason",
+        LimitViolation::ALL
+            .iter()
+            .map(|violation| (violation.code(), state.contribute_limits.violations(*violation) as f64)),
+    );
+
+    // counted from the listing events rather than the store, so a scrape never reads every
+    // active listing and a store outage cannot fail it
+    render_gauge(
+        &mut out,
+        "rpf_active_listings",
+        "Listings currently in the active window, by data center of the created world.",
+        "datacenter",
+        state
+            .listing_events
+            .active_by_data_centre(Utc::now())
+            .into_iter()
+            .map(|(datacenter, count)| (datacenter, count as f64)),
+    );
+
+    Ok(warp::reply::with_header(out, CONTENT_TYPE, CONTENT_TYPE_TEXT).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_labels_collapse_ids() {
+        assert_eq!(route_label("/"), "/");
+        assert_eq!(route_label("/api/v2/listings/4294967296"), "/api/v2/listings/{id}");
+        assert_eq!(route_label("/api/v2/listings/stream"), "/api/v2/listings/stream");
+        assert_eq!(route_label("/api/listing/12"), "/api/listing/{id}");
+        assert_eq!(
+            route_label("/api/v2/subscriptions/6710c2a4e13823a1b7c3d9f0/deliveries"),
+            "/api/v2/subscriptions/{id}/deliveries",
+        );
+        assert_eq!(route_label("/stats/7days/duty/2/0/55"), "/stats/7days/duty/{duty}");
+        assert_eq!(route_label("/assets/d3.js"), "/assets");
+        assert_eq!(route_label("/wp-login.php"), "other");
+    }
+}
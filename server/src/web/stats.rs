@@ -1,199 +1,81 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use chrono::{Duration, Utc};
-use mongodb::bson::{Document, doc};
-use mongodb::options::AggregateOptions;
-use tokio_stream::StreamExt;
-use crate::stats::{Aliases, Statistics};
-use crate::web::State;
-
-lazy_static::lazy_static! {
-    static ref QUERY: [Document; 2] = [
-        doc! {
-            "$match": {
-                // filter private pfs
-                "listing.search_area": { "$bitsAllClear": 2 },
-            }
-        },
-        doc! {
-            "$facet": {
-                "count": [
-                    {
-                        "$count": "count",
-                    },
-                ],
-                "duties": [
-                    {
-                        "$group": {
-                            "_id": [
-                                "$listing.duty_type",
-                                "$listing.category",
-                                "$listing.duty",
-                            ],
-                            "count": {
-                                "$sum": 1
-                            },
-                        }
-                    },
-                    {
-                        "$sort": {
-                            "count": -1,
-                        }
-                    }
-                ],
-                "hosts": [
-                    {
-                        "$group": {
-                            "_id": {
-                                "world": "$listing.created_world",
-                                "content_id": "$listing.content_id_lower",
-                            },
-                            "count": { "$sum": 1 },
-                        }
-                    },
-                    {
-                        "$sort": {
-                            "count": -1,
-                        }
-                    },
-                    {
-                        "$group": {
-                            "_id": "$_id.world",
-                            "count": {
-                                "$sum": "$count",
-                            },
-                            "content_ids": {
-                                "$push": {
-                                    "content_id": "$_id.content_id",
-                                    "count": "$count",
-                                }
-                            }
-                        }
-                    },
-                    {
-                        "$addFields": {
-                            "content_ids": {
-                                "$slice": ["$content_ids", 0, 15],
-                            },
-                        }
-                    },
-                    {
-                        "$sort": { "count": -1 }
-                    },
-                ],
-                "hours": [
-                    {
-                        "$group": {
-                            "_id": {
-                                "$hour": "$created_at",
-                            },
-                            "count": {
-                                "$sum": 1
-                            },
-                        }
-                    },
-                    {
-                        "$sort": {
-                            "_id": 1,
-                        }
-                    }
-                ],
-                "days": [
-                    {
-                        "$group": {
-                            "_id": {
-                                "$dayOfWeek": "$created_at",
-                            },
-                            "count": {
-                                "$sum": 1
-                            },
-                        }
-                    },
-                    {
-                        "$sort": {
-                            "_id": 1,
-                        }
-                    }
-                ],
-            }
-        },
-    ];
-
-    static ref ALIASES_QUERY: [Document; 1] = [
-        doc! {
-            "$facet": {
-                "aliases": [
-                    {
-                        "$sort": {
-                            "created_at": -1,
-                        }
-                    },
-                    {
-                        "$group": {
-                            "_id": "$listing.content_id_lower",
-                            "alias": {
-                                "$first": {
-                                    "name": "$listing.name",
-                                    "home_world": "$listing.home_world",
-                                },
-                            },
-                        }
-                    }
-                ],
-            },
-        },
-    ];
-}
+use crate::listing::{DutyCategory, DutyType};
+use crate::stats::{CachedDutyStatistics, DutyInfo, DutyKey, Statistics};
+use crate::web::{CacheEntry, State, STATS_REFRESH_INTERVAL};
 
 pub async fn get_stats(state: &State) -> Result<Statistics> {
-    get_stats_internal(state, QUERY.iter().cloned()).await
+    state.store().statistics(None).await
 }
 
 pub async fn get_stats_seven_days(state: &State) -> Result<Statistics> {
     let last_week = Utc::now() - Duration::days(7);
 
-    let mut docs = QUERY.to_vec();
-    docs.insert(0, doc! {
-        "$match": {
-            "created_at": {
-                "$gte": last_week,
-            },
-        },
-    });
+    state.store().statistics(Some(last_week)).await
+}
+
+/// Whether `duty` names a duty this server knows how to label. Normal duties and roulettes must
+/// be in the game data tables, so unknown ids are not each given their own cache entry.
+pub fn is_known_duty((duty_type, category, duty): DutyKey) -> bool {
+    let (Some(duty_type), Some(category)) = (DutyType::from_u8(duty_type), DutyCategory::from_u32(category)) else {
+        return false;
+    };
+    match duty_type {
+        DutyType::Normal if !matches!(category, DutyCategory::None) => crate::ffxiv::duty(u32::from(duty)).is_some(),
+        _ => crate::ffxiv::is_valid_duty_combination(duty_type, category, duty).is_ok(),
+    }
+}
+
+/// Per-duty statistics are computed on first request and then kept for as long as the global
+/// ones are, since there are too many duties to refresh them all in the background.
+pub async fn get_duty_stats(state: &State, duty: DutyKey) -> Result<Arc<CachedDutyStatistics>> {
+    if let Some(stats) = cached_duty_stats(state, duty).await {
+        state.metrics.cache_lookup("duty_stats", true);
+        return Ok(stats);
+    }
+    state.metrics.cache_lookup("duty_stats", false);
 
-    get_stats_internal(state, docs).await
+    // concurrent misses for one duty wait for the first one's result instead of each aggregating
+    let load = Arc::clone(state.duty_stats_loads.lock().unwrap().entry(duty).or_default());
+    let _loading = load.lock().await;
+    if let Some(stats) = cached_duty_stats(state, duty).await {
+        return Ok(stats);
+    }
+    let stats = compute_duty_stats(state, duty).await;
+    state.duty_stats_loads.lock().unwrap().remove(&duty);
+    stats
 }
 
-async fn get_stats_internal(state: &State, docs: impl IntoIterator<Item = Document>) -> Result<Statistics> {
-    let mut cursor = state
-        .collection()
-        .aggregate(docs, AggregateOptions::builder()
-            .allow_disk_use(true)
-            .build())
-        .await?;
-    let doc = cursor.try_next().await?;
-    let doc = doc.ok_or_else(|| anyhow::anyhow!("missing document"))?;
-    let mut stats: Statistics = mongodb::bson::from_document(doc)?;
+async fn cached_duty_stats(state: &State, duty: DutyKey) -> Option<Arc<CachedDutyStatistics>> {
+    state
+        .duty_stats
+        .read()
+        .await
+        .get(&duty)
+        .filter(|entry| entry.expires_at > Utc::now())
+        .map(|entry| Arc::clone(&entry.data))
+}
 
-    let ids: Vec<u32> = stats.hosts.iter().flat_map(|host| host.content_ids.iter().map(|entry| entry.content_id)).collect();
-    let mut aliases_query: Vec<Document> = ALIASES_QUERY.iter().cloned().collect();
-    aliases_query.insert(0, doc! {
-        "$match": {
-            "listing.content_id_lower": {
-                "$in": ids,
-            }
-        }
+async fn compute_duty_stats(state: &State, duty: DutyKey) -> Result<Arc<CachedDutyStatistics>> {
+    let last_week = Utc::now() - Duration::days(7);
+    let all_time = state.store().duty_statistics(duty, None).await?;
+    let seven_days = state.store().duty_statistics(duty, Some(last_week)).await?;
+    let stats = Arc::new(CachedDutyStatistics {
+        duty: DutyInfo {
+            info: duty,
+            count: all_time.num_listings(),
+        },
+        all_time,
+        seven_days,
     });
-    let mut cursor = state
-        .collection()
-        .aggregate(aliases_query, AggregateOptions::builder()
-            .allow_disk_use(true)
-            .build())
-        .await?;
-    let doc = cursor.try_next().await?;
-    let doc = doc.ok_or_else(|| anyhow::anyhow!("missing document"))?;
-    let aliases: Aliases = mongodb::bson::from_document(doc)?;
 
-    stats.aliases = aliases.aliases;
+    let expires_at = Utc::now() + Duration::from_std(STATS_REFRESH_INTERVAL)?;
+    state.duty_stats.write().await.insert(duty, CacheEntry {
+        data: Arc::clone(&stats),
+        expires_at,
+    });
 
     Ok(stats)
 }
@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::{self, AnonymousUploads, UploaderToken},
+    store::ListingStore,
+};
+
+/// Label recorded for anonymous uploads when `anonymous = "tag"`.
+pub const ANONYMOUS_UPLOADER: &str = "anonymous";
+/// How long a token looked up in the store is trusted, including tokens that were not found.
+const STORED_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthFailure {
+    MissingToken,
+    MalformedHeader,
+    UnknownToken,
+    DisabledToken,
+    LookupFailed,
+}
+
+impl AuthFailure {
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::MissingToken => "an uploader token is required",
+            Self::MalformedHeader => "expected an Authorization: Bearer <token> header",
+            Self::UnknownToken => "unknown uploader token",
+            Self::DisabledToken => "uploader token is disabled",
+            Self::LookupFailed => "could not verify uploader token",
+        }
+    }
+}
+
+struct CachedLookup {
+    uploader: Option<UploaderToken>,
+    expires_at: Instant,
+}
+
+/// Resolves the `Authorization` header of a contribution to the uploader label stored on the
+/// listings it writes.
+///
+/// Tokens from `config.toml` take precedence over the store, so a config entry with
+/// `enabled = false` revokes a token even if it is still enabled in `rpf.uploaders`.
+pub struct Uploaders {
+    config: config::Uploaders,
+    stored: Mutex<HashMap<String, CachedLookup>>,
+}
+
+impl Uploaders {
+    pub fn new(config: config::Uploaders) -> Self {
+        Self {
+            config,
+            stored: Default::default(),
+        }
+    }
+
+    /// `Ok(None)` means the upload is accepted without an uploader label.
+    pub async fn authenticate(
+        &self,
+        store: &dyn ListingStore,
+        authorization: Option<&str>,
+        now: Instant,
+    ) -> Result<Option<String>, AuthFailure> {
+        let header = match authorization {
+            Some(header) => header,
+            None => {
+                return match self.config.anonymous {
+                    AnonymousUploads::Allow => Ok(None),
+                    AnonymousUploads::Tag => Ok(Some(ANONYMOUS_UPLOADER.to_string())),
+                    AnonymousUploads::Reject => Err(AuthFailure::MissingToken),
+                };
+            }
+        };
+
+        let token = bearer_token(header).ok_or(AuthFailure::MalformedHeader)?;
+        let uploader = match self.config.tokens.iter().find(|uploader| uploader.token == token) {
+            Some(uploader) => Some(uploader.clone()),
+            None => self.lookup_stored(store, token, now).await?,
+        };
+
+        match uploader {
+            Some(uploader) if uploader.enabled => Ok(Some(uploader.label)),
+            Some(_) => Err(AuthFailure::DisabledToken),
+            None => Err(AuthFailure::UnknownToken),
+        }
+    }
+
+    /// Forgets expired store lookups.
+    pub fn sweep(&self, now: Instant) {
+        self.stored
+            .lock()
+            .unwrap()
+            .retain(|_, lookup| lookup.expires_at > now);
+    }
+
+    async fn lookup_stored(
+        &self,
+        store: &dyn ListingStore,
+        token: &str,
+        now: Instant,
+    ) -> Result<Option<UploaderToken>, AuthFailure> {
+        if let Some(lookup) = self.stored.lock().unwrap().get(token) {
+            if lookup.expires_at > now {
+                return Ok(lookup.uploader.clone());
+            }
+        }
+
+        let uploader = store.find_uploader(token).await.map_err(|e| {
+            eprintln!("{:#?}", e);
+            AuthFailure::LookupFailed
+        })?;
+
+        self.stored.lock().unwrap().insert(
+            token.to_string(),
+            CachedLookup {
+                uploader: uploader.clone(),
+                expires_at: now + STORED_TOKEN_TTL,
+            },
+        );
+
+        Ok(uploader)
+    }
+}
+
+pub(super) fn bearer_token(header: &str) -> Option<&str> {
+    let (scheme, token) = header.trim().split_once(' ')?;
+    let token = token.trim();
+
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+
+    fn token(token: &str, label: &str, enabled: bool) -> UploaderToken {
+        UploaderToken {
+            token: token.to_string(),
+            label: label.to_string(),
+            enabled,
+        }
+    }
+
+    fn uploaders(anonymous: AnonymousUploads) -> Uploaders {
+        Uploaders::new(config::Uploaders {
+            anonymous,
+            tokens: vec![
+                token("config-token", "config", true),
+                token("revoked-token", "revoked", false),
+            ],
+        })
+    }
+
+    #[tokio::test]
+    async fn anonymous_uploads_follow_the_configured_mode() {
+        let store = MemoryStore::new();
+        let now = Instant::now();
+
+        assert_eq!(
+            uploaders(AnonymousUploads::Allow).authenticate(&store, None, now).await,
+            Ok(None),
+        );
+        assert_eq!(
+            uploaders(AnonymousUploads::Tag).authenticate(&store, None, now).await,
+            Ok(Some(ANONYMOUS_UPLOADER.to_string())),
+        );
+        assert_eq!(
+            uploaders(AnonymousUploads::Reject).authenticate(&store, None, now).await,
+            Err(AuthFailure::MissingToken),
+        );
+    }
+
+    #[tokio::test]
+    async fn tokens_resolve_from_config_then_store() {
+        let store = MemoryStore::with_uploaders([
+            token("stored-token", "stored", true),
+            token("stored-disabled", "stored-disabled", false),
+            token("revoked-token", "revoked-in-config-only", true),
+        ]);
+        let uploaders = uploaders(AnonymousUploads::Allow);
+        let now = Instant::now();
+        let auth = |header: &'static str| uploaders.authenticate(&store, Some(header), now);
+
+        assert_eq!(auth("Bearer config-token").await, Ok(Some("config".to_string())));
+        assert_eq!(auth("bearer  stored-token ").await, Ok(Some("stored".to_string())));
+        assert_eq!(auth("Bearer stored-disabled").await, Err(AuthFailure::DisabledToken));
+        assert_eq!(auth("Bearer revoked-token").await, Err(AuthFailure::DisabledToken));
+        assert_eq!(auth("Bearer nope").await, Err(AuthFailure::UnknownToken));
+        assert_eq!(auth("Basic Zm9vOmJhcg==").await, Err(AuthFailure::MalformedHeader));
+        assert_eq!(auth("Bearer").await, Err(AuthFailure::MalformedHeader));
+    }
+}
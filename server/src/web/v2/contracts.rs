@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
@@ -20,8 +22,30 @@ pub struct MemberEnvelope<T> {
     pub data: T,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CursorPagination {
+    pub per_page: usize,
+    /// Opaque; pass back as `cursor` to fetch the next page. `null` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CursorEnvelope<T> {
+    pub data: Vec<T>,
+    pub pagination: CursorPagination,
+}
+
 pub type ListingCollectionResponse = CollectionEnvelope<ListingSummary>;
 pub type ListingMemberResponse = MemberEnvelope<ListingDetail>;
+pub type ListingHistoryResponse = CursorEnvelope<HistoricalListing>;
+pub type ListingFitResponse = CollectionEnvelope<ListingFit>;
+pub type ListingEligibilityResponse = CollectionEnvelope<ListingEligibility>;
+pub type DutySearchResponse = CollectionEnvelope<DutySearchHit>;
+pub type DutyStatisticsResponse = MemberEnvelope<DutyStatistics>;
+pub type StatisticsResponse = MemberEnvelope<Statistics>;
+pub type SubscriptionResponse = MemberEnvelope<WebhookSubscription>;
+pub type SubscriptionCollectionResponse = CollectionEnvelope<WebhookSubscription>;
+pub type DeliveryCollectionResponse = CollectionEnvelope<WebhookDelivery>;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ErrorEnvelope {
@@ -59,6 +83,13 @@ impl ErrorEnvelope {
         Self::new("invalid_query", message, details)
     }
 
+    pub fn invalid_body(field: impl Into<String>, message: impl Into<String>) -> Self {
+        let mut details = Map::new();
+        details.insert("field".into(), Value::String(field.into()));
+
+        Self::new("invalid_body", message, details)
+    }
+
     pub fn not_implemented(message: impl Into<String>) -> Self {
         Self::new("not_implemented", message, Map::new())
     }
@@ -83,6 +114,83 @@ pub struct ListingSummary {
     pub beginners_welcome: bool,
 }
 
+/// A stored listing row as of its last upload.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HistoricalListing {
+    #[serde(flatten)]
+    pub summary: ListingSummary,
+    pub created_at: String,
+}
+
+/// A listing that seats the whole party at once.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ListingFit {
+    #[serde(flatten)]
+    pub summary: ListingSummary,
+    /// One entry per requested job, in request order.
+    pub assignments: Vec<SlotAssignment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SlotAssignment {
+    pub job_id: u32,
+    /// Position in the listing detail's `slots`.
+    pub slot_index: usize,
+}
+
+/// The character a `POST /api/v2/listings/eligible` request checks listings for.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CharacterProfile {
+    pub home_world_id: u32,
+    pub jobs: Vec<CharacterJob>,
+    /// Whether the character has cleared the duty being searched for.
+    #[serde(default)]
+    pub duty_cleared: bool,
+    /// Whether this week's reward for that duty has already been claimed.
+    #[serde(default)]
+    pub weekly_reward_claimed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CharacterJob {
+    pub job_id: u32,
+    pub item_level: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ListingEligibility {
+    #[serde(flatten)]
+    pub summary: ListingSummary,
+    pub eligible: bool,
+    /// The profile's jobs that can take an open slot, in profile order.
+    pub eligible_job_ids: Vec<u32>,
+    pub exclusions: Vec<Exclusion>,
+}
+
+/// Why a listing, or one of the profile's jobs, cannot join.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Exclusion {
+    pub code: ExclusionCode,
+    /// Set when only this job is affected.
+    pub job_id: Option<u32>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionCode {
+    OtherWorld,
+    OtherDataCenter,
+    DutyClearRequired,
+    DutyNotClearedRequired,
+    WeeklyRewardUnclaimedRequired,
+    ItemLevelTooLow,
+    JobAlreadyInParty,
+    NoOpenSlotForJob,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ListingDetail {
     pub id: String,
@@ -113,3 +221,237 @@ pub struct ListingSlot {
     pub filled_job_id: Option<u32>,
     pub accepted_job_ids: Vec<u32>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LocalisedLabel {
+    pub en: String,
+    pub ja: String,
+    pub de: String,
+    pub fr: String,
+    pub zh: String,
+}
+
+impl From<&crate::ffxiv::LocalisedText> for LocalisedLabel {
+    fn from(text: &crate::ffxiv::LocalisedText) -> Self {
+        Self {
+            en: text.en.into(),
+            ja: text.ja.into(),
+            de: text.de.into(),
+            fr: text.fr.into(),
+            zh: text.zh.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorldLookup {
+    pub id: u32,
+    pub name: String,
+    pub datacenter: String,
+    pub region: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DatacenterLookup {
+    pub name: String,
+    pub region: String,
+    pub world_ids: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RegionLookup {
+    pub name: String,
+    pub datacenters: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DutyLookup {
+    pub duty_type_id: u32,
+    pub category_id: Option<u32>,
+    pub duty_id: u32,
+    pub name: LocalisedLabel,
+    pub high_end: bool,
+}
+
+/// A duty lookup entry ranked against `q`; `score` runs from 1 to 100.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DutySearchHit {
+    #[serde(flatten)]
+    pub duty: DutyLookup,
+    pub score: u32,
+    pub matched_on: DutyNameForm,
+}
+
+/// Which spelling of the duty name matched best.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DutyNameForm {
+    En,
+    Ja,
+    JaRomaji,
+    De,
+    Fr,
+    Zh,
+    ZhPinyin,
+    ZhInitials,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JobLookup {
+    pub id: u32,
+    pub code: String,
+    pub role_id: Option<u32>,
+    pub accepted_in_slots: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LabelLookup {
+    pub id: u32,
+    pub name: LocalisedLabel,
+}
+
+/// The `/stats` page data, with ids in place of duty and world names.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Statistics {
+    pub listings: usize,
+    /// Most listed first.
+    pub duties: Vec<DutyCount>,
+    /// Busiest world first.
+    pub hosts: Vec<HostCount>,
+    pub hours: Vec<HourCount>,
+    pub weekdays: Vec<WeekdayCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DutyCount {
+    pub duty_type_id: u32,
+    pub category_id: u32,
+    pub duty_id: u32,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HostCount {
+    pub created_world_id: u32,
+    pub count: usize,
+    /// The world's most active hosts, most listings first.
+    pub top_hosts: Vec<PlayerCount>,
+    /// Listings by every host not in `top_hosts`.
+    pub other_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlayerCount {
+    /// `null` when the host's name could not be resolved.
+    pub player_name: Option<String>,
+    pub home_world_id: Option<u32>,
+    pub count: usize,
+}
+
+/// Statistics for one duty, addressed by the same ids listings use.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DutyStatistics {
+    pub duty_type_id: u32,
+    pub category_id: u32,
+    pub duty_id: u32,
+    pub all_time: DutyStatisticsWindow,
+    pub seven_days: DutyStatisticsWindow,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DutyStatisticsWindow {
+    pub listings: usize,
+    pub volume: Vec<DailyCount>,
+    pub hours: Vec<HourCount>,
+    pub weekdays: Vec<WeekdayCount>,
+    pub median_min_item_level: Option<u16>,
+    pub objectives: Vec<IdCount>,
+    pub conditions: Vec<IdCount>,
+    pub beginners_welcome_share: f64,
+    pub filled: usize,
+    pub average_filled_lifetime_seconds: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DailyCount {
+    /// UTC day, `YYYY-MM-DD`.
+    pub date: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HourCount {
+    pub hour: u8,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WeekdayCount {
+    /// 1 is Sunday, 7 is Saturday.
+    pub weekday: u8,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IdCount {
+    pub id: u32,
+    pub count: usize,
+}
+
+/// The body of `POST /api/v2/subscriptions`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SubscriptionRequest {
+    pub url: String,
+    /// Key for the `X-RPF-Signature` HMAC of every payload.
+    pub secret: String,
+    /// `GET /api/v2/listings` filter parameters; numbers and booleans may be given unquoted.
+    #[serde(default)]
+    pub filters: BTreeMap<String, Value>,
+}
+
+/// The body of `PATCH /api/v2/subscriptions/{id}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SubscriptionUpdate {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub url: String,
+    pub filters: BTreeMap<String, String>,
+    /// Cleared by the server after too many failed deliveries in a row.
+    pub enabled: bool,
+    pub consecutive_failures: u32,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub listing_id: String,
+    pub created_at: String,
+    pub succeeded: bool,
+    pub attempts: Vec<WebhookAttempt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WebhookAttempt {
+    pub started_at: String,
+    /// `null` when no response arrived; `error` says why.
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// What a subscription's URL receives for every matching new listing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WebhookPayload {
+    pub event: String,
+    pub subscription_id: String,
+    /// Also sent as `X-RPF-Delivery`; retries of one delivery share it.
+    pub delivery_id: String,
+    pub listing: ListingSummary,
+}
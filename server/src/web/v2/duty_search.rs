@@ -0,0 +1,267 @@
+use std::{cmp::Reverse, collections::HashMap, convert::Infallible};
+
+use warp::{filters::BoxedFilter, reply::Response, Filter, Reply};
+
+use crate::search::{self, kana, pinyin};
+
+use super::{
+    contracts::{DutyLookup, DutyNameForm, DutySearchHit, DutySearchResponse, ErrorEnvelope},
+    filters::{DEFAULT_PER_PAGE, MAX_PER_PAGE},
+    listings::invalid_query_reply,
+    lookups,
+};
+
+const SUPPORTED_QUERY_FIELDS: [&str; 2] = ["q", "limit"];
+
+lazy_static::lazy_static! {
+    /// Every duty lookup entry with the spellings of its name that a query is compared with.
+    static ref CANDIDATES: Vec<(DutyLookup, Vec<(DutyNameForm, String)>)> = lookups::duties()
+        .into_iter()
+        .map(|duty| {
+            let forms = name_forms(&duty);
+            (duty, forms)
+        })
+        .collect();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DutySearchQuery {
+    pub q: String,
+    pub limit: usize,
+}
+
+/// Mounted ahead of `/api/v2/lookups/{kind}`, which only takes one segment after `lookups`.
+pub fn route() -> BoxedFilter<(Response,)> {
+    warp::path!("api" / "v2" / "lookups" / "duties" / "search")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(
+            warp::query::<HashMap<String, String>>()
+                .or(warp::any().map(HashMap::new))
+                .unify(),
+        )
+        .and_then(search_duties)
+        .boxed()
+}
+
+async fn search_duties(params: HashMap<String, String>) -> Result<Response, Infallible> {
+    match parse_duty_search_query(&params) {
+        Ok(query) => Ok(warp::reply::json(&duty_search_response(&query)).into_response()),
+        Err(error) => Ok(invalid_query_reply(error).into_response()),
+    }
+}
+
+pub fn parse_duty_search_query(
+    params: &HashMap<String, String>,
+) -> Result<DutySearchQuery, ErrorEnvelope> {
+    let mut fields = params.keys().collect::<Vec<_>>();
+    fields.sort();
+    if let Some(field) = fields
+        .into_iter()
+        .find(|field| !SUPPORTED_QUERY_FIELDS.contains(&field.as_str()))
+    {
+        return Err(ErrorEnvelope::invalid_query(
+            field.clone(),
+            format!("{field} is not a supported query parameter"),
+        ));
+    }
+
+    let q = params
+        .get("q")
+        .map(|q| compact(&search::normalize(q)))
+        .unwrap_or_default();
+    if q.is_empty() {
+        return Err(ErrorEnvelope::invalid_query(
+            "q",
+            "q must contain at least one letter or digit",
+        ));
+    }
+
+    let limit = match params.get("limit") {
+        Some(value) => value
+            .parse::<usize>()
+            .ok()
+            .filter(|limit| (1..=MAX_PER_PAGE).contains(limit))
+            .ok_or_else(|| {
+                ErrorEnvelope::invalid_query("limit", "limit must be between 1 and 100")
+            })?,
+        None => DEFAULT_PER_PAGE,
+    };
+
+    Ok(DutySearchQuery { q, limit })
+}
+
+/// The best `limit` duties for `query.q`, best first. Equal scores prefer the shorter name, then
+/// lookup order.
+pub fn duty_search_response(query: &DutySearchQuery) -> DutySearchResponse {
+    let romaji = kana::romaji(&query.q);
+    let ascii = query.q.is_ascii();
+
+    let mut hits = CANDIDATES
+        .iter()
+        .filter_map(|(duty, forms)| {
+            let (form, name_len, score) = forms
+                .iter()
+                .filter_map(|(form, name)| {
+                    let q = match form {
+                        DutyNameForm::JaRomaji => &romaji,
+                        DutyNameForm::ZhPinyin | DutyNameForm::ZhInitials if !ascii => return None,
+                        _ => &query.q,
+                    };
+                    Some((*form, name.chars().count(), fuzzy_score(name, q)?))
+                })
+                .max_by_key(|(_, name_len, score)| (*score, Reverse(*name_len)))?;
+            Some((
+                name_len,
+                DutySearchHit {
+                    duty: duty.clone(),
+                    score,
+                    matched_on: form,
+                },
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    // `CANDIDATES` is in lookup order and the sort is stable
+    hits.sort_by_key(|(name_len, hit)| (Reverse(hit.score), *name_len));
+    hits.truncate(query.limit);
+
+    lookups::lookup_collection(hits.into_iter().map(|(_, hit)| hit).collect())
+}
+
+fn name_forms(duty: &DutyLookup) -> Vec<(DutyNameForm, String)> {
+    let name = &duty.name;
+    let mut forms = vec![
+        (DutyNameForm::Zh, compact(&search::normalize(&name.zh))),
+        (DutyNameForm::Ja, compact(&search::normalize(&name.ja))),
+        (DutyNameForm::En, compact(&search::normalize(&name.en))),
+        (DutyNameForm::De, compact(&search::normalize(&name.de))),
+        (DutyNameForm::Fr, compact(&search::normalize(&name.fr))),
+    ];
+
+    if let Some((full, initials)) = pinyin::spell(&forms[0].1) {
+        forms.push((DutyNameForm::ZhPinyin, full));
+        forms.push((DutyNameForm::ZhInitials, initials));
+    }
+    let romaji = kana::romaji(&forms[1].1);
+    if romaji != forms[1].1 {
+        forms.push((DutyNameForm::JaRomaji, romaji));
+    }
+
+    forms.retain(|(_, name)| !name.is_empty());
+    forms
+}
+
+/// Letters and digits only, so spacing and punctuation never decide a match.
+fn compact(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+/// 100 for the whole name, 80–89 for a prefix, 60–69 elsewhere in the name, and up to 50 when
+/// the query's characters appear in order with gaps; longer shares of the name score higher.
+fn fuzzy_score(name: &str, q: &str) -> Option<u32> {
+    let name_len = name.chars().count() as u32;
+    let q_len = q.chars().count() as u32;
+    let share = (10 * q_len / name_len.max(1)).min(9);
+
+    if name == q {
+        return Some(100);
+    }
+    if name.starts_with(q) {
+        return Some(80 + share);
+    }
+    if name.contains(q) {
+        return Some(60 + share);
+    }
+    if q_len < 2 {
+        return None;
+    }
+
+    // the shortest window holding `q` as a subsequence, starting from each first-char match
+    let name = name.chars().collect::<Vec<_>>();
+    let q = q.chars().collect::<Vec<_>>();
+    let span = (0..name.len())
+        .filter(|start| name[*start] == q[0])
+        .filter_map(|start| {
+            let mut matched = 0;
+            for (offset, c) in name[start..].iter().enumerate() {
+                if *c == q[matched] {
+                    matched += 1;
+                    if matched == q.len() {
+                        return Some(offset as u32 + 1);
+                    }
+                }
+            }
+            None
+        })
+        .min()?;
+
+    Some(50 * q_len / span).filter(|score| *score >= 20)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(q: &str) -> DutySearchQuery {
+        let params = HashMap::from([("q".to_owned(), q.to_owned())]);
+        parse_duty_search_query(&params).unwrap()
+    }
+
+    fn top(q: &str) -> DutySearchHit {
+        duty_search_response(&query(q)).data.remove(0)
+    }
+
+    #[test]
+    fn scores_whole_names_above_prefixes_above_gaps() {
+        assert_eq!(fuzzy_score("jueyulan", "jueyulan"), Some(100));
+        assert_eq!(fuzzy_score("jueyulan", "jue"), Some(83));
+        assert_eq!(fuzzy_score("jueyulan", "yulan"), Some(66));
+        assert_eq!(fuzzy_score("jueyulan", "jyl"), Some(25));
+        assert_eq!(fuzzy_score("jueyulan", "x"), None);
+        assert_eq!(fuzzy_score("jueyulan", "jn"), None);
+    }
+
+    #[test]
+    fn chinese_names_match_by_pinyin_and_initials() {
+        let by_name = top("幻想龙诗绝境战");
+        assert_eq!(by_name.matched_on, DutyNameForm::Zh);
+        assert_eq!(by_name.score, 100);
+
+        let by_pinyin = top("xianglongshi");
+        assert_eq!(by_pinyin.matched_on, DutyNameForm::ZhPinyin);
+        assert_eq!(by_pinyin.duty, by_name.duty);
+
+        let by_initials = top("hxls");
+        assert_eq!(by_initials.matched_on, DutyNameForm::ZhInitials);
+        assert_eq!(by_initials.duty, by_name.duty);
+    }
+
+    #[test]
+    fn japanese_names_match_by_kana_and_romaji() {
+        let by_romaji = top("bahamuto");
+        assert_eq!(by_romaji.matched_on, DutyNameForm::JaRomaji);
+        assert!(by_romaji.duty.name.ja.contains("バハムート"));
+
+        let by_hiragana = top("ばはむーと");
+        assert_eq!(by_hiragana.matched_on, DutyNameForm::JaRomaji);
+        assert!(by_hiragana.duty.name.ja.contains("バハムート"));
+    }
+
+    #[test]
+    fn rejects_blank_queries_and_bad_limits() {
+        let error = |params: &[(&str, &str)]| {
+            let params = params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            parse_duty_search_query(&params).unwrap_err().error.details["field"].clone()
+        };
+
+        assert_eq!(error(&[]), "q");
+        assert_eq!(error(&[("q", " ・ ")]), "q");
+        assert_eq!(error(&[("q", "abc"), ("limit", "0")]), "limit");
+        assert_eq!(error(&[("q", "abc"), ("limit", "101")]), "limit");
+        assert_eq!(error(&[("q", "abc"), ("duty_id", "1")]), "duty_id");
+    }
+}
@@ -0,0 +1,240 @@
+use std::{collections::HashMap, convert::Infallible, sync::Arc};
+
+use warp::{
+    filters::BoxedFilter, http::StatusCode, hyper::body::Bytes, reply::Response, Filter, Reply,
+};
+
+use crate::{
+    listing::{ConditionFlags, PartyFinderListing, SearchAreaFlags},
+    listing_container::QueriedListing,
+    web::State,
+};
+
+use super::{
+    contracts::{
+        CharacterProfile, ErrorEnvelope, Exclusion, ExclusionCode, ListingEligibility,
+        ListingEligibilityResponse,
+    },
+    filters::{parse_listings_query, ListingsQuery},
+    id_inventory,
+    listings::{
+        collection_filter, empty_collection_response, internal_error_reply, invalid_query_reply,
+        paginated_collection_response, project_slots, query_demands_empty_collection,
+        sorted_matching_listings,
+    },
+};
+
+/// A profile is a handful of jobs; anything bigger is not one.
+const MAX_PROFILE_BYTES: u64 = 16 * 1024;
+
+pub fn route(state: Arc<State>) -> BoxedFilter<(Response,)> {
+    warp::path!("api" / "v2" / "listings" / "eligible")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(
+            warp::query::<HashMap<String, String>>()
+                .or(warp::any().map(HashMap::new))
+                .unify(),
+        )
+        .and(warp::body::content_length_limit(MAX_PROFILE_BYTES))
+        .and(warp::body::bytes())
+        .and(warp::any().map(move || state.clone()))
+        .and_then(eligible)
+        .boxed()
+}
+
+async fn eligible(
+    query: HashMap<String, String>,
+    body: Bytes,
+    state: Arc<State>,
+) -> Result<Response, Infallible> {
+    let query = match parse_listings_query(&query) {
+        Ok(query) => query,
+        Err(error) => return Ok(invalid_query_reply(error).into_response()),
+    };
+    let profile = match parse_profile(&body) {
+        Ok(profile) => profile,
+        Err(error) => {
+            let reply = warp::reply::with_status(warp::reply::json(&error), StatusCode::BAD_REQUEST);
+            return Ok(reply.into_response());
+        }
+    };
+
+    if query_demands_empty_collection(&query) {
+        let response = empty_collection_response::<ListingEligibility>(&query);
+        return Ok(warp::reply::json(&response).into_response());
+    }
+
+    // excluded listings are part of the answer, so the store only gets the query's filters
+    match state.store().active_listings(&collection_filter(&query)).await {
+        Ok(documents) => {
+            let response = eligibility_response(query, &profile, &documents);
+            Ok(warp::reply::json(&response).into_response())
+        }
+        Err(error) => {
+            eprintln!("{error:#?}");
+            Ok(internal_error_reply().into_response())
+        }
+    }
+}
+
+pub fn parse_profile(body: &[u8]) -> Result<CharacterProfile, ErrorEnvelope> {
+    let profile: CharacterProfile = serde_json::from_slice(body).map_err(|error| {
+        ErrorEnvelope::invalid_body("body", format!("body must be a character profile: {error}"))
+    })?;
+
+    if !id_inventory::world_ids().contains(&profile.home_world_id) {
+        return Err(ErrorEnvelope::invalid_body(
+            "home_world_id",
+            "home_world_id must be a known world id",
+        ));
+    }
+    if profile.jobs.is_empty() {
+        return Err(ErrorEnvelope::invalid_body("jobs", "jobs must list at least one job"));
+    }
+
+    let job_ids = id_inventory::job_ids();
+    for (index, job) in profile.jobs.iter().enumerate() {
+        if !job_ids.contains(&job.job_id) {
+            return Err(ErrorEnvelope::invalid_body(
+                format!("jobs[{index}].job_id"),
+                format!("jobs[{index}].job_id must be a known job id"),
+            ));
+        }
+        if profile.jobs[..index].iter().any(|other| other.job_id == job.job_id) {
+            return Err(ErrorEnvelope::invalid_body(
+                format!("jobs[{index}].job_id"),
+                format!("jobs[{index}].job_id is listed more than once"),
+            ));
+        }
+    }
+
+    Ok(profile)
+}
+
+/// Every listing that matches `query`, joinable ones first, each in `sort` order.
+pub fn eligibility_response<'a>(
+    query: ListingsQuery,
+    profile: &CharacterProfile,
+    documents: impl IntoIterator<Item = &'a QueriedListing>,
+) -> ListingEligibilityResponse {
+    let (eligible, excluded): (Vec<_>, Vec<_>) = sorted_matching_listings(&query, documents)
+        .into_iter()
+        .map(|(document, summary)| {
+            let (eligible_job_ids, exclusions) = check_listing(&document.listing, profile);
+            ListingEligibility {
+                summary,
+                eligible: listing_is_joinable(&eligible_job_ids, &exclusions),
+                eligible_job_ids,
+                exclusions,
+            }
+        })
+        .partition(|listing| listing.eligible);
+
+    paginated_collection_response(query, eligible.into_iter().chain(excluded).collect())
+}
+
+/// A listing is joinable when nothing rules out the whole listing and at least one job fits.
+fn listing_is_joinable(eligible_job_ids: &[u32], exclusions: &[Exclusion]) -> bool {
+    !eligible_job_ids.is_empty() && exclusions.iter().all(|exclusion| exclusion.job_id.is_some())
+}
+
+/// Returns the profile jobs that can take a seat, and every reason something cannot: listing-wide
+/// ones first, then per job in profile order.
+pub fn check_listing(
+    listing: &PartyFinderListing,
+    profile: &CharacterProfile,
+) -> (Vec<u32>, Vec<Exclusion>) {
+    let mut exclusions = Vec::new();
+    let exclude = |code, job_id, message: &str| Exclusion {
+        code,
+        job_id,
+        message: message.to_owned(),
+    };
+
+    let home = crate::ffxiv::WORLDS.get(&profile.home_world_id);
+    let created = crate::ffxiv::WORLDS.get(&u32::from(listing.created_world));
+    if listing.search_area.contains(SearchAreaFlags::DATA_CENTRE) {
+        let same_data_center = home
+            .zip(created)
+            .is_some_and(|(home, created)| home.data_center() == created.data_center());
+        if !same_data_center {
+            exclusions.push(exclude(
+                ExclusionCode::OtherDataCenter,
+                None,
+                "the listing is only shown on another data center",
+            ));
+        }
+    } else if profile.home_world_id != u32::from(listing.created_world) {
+        exclusions.push(exclude(
+            ExclusionCode::OtherWorld,
+            None,
+            "the listing is only shown on the world it was created on",
+        ));
+    }
+
+    if listing.conditions.contains(ConditionFlags::DUTY_COMPLETE) && !profile.duty_cleared {
+        exclusions.push(exclude(
+            ExclusionCode::DutyClearRequired,
+            None,
+            "the party only accepts players who have cleared the duty",
+        ));
+    }
+    if listing.conditions.contains(ConditionFlags::DUTY_INCOMPLETE) && profile.duty_cleared {
+        exclusions.push(exclude(
+            ExclusionCode::DutyNotClearedRequired,
+            None,
+            "the party only accepts players who have not cleared the duty",
+        ));
+    }
+    if listing
+        .conditions
+        .contains(ConditionFlags::DUTY_COMPLETE_WEEKLY_REWARD_UNCLAIMED)
+        && (!profile.duty_cleared || profile.weekly_reward_claimed)
+    {
+        exclusions.push(exclude(
+            ExclusionCode::WeeklyRewardUnclaimedRequired,
+            None,
+            "the party only accepts players who cleared the duty and have not claimed this week's reward",
+        ));
+    }
+
+    let slots = project_slots(listing);
+    let one_player_per_job = listing.search_area.contains(SearchAreaFlags::ONE_PLAYER_PER_JOB);
+    let mut eligible_job_ids = Vec::new();
+    for job in &profile.jobs {
+        let job_id = Some(job.job_id);
+        let before = exclusions.len();
+
+        if job.item_level < listing.min_item_level {
+            exclusions.push(exclude(
+                ExclusionCode::ItemLevelTooLow,
+                job_id,
+                "the job's item level is below the listing's minimum",
+            ));
+        }
+        if one_player_per_job && slots.iter().any(|slot| slot.filled_job_id == job_id) {
+            exclusions.push(exclude(
+                ExclusionCode::JobAlreadyInParty,
+                job_id,
+                "the party allows one player per job and already has this job",
+            ));
+        }
+        let open_slot = slots
+            .iter()
+            .any(|slot| !slot.filled && slot.accepted_job_ids.contains(&job.job_id));
+        if !open_slot {
+            exclusions.push(exclude(
+                ExclusionCode::NoOpenSlotForJob,
+                job_id,
+                "no open slot accepts the job",
+            ));
+        }
+
+        if exclusions.len() == before {
+            eligible_job_ids.push(job.job_id);
+        }
+    }
+
+    (eligible_job_ids, exclusions)
+}
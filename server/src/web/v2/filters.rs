@@ -16,9 +16,23 @@ const SUPPORTED_QUERY_FIELDS: &[&str] = &[
     "datacenter",
     "region",
     "category_id",
+    "category_ids",
     "duty_id",
     "job_ids",
+    "min_item_level_gte",
+    "min_item_level_lte",
+    "duty_type_id",
+    "objective_ids",
+    "condition_ids",
+    "loot_rule_id",
+    "beginners_welcome",
+    "one_player_per_job",
+    "is_cross_world",
+    "slots_open_gte",
+    "open_role_ids",
+    "open_job_ids",
     "search",
+    "sort",
 ];
 
 const LEGACY_LABEL_FIELDS: &[(&str, &str)] = &[
@@ -43,9 +57,80 @@ pub struct ListingsQuery {
     pub datacenter: Option<String>,
     pub region: Option<String>,
     pub category_id: Option<u32>,
+    pub category_ids: Vec<u32>,
     pub duty_id: Option<u32>,
     pub job_ids: Vec<u32>,
+    pub min_item_level_gte: Option<u16>,
+    pub min_item_level_lte: Option<u16>,
+    pub duty_type_id: Option<u32>,
+    pub objective_ids: Vec<u32>,
+    pub condition_ids: Vec<u32>,
+    pub loot_rule_id: Option<u32>,
+    pub beginners_welcome: Option<bool>,
+    pub one_player_per_job: Option<bool>,
+    pub is_cross_world: Option<bool>,
+    pub slots_open_gte: Option<u32>,
+    pub open_role_ids: Vec<u32>,
+    pub open_job_ids: Vec<u32>,
     pub search: Option<String>,
+    /// Empty means [`DEFAULT_SORT`]. The listing id tie-breaker is never listed here.
+    pub sort: Vec<SortField>,
+}
+
+/// A collection sort key; each is a summary field of the same name.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    UpdatedAt,
+    TimeLeftSeconds,
+    MinItemLevel,
+    SlotsFilled,
+    SlotsAvailable,
+    CategoryId,
+}
+
+impl SortKey {
+    pub const ALL: [Self; 6] = [
+        Self::UpdatedAt,
+        Self::TimeLeftSeconds,
+        Self::MinItemLevel,
+        Self::SlotsFilled,
+        Self::SlotsAvailable,
+        Self::CategoryId,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UpdatedAt => "updated_at",
+            Self::TimeLeftSeconds => "time_left_seconds",
+            Self::MinItemLevel => "min_item_level",
+            Self::SlotsFilled => "slots_filled",
+            Self::SlotsAvailable => "slots_available",
+            Self::CategoryId => "category_id",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SortField {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+/// `-updated_at`: the most recently refreshed listings first.
+pub const DEFAULT_SORT: &[SortField] = &[SortField {
+    key: SortKey::UpdatedAt,
+    descending: true,
+}];
+
+impl ListingsQuery {
+    pub fn sort_fields(&self) -> &[SortField] {
+        if self.sort.is_empty() {
+            DEFAULT_SORT
+        } else {
+            &self.sort
+        }
+    }
 }
 
 impl Default for ListingsQuery {
@@ -58,9 +143,23 @@ impl Default for ListingsQuery {
             datacenter: None,
             region: None,
             category_id: None,
+            category_ids: Vec::new(),
             duty_id: None,
             job_ids: Vec::new(),
+            min_item_level_gte: None,
+            min_item_level_lte: None,
+            duty_type_id: None,
+            objective_ids: Vec::new(),
+            condition_ids: Vec::new(),
+            loot_rule_id: None,
+            beginners_welcome: None,
+            one_player_per_job: None,
+            is_cross_world: None,
+            slots_open_gte: None,
+            open_role_ids: Vec::new(),
+            open_job_ids: Vec::new(),
             search: None,
+            sort: Vec::new(),
         }
     }
 }
@@ -104,18 +203,36 @@ pub fn parse_listings_query(
     query.region = parse_csv_names(params, "region")?;
 
     query.category_id = parse_optional_u32(params, "category_id")?;
+    query.category_ids = parse_csv_u32s(params, "category_ids")?;
     query.duty_id = parse_optional_u32(params, "duty_id")?;
 
     if let Some(value) = params.get("job_ids") {
         query.job_ids = parse_job_ids(value)?;
     }
 
+    query.min_item_level_gte = parse_optional_u16(params, "min_item_level_gte")?;
+    query.min_item_level_lte = parse_optional_u16(params, "min_item_level_lte")?;
+    query.duty_type_id = parse_optional_u32(params, "duty_type_id")?;
+    query.objective_ids = parse_csv_u32s(params, "objective_ids")?;
+    query.condition_ids = parse_csv_u32s(params, "condition_ids")?;
+    query.loot_rule_id = parse_optional_u32(params, "loot_rule_id")?;
+    query.beginners_welcome = parse_optional_bool(params, "beginners_welcome")?;
+    query.one_player_per_job = parse_optional_bool(params, "one_player_per_job")?;
+    query.is_cross_world = parse_optional_bool(params, "is_cross_world")?;
+    query.slots_open_gte = parse_optional_u32(params, "slots_open_gte")?;
+    query.open_role_ids = parse_csv_u32s(params, "open_role_ids")?;
+    query.open_job_ids = parse_csv_u32s(params, "open_job_ids")?;
+
     if let Some(value) = params.get("search") {
         if !value.is_empty() {
             query.search = Some(value.clone());
         }
     }
 
+    if let Some(value) = params.get("sort") {
+        query.sort = parse_sort(value)?;
+    }
+
     Ok(query)
 }
 
@@ -169,11 +286,74 @@ fn parse_optional_u32(
         .transpose()
 }
 
+fn parse_optional_u16(
+    params: &HashMap<String, String>,
+    field: &'static str,
+) -> Result<Option<u16>, ErrorEnvelope> {
+    params
+        .get(field)
+        .map(|value| {
+            value.parse::<u16>().map_err(|_| {
+                ErrorEnvelope::invalid_query(
+                    field,
+                    format!("{field} must be an integer between 0 and {}", u16::MAX),
+                )
+            })
+        })
+        .transpose()
+}
+
+fn parse_optional_bool(
+    params: &HashMap<String, String>,
+    field: &'static str,
+) -> Result<Option<bool>, ErrorEnvelope> {
+    params
+        .get(field)
+        .map(|value| match value.as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(ErrorEnvelope::invalid_query(
+                field,
+                format!("{field} must be true or false"),
+            )),
+        })
+        .transpose()
+}
+
+fn parse_sort(value: &str) -> Result<Vec<SortField>, ErrorEnvelope> {
+    let invalid = || {
+        let names = SortKey::ALL.map(SortKey::name).join(", ");
+        ErrorEnvelope::invalid_query(
+            "sort",
+            format!("sort must be a comma-separated list of distinct fields from {names}, each optionally prefixed with -"),
+        )
+    };
+
+    let mut fields: Vec<SortField> = Vec::new();
+    for segment in value.split(',').map(str::trim) {
+        let (descending, name) = match segment.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, segment),
+        };
+        let key = SortKey::ALL
+            .into_iter()
+            .find(|key| key.name() == name)
+            .ok_or_else(invalid)?;
+        if fields.iter().any(|field| field.key == key) {
+            return Err(invalid());
+        }
+
+        fields.push(SortField { key, descending });
+    }
+
+    Ok(fields)
+}
+
 fn parse_job_ids(value: &str) -> Result<Vec<u32>, ErrorEnvelope> {
     parse_csv_u32s_impl(value, "job_ids")
 }
 
-fn parse_csv_u32s(
+pub(super) fn parse_csv_u32s(
     params: &HashMap<String, String>,
     field: &'static str,
 ) -> Result<Vec<u32>, ErrorEnvelope> {
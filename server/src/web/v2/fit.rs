@@ -0,0 +1,260 @@
+use std::{collections::HashMap, convert::Infallible, sync::Arc};
+
+use warp::{filters::BoxedFilter, reply::Response, Filter, Reply};
+
+use crate::{
+    listing::{PartyFinderListing, SearchAreaFlags},
+    listing_container::QueriedListing,
+    web::State,
+};
+
+use super::{
+    contracts::{ErrorEnvelope, ListingFit, ListingFitResponse, SlotAssignment},
+    filters::{parse_csv_u32s, parse_listings_query, ListingsQuery},
+    id_inventory,
+    listings::{
+        collection_filter, empty_collection_response, internal_error_reply, invalid_query_reply,
+        paginated_collection_response, project_slots, query_demands_empty_collection,
+        sorted_matching_listings,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitParams {
+    /// One job id per player; a job may appear more than once.
+    pub party_job_ids: Vec<u32>,
+    pub listings: ListingsQuery,
+}
+
+/// Must be mounted before the listing member route, which would take `fit` as an id.
+pub fn route(state: Arc<State>) -> BoxedFilter<(Response,)> {
+    warp::path!("api" / "v2" / "listings" / "fit")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(
+            warp::query::<HashMap<String, String>>()
+                .or(warp::any().map(HashMap::new))
+                .unify(),
+        )
+        .and(warp::any().map(move || state.clone()))
+        .and_then(fit)
+        .boxed()
+}
+
+async fn fit(query: HashMap<String, String>, state: Arc<State>) -> Result<Response, Infallible> {
+    let params = match parse_fit_query(query) {
+        Ok(params) => params,
+        Err(error) => return Ok(invalid_query_reply(error).into_response()),
+    };
+
+    if fit_demands_empty_collection(&params) {
+        let response = empty_collection_response::<ListingFit>(&params.listings);
+        return Ok(warp::reply::json(&response).into_response());
+    }
+
+    // every player needs an open slot for their job, which the store can check on its own
+    let mut filter = collection_filter(&params.listings);
+    filter.open_slot_bits.extend(
+        params
+            .party_job_ids
+            .iter()
+            .filter_map(|job_id| id_inventory::accepted_job_flag_bits(*job_id))
+            .map(|bits| vec![bits]),
+    );
+    let party_size = params.party_job_ids.len() as u32;
+    filter.slots_open_gte = Some(filter.slots_open_gte.unwrap_or_default().max(party_size));
+
+    match state.store().active_listings(&filter).await {
+        Ok(documents) => Ok(warp::reply::json(&fit_response(params, &documents)).into_response()),
+        Err(error) => {
+            eprintln!("{error:#?}");
+            Ok(internal_error_reply().into_response())
+        }
+    }
+}
+
+pub fn parse_fit_query(mut params: HashMap<String, String>) -> Result<FitParams, ErrorEnvelope> {
+    let party_job_ids = parse_csv_u32s(&params, "party_job_ids")?;
+    if party_job_ids.is_empty() {
+        return Err(ErrorEnvelope::invalid_query(
+            "party_job_ids",
+            "party_job_ids is required",
+        ));
+    }
+    params.remove("party_job_ids");
+
+    Ok(FitParams {
+        party_job_ids,
+        listings: parse_listings_query(&params)?,
+    })
+}
+
+fn fit_demands_empty_collection(params: &FitParams) -> bool {
+    let job_ids = id_inventory::job_ids();
+    query_demands_empty_collection(&params.listings)
+        || params
+            .party_job_ids
+            .iter()
+            .any(|job_id| !job_ids.contains(job_id))
+}
+
+pub fn fit_response<'a>(
+    params: FitParams,
+    documents: impl IntoIterator<Item = &'a QueriedListing>,
+) -> ListingFitResponse {
+    let fits = sorted_matching_listings(&params.listings, documents)
+        .into_iter()
+        .filter_map(|(document, summary)| {
+            let slots = seat_party(&document.listing, &params.party_job_ids)?;
+            let assignments = params
+                .party_job_ids
+                .iter()
+                .zip(slots)
+                .map(|(job_id, slot_index)| SlotAssignment {
+                    job_id: *job_id,
+                    slot_index,
+                })
+                .collect();
+            Some(ListingFit {
+                summary,
+                assignments,
+            })
+        })
+        .collect();
+
+    paginated_collection_response(params.listings, fits)
+}
+
+/// Seats every player in a distinct open slot that accepts their job, returning each player's
+/// slot index in party order, or `None` when no such seating exists. On one-player-per-job
+/// listings no job may be taken twice, counting the members already in the party.
+pub fn seat_party(listing: &PartyFinderListing, party_job_ids: &[u32]) -> Option<Vec<usize>> {
+    let slots = project_slots(listing);
+
+    if listing.search_area.contains(SearchAreaFlags::ONE_PLAYER_PER_JOB) {
+        let mut taken = slots
+            .iter()
+            .filter_map(|slot| slot.filled_job_id)
+            .collect::<Vec<_>>();
+        for job_id in party_job_ids {
+            if taken.contains(job_id) {
+                return None;
+            }
+            taken.push(*job_id);
+        }
+    }
+
+    // candidate open slots per player
+    let candidates = party_job_ids
+        .iter()
+        .map(|job_id| {
+            slots
+                .iter()
+                .enumerate()
+                .filter(|(_, slot)| !slot.filled && slot.accepted_job_ids.contains(job_id))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // Kuhn's augmenting paths; parties and listings are small enough that this is instant
+    let mut slot_owner = vec![None; slots.len()];
+    for player in 0..party_job_ids.len() {
+        let mut visited = vec![false; slots.len()];
+        if !augment(player, &candidates, &mut slot_owner, &mut visited) {
+            return None;
+        }
+    }
+
+    let mut seats = vec![0; party_job_ids.len()];
+    for (slot_index, owner) in slot_owner.into_iter().enumerate() {
+        if let Some(player) = owner {
+            seats[player] = slot_index;
+        }
+    }
+    Some(seats)
+}
+
+fn augment(
+    player: usize,
+    candidates: &[Vec<usize>],
+    slot_owner: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    // a free seat first, so earlier players are only moved when they have to be
+    let free = candidates[player]
+        .iter()
+        .find(|slot| !visited[**slot] && slot_owner[**slot].is_none());
+    if let Some(&slot) = free {
+        slot_owner[slot] = Some(player);
+        return true;
+    }
+
+    for &slot in &candidates[player] {
+        if visited[slot] {
+            continue;
+        }
+        visited[slot] = true;
+
+        let owner = slot_owner[slot];
+        if owner.is_none_or(|other| augment(other, candidates, slot_owner, visited)) {
+            slot_owner[slot] = Some(player);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::listing::{JobFlags, PartyFinderSlot};
+
+    const PALADIN: u32 = 19;
+    const WARRIOR: u32 = 21;
+    const WHITE_MAGE: u32 = 24;
+    const SCHOLAR: u32 = 28;
+
+    fn listing(slots: &[JobFlags], jobs_present: &[u8]) -> PartyFinderListing {
+        PartyFinderListing {
+            slots_available: slots.len() as u8,
+            slots: slots.iter().map(|&accepting| PartyFinderSlot { accepting }).collect(),
+            jobs_present: jobs_present.to_vec(),
+            ..crate::test::listing()
+        }
+    }
+
+    #[test]
+    fn reassigns_earlier_players_to_seat_everyone() {
+        let tank_or_healer = JobFlags::PALADIN | JobFlags::WHITE_MAGE;
+        let listing = listing(&[tank_or_healer, JobFlags::PALADIN], &[0, 0]);
+
+        // the paladin would greedily take slot 0, leaving nothing for the white mage
+        assert_eq!(seat_party(&listing, &[PALADIN, WHITE_MAGE]), Some(vec![1, 0]));
+        assert_eq!(seat_party(&listing, &[WHITE_MAGE, PALADIN]), Some(vec![0, 1]));
+        assert_eq!(seat_party(&listing, &[WHITE_MAGE, SCHOLAR]), None);
+    }
+
+    #[test]
+    fn filled_slots_are_not_offered() {
+        let healer = JobFlags::WHITE_MAGE | JobFlags::SCHOLAR;
+        let listing = listing(&[healer, healer, JobFlags::WARRIOR], &[24, 0, 0]);
+
+        assert_eq!(seat_party(&listing, &[SCHOLAR, WARRIOR]), Some(vec![1, 2]));
+        assert_eq!(seat_party(&listing, &[SCHOLAR, WHITE_MAGE]), None);
+    }
+
+    #[test]
+    fn one_player_per_job_rejects_repeated_and_present_jobs() {
+        let healer = JobFlags::WHITE_MAGE | JobFlags::SCHOLAR;
+        let mut listing = listing(&[healer, healer, healer], &[24, 0, 0]);
+
+        assert_eq!(seat_party(&listing, &[WHITE_MAGE, SCHOLAR]), Some(vec![1, 2]));
+        assert_eq!(seat_party(&listing, &[SCHOLAR, SCHOLAR]), Some(vec![1, 2]));
+
+        listing.search_area |= SearchAreaFlags::ONE_PLAYER_PER_JOB;
+        assert_eq!(seat_party(&listing, &[WHITE_MAGE, SCHOLAR]), None);
+        assert_eq!(seat_party(&listing, &[SCHOLAR, SCHOLAR]), None);
+        assert_eq!(seat_party(&listing, &[SCHOLAR]), Some(vec![1]));
+    }
+}
@@ -0,0 +1,326 @@
+use std::{collections::HashMap, convert::Infallible, sync::Arc};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use warp::{filters::BoxedFilter, reply::Response, Filter, Reply};
+
+use crate::{
+    store::{HistoryCursor, HistoryQuery, ListingStore},
+    web::State,
+};
+
+use super::{
+    contracts::{
+        CursorEnvelope, CursorPagination, ErrorEnvelope, HistoricalListing, ListingHistoryResponse,
+    },
+    filters::{parse_listings_query, ListingsQuery},
+    listings::{
+        collection_filter, internal_error_reply, invalid_query_reply, project_event_summary,
+        query_demands_empty_collection,
+    },
+};
+
+/// Longest `[from, to)` range a single history query may cover.
+pub const MAX_HISTORY_SPAN: Duration = Duration::days(31);
+/// Rows fetched from the store per round trip while filling a page.
+const HISTORY_BATCH_LEN: usize = 200;
+/// Round trips one request may make. Filters checked only after the fetch, such as `search`,
+/// could otherwise walk the whole range; the page comes back short with a cursor instead.
+const HISTORY_MAX_BATCHES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryParams {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub after: Option<HistoryCursor>,
+    /// The v2 collection filters; `page` is always the default.
+    pub listings: ListingsQuery,
+}
+
+pub fn routes(state: Arc<State>) -> BoxedFilter<(Response,)> {
+    warp::path!("api" / "v2" / "history" / "listings")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(
+            warp::query::<HashMap<String, String>>()
+                .or(warp::any().map(HashMap::new))
+                .unify(),
+        )
+        .and(warp::any().map(move || state.clone()))
+        .and_then(history)
+        .boxed()
+}
+
+async fn history(
+    query: HashMap<String, String>,
+    state: Arc<State>,
+) -> Result<Response, Infallible> {
+    let params = match parse_history_query(query) {
+        Ok(params) => params,
+        Err(error) => return Ok(invalid_query_reply(error).into_response()),
+    };
+
+    match history_response(state.store(), params).await {
+        Ok(response) => Ok(warp::reply::json(&response).into_response()),
+        Err(error) => {
+            eprintln!("{error:#?}");
+            Ok(internal_error_reply().into_response())
+        }
+    }
+}
+
+pub fn parse_history_query(
+    mut params: HashMap<String, String>,
+) -> Result<HistoryParams, ErrorEnvelope> {
+    if params.contains_key("page") {
+        return Err(ErrorEnvelope::invalid_query(
+            "page",
+            "page is not supported for history; use cursor",
+        ));
+    }
+    if params.contains_key("sort") {
+        return Err(ErrorEnvelope::invalid_query(
+            "sort",
+            "sort is not supported for history; rows are newest first",
+        ));
+    }
+
+    let from = parse_timestamp(params.remove("from"), "from")?;
+    let to = parse_timestamp(params.remove("to"), "to")?;
+    if from >= to {
+        return Err(ErrorEnvelope::invalid_query("to", "to must be after from"));
+    }
+    if to - from > MAX_HISTORY_SPAN {
+        return Err(ErrorEnvelope::invalid_query(
+            "to",
+            format!("from and to must be at most {} days apart", MAX_HISTORY_SPAN.num_days()),
+        ));
+    }
+
+    let after = params
+        .remove("cursor")
+        .map(|cursor| {
+            decode_cursor(&cursor)
+                .ok_or_else(|| ErrorEnvelope::invalid_query("cursor", "cursor is malformed"))
+        })
+        .transpose()?;
+
+    Ok(HistoryParams {
+        from,
+        to,
+        after,
+        listings: parse_listings_query(&params)?,
+    })
+}
+
+fn parse_timestamp(value: Option<String>, field: &'static str) -> Result<DateTime<Utc>, ErrorEnvelope> {
+    value
+        .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .ok_or_else(|| {
+            ErrorEnvelope::invalid_query(field, format!("{field} must be an RFC 3339 timestamp"))
+        })
+}
+
+/// Fills one page, re-checking every row against the full query since the store only narrows.
+/// Fetches one match past the page so `next_cursor` is only set when more rows exist.
+pub async fn history_response(
+    store: &dyn ListingStore,
+    params: HistoryParams,
+) -> Result<ListingHistoryResponse> {
+    fill_history_page(store, params, HISTORY_BATCH_LEN, HISTORY_MAX_BATCHES).await
+}
+
+/// When `max_batches` runs out first, the page holds what was found so far and `next_cursor`
+/// points past the last row scanned, so the rows already ruled out are not read again.
+async fn fill_history_page(
+    store: &dyn ListingStore,
+    params: HistoryParams,
+    batch_len: usize,
+    max_batches: usize,
+) -> Result<ListingHistoryResponse> {
+    let per_page = params.listings.per_page;
+    let mut page = Vec::new();
+    let mut next_cursor = None;
+
+    if !query_demands_empty_collection(&params.listings) {
+        let mut query = HistoryQuery {
+            from: params.from,
+            to: params.to,
+            filter: collection_filter(&params.listings),
+            after: params.after,
+            limit: batch_len.max(per_page + 1),
+        };
+        query.filter.latest_per_player = false;
+
+        let mut last_in_page = None;
+        'fill: for batch in 1..=max_batches {
+            let documents = store.history(&query).await?;
+            for document in &documents {
+                let Some(summary) = project_event_summary(document, &params.listings) else {
+                    continue;
+                };
+                if page.len() == per_page {
+                    next_cursor = last_in_page;
+                    break 'fill;
+                }
+
+                last_in_page = Some(HistoryCursor::of(document));
+                page.push(HistoricalListing {
+                    summary,
+                    created_at: document.created_at.to_rfc3339(),
+                });
+            }
+
+            if documents.len() < query.limit {
+                break;
+            }
+            query.after = documents.last().map(HistoryCursor::of);
+            if batch == max_batches {
+                next_cursor = query.after.take();
+            }
+        }
+    }
+
+    Ok(CursorEnvelope {
+        data: page,
+        pagination: CursorPagination {
+            per_page,
+            next_cursor: next_cursor.map(|cursor| encode_cursor(&cursor)),
+        },
+    })
+}
+
+/// `updated_at` nanoseconds and the row identity, dot-separated and URL-safe base64 encoded.
+pub fn encode_cursor(cursor: &HistoryCursor) -> String {
+    let (id, last_server_restart, created_world) = cursor.identity;
+    let nanos = cursor.updated_at.timestamp_nanos_opt().unwrap_or_default();
+
+    base64::encode_config(
+        format!("{nanos}.{id}.{last_server_restart}.{created_world}"),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+pub fn decode_cursor(cursor: &str) -> Option<HistoryCursor> {
+    let bytes = base64::decode_config(cursor, base64::URL_SAFE_NO_PAD).ok()?;
+    let text = String::from_utf8(bytes).ok()?;
+    let mut parts = text.split('.');
+    let nanos = parts.next()?.parse().ok()?;
+    let id = parts.next()?.parse().ok()?;
+    let last_server_restart = parts.next()?.parse().ok()?;
+    let created_world = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(HistoryCursor {
+        updated_at: DateTime::from_timestamp_nanos(nanos),
+        identity: (id, last_server_restart, created_world),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn capped_scans_return_a_short_page_that_resumes_past_the_scanned_rows() {
+        use crate::{listing::PartyFinderListing, store::MemoryStore};
+
+        let store = MemoryStore::new();
+        let uploaded_at = Utc::now() - Duration::minutes(10);
+        // newest first: four rows the search rules out, then the one it finds
+        for (offset, description) in ["wanted", "other", "other", "other", "other"].into_iter().enumerate() {
+            let mut listing: PartyFinderListing =
+                serde_json::from_str(&crate::test::listing_json_with_id(1000 + offset as u64)).unwrap();
+            listing.content_id_lower += offset as u32;
+            listing.description = sestring::SeString::parse(description.as_bytes()).unwrap();
+            store.upsert(&listing, None, uploaded_at + Duration::seconds(offset as i64)).await.unwrap();
+        }
+        let params = |cursor: Option<String>| {
+            let mut pairs = vec![
+                ("from".to_owned(), (uploaded_at - Duration::hours(1)).to_rfc3339()),
+                ("to".to_owned(), (uploaded_at + Duration::hours(1)).to_rfc3339()),
+                ("search".to_owned(), "wanted".to_owned()),
+                ("per_page".to_owned(), "1".to_owned()),
+            ];
+            pairs.extend(cursor.map(|cursor| ("cursor".to_owned(), cursor)));
+            parse_history_query(pairs.into_iter().collect()).unwrap()
+        };
+
+        let first = fill_history_page(&store, params(None), 2, 1).await.unwrap();
+        assert!(first.data.is_empty());
+        let cursor = first.pagination.next_cursor.expect("a capped scan leaves a cursor");
+        assert_eq!(decode_cursor(&cursor).unwrap().identity.0, 1003);
+
+        let second = fill_history_page(&store, params(Some(cursor)), 2, 1).await.unwrap();
+        assert!(second.data.is_empty());
+        let third = fill_history_page(&store, params(second.pagination.next_cursor), 2, 1).await.unwrap();
+        assert_eq!(third.data.len(), 1);
+        assert_eq!(third.data[0].summary.id, "1000");
+        assert_eq!(third.pagination.next_cursor, None);
+    }
+
+    #[test]
+    fn cursor_round_trips_and_rejects_garbage() {
+        let cursor = HistoryCursor {
+            updated_at: Utc.with_ymd_and_hms(2026, 5, 5, 21, 0, 0).unwrap()
+                + Duration::nanoseconds(123_456_789),
+            identity: (u64::MAX, -42, 1042),
+        };
+
+        assert_eq!(decode_cursor(&encode_cursor(&cursor)), Some(cursor));
+        assert_eq!(decode_cursor("not base64!"), None);
+        assert_eq!(decode_cursor(&base64::encode_config("1.2.3", base64::URL_SAFE_NO_PAD)), None);
+        assert_eq!(decode_cursor(&base64::encode_config("1.2.3.4.5", base64::URL_SAFE_NO_PAD)), None);
+    }
+
+    #[test]
+    fn history_query_requires_a_bounded_range() {
+        let field = |pairs: &[(&str, &str)]| {
+            parse_history_query(params(pairs)).unwrap_err().error.details["field"].clone()
+        };
+
+        assert_eq!(field(&[("to", "2026-05-05T00:00:00Z")]), "from");
+        assert_eq!(field(&[("from", "yesterday"), ("to", "2026-05-05T00:00:00Z")]), "from");
+        assert_eq!(field(&[("from", "2026-05-05T00:00:00Z"), ("to", "2026-05-05T00:00:00Z")]), "to");
+        assert_eq!(field(&[("from", "2026-04-01T00:00:00Z"), ("to", "2026-05-05T00:00:00Z")]), "to");
+        assert_eq!(
+            field(&[("from", "2026-05-04T00:00:00Z"), ("to", "2026-05-05T00:00:00Z"), ("page", "2")]),
+            "page",
+        );
+        assert_eq!(
+            field(&[("from", "2026-05-04T00:00:00Z"), ("to", "2026-05-05T00:00:00Z"), ("sort", "updated_at")]),
+            "sort",
+        );
+        assert_eq!(
+            field(&[("from", "2026-05-04T00:00:00Z"), ("to", "2026-05-05T00:00:00Z"), ("cursor", "x")]),
+            "cursor",
+        );
+        assert_eq!(
+            field(&[("from", "2026-05-04T00:00:00Z"), ("to", "2026-05-05T00:00:00Z"), ("world", "73")]),
+            "world",
+        );
+
+        let parsed = parse_history_query(params(&[
+            ("from", "2026-05-04T08:00:00+08:00"),
+            ("to", "2026-05-05T00:00:00Z"),
+            ("per_page", "5"),
+            ("duty_id", "55"),
+        ]))
+        .unwrap();
+        assert_eq!(parsed.from, Utc.with_ymd_and_hms(2026, 5, 4, 0, 0, 0).unwrap());
+        assert_eq!(parsed.listings.per_page, 5);
+        assert_eq!(parsed.listings.duty_id, Some(55));
+        assert_eq!(parsed.after, None);
+    }
+}
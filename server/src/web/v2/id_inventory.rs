@@ -92,6 +92,13 @@ pub fn role_id_for_job_id(job_id: u32) -> Option<u32> {
     ffxiv::JOBS.get(&job_id)?.role().map(role_id)
 }
 
+pub fn job_ids_for_role_id(role_id: u32) -> Vec<u32> {
+    job_ids()
+        .into_iter()
+        .filter(|job_id| role_id_for_job_id(*job_id) == Some(role_id))
+        .collect()
+}
+
 pub fn role_id_for_job_ids(job_ids: &[u32]) -> Option<u32> {
     let mut role_ids = job_ids
         .iter()
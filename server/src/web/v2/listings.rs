@@ -1,15 +1,15 @@
-use std::{collections::HashMap, convert::Infallible, sync::Arc};
+use std::{cmp::Ordering, collections::HashMap, convert::Infallible, sync::Arc};
 
-use chrono::{Duration, Utc};
-use mongodb::bson::{doc, to_bson, Document};
+use chrono::Utc;
 use serde_json::{Map, Value};
-use tokio_stream::StreamExt;
 use warp::{filters::BoxedFilter, http::StatusCode, reply::Response, Filter, Reply};
 
 use crate::{
     listing::{PartyFinderListing, SearchAreaFlags},
     listing_container::QueriedListing,
+    search::SearchQuery,
     sestring_ext::SeStringExt,
+    store::{ListingFilter, ACTIVE_UPDATE_WINDOW},
     web::State,
 };
 
@@ -18,15 +18,18 @@ use super::{
         CollectionEnvelope, ErrorEnvelope, ListingCollectionResponse, ListingDetail,
         ListingMemberResponse, ListingSlot, ListingSummary, Pagination,
     },
-    filters::{parse_listings_query, ListingsQuery},
+    filters::{parse_listings_query, ListingsQuery, SortField, SortKey},
     id_inventory,
 };
 
-const ACTIVE_UPDATE_WINDOW: Duration = Duration::minutes(5);
-const RECENT_LISTING_WINDOW: Duration = Duration::hours(2);
-
 pub fn routes(state: Arc<State>) -> BoxedFilter<(Response,)> {
     collection_route(Arc::clone(&state))
+        .or(super::stream::route(Arc::clone(&state.listing_events)))
+        .unify()
+        .or(super::fit::route(Arc::clone(&state)))
+        .unify()
+        .or(super::eligible::route(Arc::clone(&state)))
+        .unify()
         .or(member_route(state))
         .unify()
         .boxed()
@@ -71,7 +74,7 @@ pub(crate) fn collection_route_for_tests() -> BoxedFilter<(Response,)> {
 #[cfg(test)]
 pub(crate) fn collection_response_from_raw_documents_for_tests(
     query: ListingsQuery,
-    documents: Vec<Document>,
+    documents: Vec<mongodb::bson::Document>,
 ) -> Response {
     collection_response_from_raw_documents(query, documents)
 }
@@ -143,38 +146,8 @@ async fn member_from_documents(
 }
 
 async fn member_response(id: u64, state: Arc<State>) -> Response {
-    let pipeline = match member_pipeline(id) {
-        Ok(pipeline) => pipeline,
-        Err(error) => {
-            eprintln!("{error:#?}");
-            return internal_error_reply().into_response();
-        }
-    };
-
-    let aggregation = state.collection().aggregate(pipeline, None).await;
-
-    match aggregation {
-        Ok(mut cursor) => {
-            let mut documents = Vec::new();
-
-            while let Some(result) = cursor.next().await {
-                match result {
-                    Ok(document) => match mongodb::bson::from_document::<QueriedListing>(document) {
-                        Ok(document) => documents.push(document),
-                        Err(error) => {
-                            eprintln!("{error:#?}");
-                            return internal_error_reply().into_response();
-                        }
-                    },
-                    Err(error) => {
-                        eprintln!("{error:#?}");
-                        return internal_error_reply().into_response();
-                    }
-                }
-            }
-
-            member_response_from_documents(id, documents.iter())
-        }
+    match state.store().listings_by_id(id).await {
+        Ok(documents) => member_response_from_documents(id, documents.iter()),
         Err(error) => {
             eprintln!("{error:#?}");
             internal_error_reply().into_response()
@@ -182,53 +155,6 @@ async fn member_response(id: u64, state: Arc<State>) -> Response {
     }
 }
 
-fn member_pipeline(id: u64) -> mongodb::bson::ser::Result<Vec<Document>> {
-    let listing_id = to_bson(&id)?;
-
-    Ok(vec![
-        doc! {
-            "$match": {
-                "updated_at": { "$gte": Utc::now() - RECENT_LISTING_WINDOW },
-                "listing.id": listing_id,
-                "listing.search_area": { "$bitsAllClear": SearchAreaFlags::PRIVATE.bits() as i32 },
-            }
-        },
-        doc! {
-            "$set": {
-                "time_left": {
-                    "$divide": [
-                        {
-                            "$subtract": [
-                                { "$multiply": ["$listing.seconds_remaining", 1000] },
-                                { "$subtract": ["$$NOW", "$updated_at"] },
-                            ]
-                        },
-                        1000,
-                    ]
-                },
-                "minutes_since_update": {
-                    "$divide": [
-                        { "$subtract": ["$$NOW", "$updated_at"] },
-                        60000,
-                    ]
-                },
-                "updated_minute": {
-                    "$dateTrunc": {
-                        "date": "$updated_at",
-                        "unit": "minute",
-                        "binSize": 5,
-                    }
-                },
-            }
-        },
-        doc! {
-            "$sort": {
-                "updated_at": -1,
-            }
-        },
-    ])
-}
-
 pub(crate) fn member_response_from_documents<'a>(
     id: u64,
     documents: impl IntoIterator<Item = &'a QueriedListing>,
@@ -253,36 +179,17 @@ fn not_found_reply(id: u64) -> impl Reply {
     )
 }
 
-fn invalid_query_reply(error: ErrorEnvelope) -> impl Reply {
+pub(crate) fn invalid_query_reply(error: ErrorEnvelope) -> impl Reply {
     warp::reply::with_status(warp::reply::json(&error), StatusCode::BAD_REQUEST)
 }
 
 async fn collection_response(query: ListingsQuery, state: Arc<State>) -> Response {
     if query_demands_empty_collection(&query) {
-        return warp::reply::json(&empty_collection_response(&query)).into_response();
+        return warp::reply::json(&empty_collection_response::<ListingSummary>(&query)).into_response();
     }
 
-    let aggregation = state.collection().aggregate(collection_pipeline(&query), None).await;
-
-    match aggregation {
-        Ok(mut cursor) => {
-            let mut documents = Vec::new();
-
-            while let Some(result) = cursor.next().await {
-                match result {
-                    Ok(document) => match decode_queried_listing(document) {
-                        Ok(document) => documents.push(document),
-                        Err(()) => return internal_error_reply().into_response(),
-                    },
-                    Err(error) => {
-                        eprintln!("{error:#?}");
-                        return internal_error_reply().into_response();
-                    }
-                }
-            }
-
-            collection_response_from_decoded_documents(query, documents)
-        }
+    match state.store().active_listings(&collection_filter(&query)).await {
+        Ok(documents) => collection_response_from_decoded_documents(query, documents),
         Err(error) => {
             eprintln!("{error:#?}");
             internal_error_reply().into_response()
@@ -290,7 +197,24 @@ async fn collection_response(query: ListingsQuery, state: Arc<State>) -> Respons
     }
 }
 
-fn internal_error_reply() -> impl Reply {
+/// The visible listings `query` matches, in `sort` order and not paged, for routes that filter
+/// like `GET /api/v2/listings` but render something else.
+pub(crate) async fn matching_listings(
+    state: &State,
+    query: &ListingsQuery,
+) -> anyhow::Result<Vec<QueriedListing>> {
+    if query_demands_empty_collection(query) {
+        return Ok(Vec::new());
+    }
+
+    let documents = state.store().active_listings(&collection_filter(query)).await?;
+    Ok(sorted_matching_listings(query, &documents)
+        .into_iter()
+        .map(|(document, _)| document.clone())
+        .collect())
+}
+
+pub(super) fn internal_error_reply() -> impl Reply {
     warp::reply::with_status(
         warp::reply::json(&ErrorEnvelope::new(
             "internal_error",
@@ -301,210 +225,173 @@ fn internal_error_reply() -> impl Reply {
     )
 }
 
-pub(crate) fn collection_pipeline(query: &ListingsQuery) -> Vec<Document> {
-    let mut pipeline = vec![doc! {
-        "$match": {
-            "updated_at": { "$gte": Utc::now() - RECENT_LISTING_WINDOW },
-            "listing.search_area": { "$bitsAllClear": SearchAreaFlags::PRIVATE.bits() as i32 },
-        }
-    }];
+/// Store-side pushdown for a collection query. `matches_query` re-checks every filter on the
+/// returned documents, so this only has to narrow, never to be exact.
+pub(crate) fn collection_filter(query: &ListingsQuery) -> ListingFilter {
+    let mut filter = ListingFilter {
+        latest_per_player: true,
+        ..Default::default()
+    };
 
     // Precedence: world-id > datacenter > region
     // If a higher-priority filter is active and well-formed, lower-priority filters are masked
 
-    let world_id_active = world_id_filter_is_active(query);
-    let datacenter_active = datacenter_filter_is_active(query);
-
     // World-id filters (highest priority)
-    if !query.created_world_id.is_empty() {
-        let valid_created_world_ids: Vec<i32> = query
-            .created_world_id
-            .iter()
-            .filter(|id| id_inventory::world_ids().contains(id))
-            .map(|id| *id as i32)
-            .collect();
-        if !valid_created_world_ids.is_empty() {
-            pipeline.push(doc! {
-                "$match": {
-                    "listing.created_world": { "$in": valid_created_world_ids },
-                }
-            });
-        }
+    filter.created_world_ids = known_world_ids(&query.created_world_id);
+    filter.home_world_ids = known_world_ids(&query.home_world_id);
+
+    // both category filters must hold; a `category_id` outside `category_ids` never gets here
+    match query.category_id {
+        Some(category_id) => filter.category_ids.push(category_id),
+        None => filter.category_ids.extend(&query.category_ids),
     }
 
-    if !query.home_world_id.is_empty() {
-        let valid_home_world_ids: Vec<i32> = query
-            .home_world_id
-            .iter()
-            .filter(|id| id_inventory::world_ids().contains(id))
-            .map(|id| *id as i32)
-            .collect();
-        if !valid_home_world_ids.is_empty() {
-            pipeline.push(doc! {
-                "$match": {
-                    "listing.home_world": { "$in": valid_home_world_ids },
-                }
-            });
-        }
+    if let Some(duty_id) = query.duty_id {
+        filter.duty_ids.push(duty_id);
     }
 
-    if let Some(category_id) = query.category_id {
-        pipeline.push(doc! {
-            "$match": {
-                "listing.category": category_id as i32,
-            }
-        });
+    filter.accepted_slot_bits = query
+        .job_ids
+        .iter()
+        .filter_map(|job_id| id_inventory::accepted_job_flag_bits(*job_id))
+        .collect();
+
+    if let Some(duty_type_id) = query.duty_type_id {
+        filter.duty_type_ids.push(duty_type_id);
     }
 
-    if let Some(duty_id) = query.duty_id {
-        pipeline.push(doc! {
-            "$match": {
-                "listing.duty": duty_id as i32,
-            }
-        });
+    if let Some(loot_rule_id) = query.loot_rule_id {
+        filter.loot_rule_ids.push(loot_rule_id);
     }
 
-    if !query.job_ids.is_empty() {
-        let job_conditions = query
-            .job_ids
+    filter.min_item_level_gte = query.min_item_level_gte;
+    filter.min_item_level_lte = query.min_item_level_lte;
+    filter.objective_bits_any = known_flag_mask(&query.objective_ids, &id_inventory::OBJECTIVE_IDS);
+    filter.condition_bits_any = known_flag_mask(&query.condition_ids, &id_inventory::CONDITION_IDS);
+    filter.beginners_welcome = query.beginners_welcome;
+    filter.one_player_per_job = query.one_player_per_job;
+    filter.cross_world = query.is_cross_world;
+    filter.slots_open_gte = query.slots_open_gte;
+
+    let open_job_groups = [
+        query.open_job_ids.clone(),
+        query
+            .open_role_ids
             .iter()
-            .filter_map(|job_id| id_inventory::accepted_job_flag_bits(*job_id))
-            .map(|job_flag_bits| {
-                doc! {
-                    "listing.slots": {
-                        "$elemMatch": {
-                            "accepting": {
-                                "$bitsAllSet": job_flag_bits as i64
-                            }
-                        }
-                    }
-                }
-            })
-            .collect::<Vec<_>>();
-
-        pipeline.push(doc! {
-            "$match": {
-                "$or": job_conditions,
-            }
-        });
-    }
+            .flat_map(|role_id| id_inventory::job_ids_for_role_id(*role_id))
+            .collect(),
+    ];
+    filter.open_slot_bits = open_job_groups
+        .into_iter()
+        .map(|job_ids| {
+            job_ids
+                .iter()
+                .filter_map(|job_id| id_inventory::accepted_job_flag_bits(*job_id))
+                .collect::<Vec<_>>()
+        })
+        .filter(|bits| !bits.is_empty())
+        .collect();
 
     // Datacenter filter (middle priority - masked by world-id, masks region)
     // Only apply if no world-id filter is active
-    if !world_id_active {
+    if !world_id_filter_is_active(query) {
         if let Some(datacenter_query) = &query.datacenter {
-            let datacenter_names = split_csv_names(datacenter_query);
-            let valid_created_world_ids = matching_created_world_ids_for_datacenters(&datacenter_names);
-            if !valid_created_world_ids.is_empty() {
-                pipeline.push(doc! {
-                    "$match": {
-                        "listing.created_world": { "$in": valid_created_world_ids },
-                    }
-                });
-            }
+            filter.created_world_ids =
+                matching_created_world_ids_for_datacenters(&split_csv_names(datacenter_query));
         }
 
         // Region filter (lowest priority - masked by world-id and datacenter)
         // Only apply if no world-id and no datacenter filter is active
-        if !datacenter_active {
+        if !datacenter_filter_is_active(query) {
             if let Some(region_query) = &query.region {
-                let region_names = split_csv_names(region_query);
-                let valid_created_world_ids = matching_created_world_ids_for_regions(&region_names);
-                if !valid_created_world_ids.is_empty() {
-                    pipeline.push(doc! {
-                        "$match": {
-                            "listing.created_world": { "$in": valid_created_world_ids },
-                        }
-                    });
-                }
+                filter.created_world_ids =
+                    matching_created_world_ids_for_regions(&split_csv_names(region_query));
             }
         }
     }
 
-    pipeline.extend([
-        doc! {
-            "$set": {
-                "time_left": {
-                    "$divide": [
-                        {
-                            "$subtract": [
-                                { "$multiply": ["$listing.seconds_remaining", 1000] },
-                                { "$subtract": ["$$NOW", "$updated_at"] },
-                            ]
-                        },
-                        1000,
-                    ]
-                },
-                "minutes_since_update": {
-                    "$divide": [
-                        { "$subtract": ["$$NOW", "$updated_at"] },
-                        60000,
-                    ]
-                },
-            }
-        },
-        doc! {
-            "$match": {
-                "$and": [
-                    { "time_left": { "$gte": 0 } },
-                    { "minutes_since_update": { "$lt": 5.0 } },
-                ]
-            }
-        },
-        doc! {
-            "$sort": {
-                "updated_at": -1,
-            }
-        },
-        doc! {
-            "$group": {
-                "_id": "$listing.content_id_lower",
-                "doc": { "$first": "$$ROOT" },
-            }
-        },
-        doc! {
-            "$replaceRoot": { "newRoot": "$doc" }
-        },
-        doc! {
-            "$set": {
-                "updated_minute": {
-                    "$dateTrunc": {
-                        "date": "$updated_at",
-                        "unit": "minute",
-                        "binSize": 5,
-                    }
-                }
-            }
-        },
-        doc! {
-            "$sort": {
-                "updated_minute": -1,
-                "listing.category": -1,
-                "time_left": 1,
-            }
-        },
-    ]);
+    filter
+}
 
-    pipeline
+fn known_world_ids(world_ids: &[u32]) -> Vec<u32> {
+    world_ids
+        .iter()
+        .filter(|id| id_inventory::world_ids().contains(id))
+        .copied()
+        .collect()
+}
+
+/// ORs the known flag ids together; `None` when no ids were asked for. Unknown ids only ever
+/// narrow the result to nothing, which `query_demands_empty_collection` handles.
+fn known_flag_mask(ids: &[u32], known: &[u32]) -> Option<u32> {
+    (!ids.is_empty()).then(|| {
+        ids.iter()
+            .filter(|id| known.contains(id))
+            .fold(0, |mask, id| mask | id)
+    })
 }
 
 pub(crate) fn collection_response_from_documents<'a>(
     query: ListingsQuery,
     documents: impl IntoIterator<Item = &'a QueriedListing>,
 ) -> ListingCollectionResponse {
-    let filtered = documents
+    let filtered = sorted_matching_listings(&query, documents)
         .into_iter()
-        .filter(|document| matches_query(document, &query))
-        .filter_map(project_listing_summary)
-        .collect::<Vec<_>>();
-
+        .map(|(_, summary)| summary)
+        .collect();
     paginated_collection_response(query, filtered)
 }
 
-fn paginated_collection_response(
+/// The visible documents that match every filter, with their summaries, in `sort` order.
+pub(super) fn sorted_matching_listings<'a>(
+    query: &ListingsQuery,
+    documents: impl IntoIterator<Item = &'a QueriedListing>,
+) -> Vec<(&'a QueriedListing, ListingSummary)> {
+    let mut filtered = documents
+        .into_iter()
+        .filter(|document| matches_query(document, query))
+        .filter_map(|document| Some((document, project_listing_summary(document)?)))
+        .collect::<Vec<_>>();
+    filtered.sort_by(|(a, a_summary), (b, b_summary)| {
+        compare_listings(a, a_summary, b, b_summary, query.sort_fields())
+    });
+    filtered
+}
+
+/// Orders by `sort`, then by listing id and created world ascending, so equal rows keep the
+/// same order on every request and pages never shuffle.
+fn compare_listings(
+    a: &QueriedListing,
+    a_summary: &ListingSummary,
+    b: &QueriedListing,
+    b_summary: &ListingSummary,
+    sort: &[SortField],
+) -> Ordering {
+    sort.iter()
+        .map(|field| {
+            let ordering = match field.key {
+                SortKey::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+                SortKey::TimeLeftSeconds => a_summary.time_left_seconds.cmp(&b_summary.time_left_seconds),
+                SortKey::MinItemLevel => a_summary.min_item_level.cmp(&b_summary.min_item_level),
+                SortKey::SlotsFilled => a_summary.slots_filled.cmp(&b_summary.slots_filled),
+                SortKey::SlotsAvailable => a_summary.slots_available.cmp(&b_summary.slots_available),
+                SortKey::CategoryId => a_summary.category_id.cmp(&b_summary.category_id),
+            };
+            if field.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        })
+        .fold(Ordering::Equal, Ordering::then)
+        .then(a.listing.id.cmp(&b.listing.id))
+        .then(a.listing.created_world.cmp(&b.listing.created_world))
+}
+
+pub(crate) fn paginated_collection_response<T>(
     query: ListingsQuery,
-    filtered: Vec<ListingSummary>,
-) -> ListingCollectionResponse {
+    filtered: Vec<T>,
+) -> CollectionEnvelope<T> {
     let total = filtered.len();
     let total_pages = total_pages(total, query.per_page);
     let data = if query.page > total_pages && total > 0 {
@@ -528,7 +415,7 @@ fn paginated_collection_response(
     }
 }
 
-fn empty_collection_response(query: &ListingsQuery) -> ListingCollectionResponse {
+pub(crate) fn empty_collection_response<T>(query: &ListingsQuery) -> CollectionEnvelope<T> {
     CollectionEnvelope {
         data: Vec::new(),
         pagination: Pagination {
@@ -560,7 +447,7 @@ fn region_filter_is_active(query: &ListingsQuery) -> bool {
     query.region.is_some()
 }
 
-fn query_demands_empty_collection(query: &ListingsQuery) -> bool {
+pub(super) fn query_demands_empty_collection(query: &ListingsQuery) -> bool {
     // Precedence: world-id > datacenter > region
     // If a higher-priority filter is active and well-formed, lower-priority filters are masked
 
@@ -584,13 +471,7 @@ fn query_demands_empty_collection(query: &ListingsQuery) -> bool {
 
         return created_world_all_unknown
             || home_world_all_unknown
-            || query.category_id
-                .is_some_and(|category_id| !id_inventory::CATEGORY_IDS.contains(&category_id))
-            || query.duty_id.is_some_and(|duty_id| duty_id > u16::MAX as u32)
-            || query
-                .job_ids
-                .iter()
-                .any(|job_id| !id_inventory::job_ids().contains(job_id));
+            || other_filters_demand_empty_collection(query);
     }
 
     // Check datacenter filter (middle priority - masks region)
@@ -601,13 +482,7 @@ fn query_demands_empty_collection(query: &ListingsQuery) -> bool {
         });
 
         return datacenter_all_unknown
-            || query.category_id
-                .is_some_and(|category_id| !id_inventory::CATEGORY_IDS.contains(&category_id))
-            || query.duty_id.is_some_and(|duty_id| duty_id > u16::MAX as u32)
-            || query
-                .job_ids
-                .iter()
-                .any(|job_id| !id_inventory::job_ids().contains(job_id));
+            || other_filters_demand_empty_collection(query);
     }
 
     // Check region filter (lowest priority)
@@ -618,31 +493,53 @@ fn query_demands_empty_collection(query: &ListingsQuery) -> bool {
         });
 
         return region_all_unknown
-            || query.category_id
-                .is_some_and(|category_id| !id_inventory::CATEGORY_IDS.contains(&category_id))
-            || query.duty_id.is_some_and(|duty_id| duty_id > u16::MAX as u32)
-            || query
-                .job_ids
-                .iter()
-                .any(|job_id| !id_inventory::job_ids().contains(job_id));
+            || other_filters_demand_empty_collection(query);
     }
 
     // No world-id/datacenter/region filter active - check other filters
+    other_filters_demand_empty_collection(query)
+}
+
+/// Well-formed values that no listing can match, for every filter outside the world-id >
+/// datacenter > region chain.
+fn other_filters_demand_empty_collection(query: &ListingsQuery) -> bool {
+    let all_unknown =
+        |ids: &[u32], known: &[u32]| !ids.is_empty() && !ids.iter().any(|id| known.contains(id));
+
     query.category_id
         .is_some_and(|category_id| !id_inventory::CATEGORY_IDS.contains(&category_id))
+        || all_unknown(&query.category_ids, &id_inventory::CATEGORY_IDS)
+        || query.category_id.is_some_and(|category_id| {
+            !query.category_ids.is_empty() && !query.category_ids.contains(&category_id)
+        })
         || query.duty_id.is_some_and(|duty_id| duty_id > u16::MAX as u32)
         || query
             .job_ids
             .iter()
             .any(|job_id| !id_inventory::job_ids().contains(job_id))
+        || query
+            .duty_type_id
+            .is_some_and(|duty_type_id| !id_inventory::DUTY_TYPE_IDS.contains(&duty_type_id))
+        || all_unknown(&query.objective_ids, &id_inventory::OBJECTIVE_IDS)
+        || all_unknown(&query.condition_ids, &id_inventory::CONDITION_IDS)
+        || all_unknown(&query.open_role_ids, &id_inventory::ROLE_IDS)
+        || all_unknown(&query.open_job_ids, &id_inventory::job_ids())
+        || query
+            .loot_rule_id
+            .is_some_and(|loot_rule_id| !id_inventory::LOOT_RULE_IDS.contains(&loot_rule_id))
+        || query
+            .min_item_level_gte
+            .zip(query.min_item_level_lte)
+            .is_some_and(|(gte, lte)| gte > lte)
 }
 
-fn matches_query(document: &QueriedListing, query: &ListingsQuery) -> bool {
-    let listing = match visible_listing(document) {
-        Some(listing) => listing,
-        None => return false,
-    };
 
+/// Whether the listing is visible and passes every filter of `query`.
+pub(crate) fn matches_query(document: &QueriedListing, query: &ListingsQuery) -> bool {
+    visible_listing(document).is_some_and(|listing| matches_filters(listing, query))
+}
+
+fn matches_filters(listing: &PartyFinderListing, query: &ListingsQuery) -> bool {
     // Precedence: world-id > datacenter > region
     // If a higher-priority filter is active, lower-priority filters are masked
 
@@ -698,11 +595,62 @@ fn matches_query(document: &QueriedListing, query: &ListingsQuery) -> bool {
     }
 
     query.category_id.is_none_or(|category_id| id_inventory::category_id(listing.category) == category_id)
+        && matches_any_id(&[id_inventory::category_id(listing.category)], &query.category_ids)
         && query.duty_id.is_none_or(|duty_id| id_inventory::duty_id(listing.duty) == duty_id)
         && matches_job_ids(listing, &query.job_ids)
+        && query
+            .duty_type_id
+            .is_none_or(|duty_type_id| id_inventory::duty_type_id(listing.duty_type) == duty_type_id)
+        && matches_any_id(&id_inventory::objective_ids(listing.objective), &query.objective_ids)
+        && matches_any_id(&id_inventory::condition_ids(listing.conditions), &query.condition_ids)
+        && query
+            .loot_rule_id
+            .is_none_or(|loot_rule_id| id_inventory::loot_rule_id(listing.loot_rules) == loot_rule_id)
+        && query.min_item_level_gte.is_none_or(|level| listing.min_item_level >= level)
+        && query.min_item_level_lte.is_none_or(|level| listing.min_item_level <= level)
+        && query.beginners_welcome.is_none_or(|welcome| listing.beginners_welcome == welcome)
+        && query.one_player_per_job.is_none_or(|set| {
+            listing.search_area.contains(SearchAreaFlags::ONE_PLAYER_PER_JOB) == set
+        })
+        && query.is_cross_world.is_none_or(|set| is_cross_world(listing) == set)
+        && query.slots_open_gte.is_none_or(|open| {
+            usize::from(listing.slots_available).saturating_sub(count_slots_filled(listing))
+                >= open as usize
+        })
+        && matches_open_slots(listing, &query.open_job_ids, &query.open_role_ids)
         && matches_search(listing, query.search.as_deref())
 }
 
+/// Unlike `job_ids`, only unfilled slots count. A slot is open for a role when it accepts any
+/// job of that role, so a slot open to every job is open to healers too.
+fn matches_open_slots(listing: &PartyFinderListing, job_ids: &[u32], role_ids: &[u32]) -> bool {
+    if job_ids.is_empty() && role_ids.is_empty() {
+        return true;
+    }
+
+    let open_slots = project_slots(listing)
+        .into_iter()
+        .filter(|slot| !slot.filled)
+        .collect::<Vec<_>>();
+    let any_open_slot_accepts = |wanted: &dyn Fn(u32) -> bool| {
+        open_slots
+            .iter()
+            .any(|slot| slot.accepted_job_ids.iter().any(|job_id| wanted(*job_id)))
+    };
+
+    (job_ids.is_empty() || any_open_slot_accepts(&|job_id| job_ids.contains(&job_id)))
+        && (role_ids.is_empty()
+            || any_open_slot_accepts(&|job_id| {
+                id_inventory::role_id_for_job_id(job_id)
+                    .is_some_and(|role_id| role_ids.contains(&role_id))
+            }))
+}
+
+/// Empty `wanted` matches everything; otherwise any shared id does.
+fn matches_any_id(listing_ids: &[u32], wanted: &[u32]) -> bool {
+    wanted.is_empty() || wanted.iter().any(|id| listing_ids.contains(id))
+}
+
 fn split_csv_names(value: &str) -> Vec<String> {
     value
         .split(',')
@@ -712,15 +660,15 @@ fn split_csv_names(value: &str) -> Vec<String> {
         .collect()
 }
 
-fn matching_created_world_ids_for_datacenters(datacenter_names: &[String]) -> Vec<i32> {
+fn matching_created_world_ids_for_datacenters(datacenter_names: &[String]) -> Vec<u32> {
     crate::ffxiv::WORLDS
         .iter()
         .filter(|(_, world)| datacenter_names.iter().any(|query_name| *query_name == world.data_center().name()))
-        .map(|(world_id, _)| *world_id as i32)
+        .map(|(world_id, _)| *world_id)
         .collect()
 }
 
-fn matching_created_world_ids_for_regions(region_names: &[String]) -> Vec<i32> {
+fn matching_created_world_ids_for_regions(region_names: &[String]) -> Vec<u32> {
     crate::ffxiv::WORLDS
         .iter()
         .filter(|(_, world)| {
@@ -728,7 +676,7 @@ fn matching_created_world_ids_for_regions(region_names: &[String]) -> Vec<i32> {
                 .iter()
                 .any(|query_name| *query_name == data_center_region_name(world.data_center()))
         })
-        .map(|(world_id, _)| *world_id as i32)
+        .map(|(world_id, _)| *world_id)
         .collect()
 }
 
@@ -738,7 +686,7 @@ fn created_world_region_name(listing: &PartyFinderListing) -> Option<&'static st
         .map(|world| data_center_region_name(world.data_center()))
 }
 
-fn data_center_region_name(data_center: ffxiv_types_cn::DataCenter) -> &'static str {
+pub(crate) fn data_center_region_name(data_center: ffxiv_types_cn::DataCenter) -> &'static str {
     use ffxiv_types_cn::DataCenter;
 
     match data_center {
@@ -771,7 +719,8 @@ fn matches_job_ids(listing: &PartyFinderListing, job_ids: &[u32]) -> bool {
         })
 }
 
-fn collection_response_from_raw_documents(query: ListingsQuery, documents: Vec<Document>) -> Response {
+#[cfg(test)]
+fn collection_response_from_raw_documents(query: ListingsQuery, documents: Vec<mongodb::bson::Document>) -> Response {
     let mut decoded_documents = Vec::new();
 
     for document in documents {
@@ -791,28 +740,15 @@ fn collection_response_from_decoded_documents(
     warp::reply::json(&collection_response_from_documents(query, documents.iter())).into_response()
 }
 
-fn decode_queried_listing(document: Document) -> Result<QueriedListing, ()> {
+#[cfg(test)]
+fn decode_queried_listing(document: mongodb::bson::Document) -> Result<QueriedListing, ()> {
     mongodb::bson::from_document::<QueriedListing>(document).map_err(|error| {
         eprintln!("{error:#?}");
     })
 }
 
 fn matches_search(listing: &PartyFinderListing, search: Option<&str>) -> bool {
-    let Some(search) = search else {
-        return true;
-    };
-
-    let search = search.to_lowercase();
-    let player_name = listing
-        .name
-        .full_text(&crate::ffxiv::Language::ChineseSimplified)
-        .to_lowercase();
-    let description = listing
-        .description
-        .full_text(&crate::ffxiv::Language::ChineseSimplified)
-        .to_lowercase();
-
-    player_name.contains(&search) || description.contains(&search)
+    search.is_none_or(|search| SearchQuery::parse(search).matches(listing))
 }
 
 pub(crate) fn project_listing_summaries<'a>(
@@ -827,7 +763,28 @@ pub(crate) fn project_listing_summaries<'a>(
 pub(crate) fn project_listing_summary(document: &QueriedListing) -> Option<ListingSummary> {
     let listing = visible_listing(document)?;
 
-Some(ListingSummary {
+    Some(listing_summary(document, listing))
+}
+
+/// Stream events and history rows skip the active-window check so `expired` events and past
+/// rows still project, but private listings stay hidden exactly like the collection route.
+pub(crate) fn project_event_summary(
+    document: &QueriedListing,
+    query: &ListingsQuery,
+) -> Option<ListingSummary> {
+    let listing = &document.listing;
+    if listing.search_area.contains(SearchAreaFlags::PRIVATE) || !matches_filters(listing, query) {
+        return None;
+    }
+
+    Some(listing_summary(document, listing))
+}
+
+pub(crate) fn listing_summary(
+    document: &QueriedListing,
+    listing: &PartyFinderListing,
+) -> ListingSummary {
+    ListingSummary {
         id: listing.id.to_string(),
         player_name: listing
             .name
@@ -849,13 +806,22 @@ Some(ListingSummary {
         updated_at: document.updated_at.to_rfc3339(),
         is_cross_world: is_cross_world(listing),
         beginners_welcome: listing.beginners_welcome,
-    })
+    }
 }
 
 pub(crate) fn resolve_listing_detail<'a>(
     id: u64,
     documents: impl IntoIterator<Item = &'a QueriedListing>,
 ) -> Option<ListingDetail> {
+    resolve_visible_listing(id, documents).and_then(project_listing_detail)
+}
+
+/// The most recently updated visible document for listing `id`, which is what
+/// `/api/v2/listings/{id}` and the `/listings/{id}` page show.
+pub(crate) fn resolve_visible_listing<'a>(
+    id: u64,
+    documents: impl IntoIterator<Item = &'a QueriedListing>,
+) -> Option<&'a QueriedListing> {
     let mut selected: Option<&QueriedListing> = None;
 
     for document in documents {
@@ -873,7 +839,7 @@ pub(crate) fn resolve_listing_detail<'a>(
         }
     }
 
-    selected.and_then(project_listing_detail)
+    selected
 }
 
 pub(crate) fn project_listing_detail(document: &QueriedListing) -> Option<ListingDetail> {
@@ -930,7 +896,7 @@ fn is_cross_world(listing: &PartyFinderListing) -> bool {
     listing.search_area.contains(SearchAreaFlags::DATA_CENTRE)
 }
 
-fn project_slots(listing: &PartyFinderListing) -> Vec<ListingSlot> {
+pub(super) fn project_slots(listing: &PartyFinderListing) -> Vec<ListingSlot> {
     listing
         .slots
         .iter()
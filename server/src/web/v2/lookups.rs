@@ -1 +1,421 @@
-pub const PHASE_ONE_LOOKUPS_EXPOSED: bool = false;
+use std::{collections::BTreeMap, convert::Infallible};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use warp::{filters::BoxedFilter, http::StatusCode, reply::Response, Filter, Reply};
+
+use crate::{
+    ffxiv::{self, LocalisedText},
+    listing::{DutyCategory, DutyType},
+};
+
+use super::{
+    contracts::{
+        CollectionEnvelope, DatacenterLookup, DutyLookup, ErrorEnvelope, JobLookup, LabelLookup,
+        LocalisedLabel, Pagination, RegionLookup, WorldLookup,
+    },
+    id_inventory,
+    listings::data_center_region_name,
+};
+
+pub const LOOKUP_KINDS: [&str; 11] = [
+    "worlds",
+    "datacenters",
+    "regions",
+    "categories",
+    "duties",
+    "duty_types",
+    "jobs",
+    "roles",
+    "objectives",
+    "conditions",
+    "loot_rules",
+];
+
+const DUTY_TYPE_LABELS: [(DutyType, LocalisedText); 3] = [
+    (
+        DutyType::Other,
+        LocalisedText {
+            en: "Other",
+            ja: "その他",
+            de: "Sonstiges",
+            fr: "Autre",
+            zh: "其他",
+        },
+    ),
+    (
+        DutyType::Roulette,
+        LocalisedText {
+            en: "Duty Roulette",
+            ja: "コンテンツルーレット",
+            de: "Zufallsinhalt",
+            fr: "Mission aléatoire",
+            zh: "随机任务",
+        },
+    ),
+    (
+        DutyType::Normal,
+        LocalisedText {
+            en: "Duty",
+            ja: "コンテンツ",
+            de: "Inhalt",
+            fr: "Mission",
+            zh: "任务",
+        },
+    ),
+];
+
+const ROLE_LABELS: [(u32, LocalisedText); 3] = [
+    (
+        id_inventory::ROLE_ID_TANK,
+        LocalisedText {
+            en: "Tank",
+            ja: "タンク",
+            de: "Verteidiger",
+            fr: "Tank",
+            zh: "防护职业",
+        },
+    ),
+    (
+        id_inventory::ROLE_ID_HEALER,
+        LocalisedText {
+            en: "Healer",
+            ja: "ヒーラー",
+            de: "Heiler",
+            fr: "Soigneur",
+            zh: "治疗职业",
+        },
+    ),
+    (
+        id_inventory::ROLE_ID_DPS,
+        LocalisedText {
+            en: "DPS",
+            ja: "DPS",
+            de: "Angreifer",
+            fr: "DPS",
+            zh: "进攻职业",
+        },
+    ),
+];
+
+pub(crate) const OBJECTIVE_LABELS: [LocalisedText; 3] = [
+    LocalisedText {
+        en: "Duty Completion",
+        ja: "クリア目的",
+        de: "Abschluss",
+        fr: "Terminer la mission",
+        zh: "完成任务",
+    },
+    LocalisedText {
+        en: "Practice",
+        ja: "練習",
+        de: "Übung",
+        fr: "Entraînement",
+        zh: "练习",
+    },
+    LocalisedText {
+        en: "Loot",
+        ja: "アイテム目的",
+        de: "Beute",
+        fr: "Butin",
+        zh: "获取道具",
+    },
+];
+
+pub(crate) const CONDITION_LABELS: [LocalisedText; 3] = [
+    LocalisedText {
+        en: "Duty Complete",
+        ja: "クリア済み",
+        de: "Abgeschlossen",
+        fr: "Mission accomplie",
+        zh: "已完成",
+    },
+    LocalisedText {
+        en: "Duty Incomplete",
+        ja: "未クリア",
+        de: "Nicht abgeschlossen",
+        fr: "Mission non accomplie",
+        zh: "未完成",
+    },
+    LocalisedText {
+        en: "Duty Complete (Weekly Reward Unclaimed)",
+        ja: "クリア済み（報酬未獲得）",
+        de: "Abgeschlossen (Wochenbelohnung nicht erhalten)",
+        fr: "Mission accomplie (récompense hebdomadaire non obtenue)",
+        zh: "已完成（本周奖励未获得）",
+    },
+];
+
+const LOOT_RULE_LABELS: [LocalisedText; 4] = [
+    LocalisedText {
+        en: "Normal",
+        ja: "通常",
+        de: "Normal",
+        fr: "Normal",
+        zh: "普通",
+    },
+    LocalisedText {
+        en: "Greed Only",
+        ja: "NEED禁止",
+        de: "Nur Gier",
+        fr: "Cupidité uniquement",
+        zh: "仅限贪婪",
+    },
+    LocalisedText {
+        en: "Lootmaster",
+        ja: "リーダー分配",
+        de: "Beutemeister",
+        fr: "Maître du butin",
+        zh: "队长分配",
+    },
+    LocalisedText {
+        en: "Greed Only, Lootmaster",
+        ja: "NEED禁止・リーダー分配",
+        de: "Nur Gier, Beutemeister",
+        fr: "Cupidité uniquement, maître du butin",
+        zh: "仅限贪婪、队长分配",
+    },
+];
+
+pub fn routes() -> BoxedFilter<(Response,)> {
+    warp::path!("api" / "v2" / "lookups" / String)
+        .and(warp::path::end())
+        .and(warp::get())
+        .and_then(lookup)
+        .boxed()
+}
+
+async fn lookup(kind: String) -> Result<Response, Infallible> {
+    Ok(match kind.as_str() {
+        "worlds" => collection_reply(worlds()),
+        "datacenters" => collection_reply(datacenters()),
+        "regions" => collection_reply(regions()),
+        "categories" => collection_reply(categories()),
+        "duties" => collection_reply(duties()),
+        "duty_types" => collection_reply(duty_types()),
+        "jobs" => collection_reply(jobs()),
+        "roles" => collection_reply(roles()),
+        "objectives" => collection_reply(objectives()),
+        "conditions" => collection_reply(conditions()),
+        "loot_rules" => collection_reply(loot_rules()),
+        _ => not_found_reply(&kind).into_response(),
+    })
+}
+
+fn collection_reply<T: Serialize>(data: Vec<T>) -> Response {
+    warp::reply::json(&lookup_collection(data)).into_response()
+}
+
+/// Lookup tables are small and static, so every response carries the whole table on one page.
+pub(crate) fn lookup_collection<T>(data: Vec<T>) -> CollectionEnvelope<T> {
+    let total = data.len();
+
+    CollectionEnvelope {
+        data,
+        pagination: Pagination {
+            total,
+            page: 1,
+            per_page: total,
+            total_pages: usize::from(total > 0),
+        },
+    }
+}
+
+fn not_found_reply(kind: &str) -> impl Reply {
+    let mut details = Map::new();
+    details.insert("lookup".into(), Value::from(kind));
+    details.insert("supported".into(), Value::from(LOOKUP_KINDS.to_vec()));
+
+    warp::reply::with_status(
+        warp::reply::json(&ErrorEnvelope::new(
+            "not_found",
+            "Lookup not found",
+            details,
+        )),
+        StatusCode::NOT_FOUND,
+    )
+}
+
+pub(crate) fn worlds() -> Vec<WorldLookup> {
+    id_inventory::world_ids()
+        .into_iter()
+        .filter_map(|world_id| {
+            let world = ffxiv::WORLDS.get(&world_id)?;
+            Some(WorldLookup {
+                id: world_id,
+                name: world.name().into(),
+                datacenter: world.data_center().name().into(),
+                region: data_center_region_name(world.data_center()).into(),
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn datacenters() -> Vec<DatacenterLookup> {
+    let mut datacenters = BTreeMap::<&'static str, DatacenterLookup>::new();
+
+    for world in worlds() {
+        let data_center = ffxiv::WORLDS[&world.id].data_center();
+        datacenters
+            .entry(data_center.name())
+            .or_insert_with(|| DatacenterLookup {
+                name: data_center.name().into(),
+                region: data_center_region_name(data_center).into(),
+                world_ids: Vec::new(),
+            })
+            .world_ids
+            .push(world.id);
+    }
+
+    datacenters.into_values().collect()
+}
+
+pub(crate) fn regions() -> Vec<RegionLookup> {
+    let mut regions = BTreeMap::<String, RegionLookup>::new();
+
+    for datacenter in datacenters() {
+        regions
+            .entry(datacenter.region.clone())
+            .or_insert_with(|| RegionLookup {
+                name: datacenter.region.clone(),
+                datacenters: Vec::new(),
+            })
+            .datacenters
+            .push(datacenter.name);
+    }
+
+    regions.into_values().collect()
+}
+
+pub(crate) fn categories() -> Vec<LabelLookup> {
+    id_inventory::CATEGORY_IDS
+        .into_iter()
+        .filter_map(|category_id| {
+            let category = DutyCategory::from_u32(category_id)?;
+            Some(LabelLookup {
+                id: id_inventory::category_id(category),
+                name: LocalisedLabel::from(&category.pf_category().name()),
+            })
+        })
+        .collect()
+}
+
+/// Duty ids only mean something together with the listing's duty type and, for the
+/// `Other` duty type, its category: treasure maps and FATE zones reuse small ids.
+pub(crate) fn duties() -> Vec<DutyLookup> {
+    let normal = id_inventory::duty_type_id(DutyType::Normal);
+    let roulette = id_inventory::duty_type_id(DutyType::Roulette);
+    let other = id_inventory::duty_type_id(DutyType::Other);
+    let treasure_hunt = id_inventory::category_id(DutyCategory::TreasureHunt);
+    let fate = id_inventory::category_id(DutyCategory::Fate);
+
+    let mut duties = ffxiv::DUTIES
+        .iter()
+        .map(|(duty_id, info)| DutyLookup {
+            duty_type_id: normal,
+            category_id: None,
+            duty_id: *duty_id,
+            name: LocalisedLabel::from(&info.name),
+            high_end: info.high_end,
+        })
+        .chain(ffxiv::ROULETTES.iter().map(|(duty_id, info)| DutyLookup {
+            duty_type_id: roulette,
+            category_id: None,
+            duty_id: *duty_id,
+            name: LocalisedLabel::from(&info.name),
+            high_end: false,
+        }))
+        .chain(ffxiv::TREASURE_MAPS.iter().map(|(duty_id, name)| DutyLookup {
+            duty_type_id: other,
+            category_id: Some(treasure_hunt),
+            duty_id: *duty_id,
+            name: LocalisedLabel::from(name),
+            high_end: false,
+        }))
+        .chain(ffxiv::TERRITORY_NAMES.iter().map(|(duty_id, name)| DutyLookup {
+            duty_type_id: other,
+            category_id: Some(fate),
+            duty_id: *duty_id,
+            name: LocalisedLabel::from(name),
+            high_end: false,
+        }))
+        .collect::<Vec<_>>();
+
+    duties.sort_unstable_by_key(|duty| (duty.duty_type_id, duty.category_id, duty.duty_id));
+    duties
+}
+
+pub(crate) fn duty_types() -> Vec<LabelLookup> {
+    DUTY_TYPE_LABELS
+        .iter()
+        .map(|(duty_type, name)| LabelLookup {
+            id: id_inventory::duty_type_id(*duty_type),
+            name: LocalisedLabel::from(name),
+        })
+        .collect()
+}
+
+pub(crate) fn jobs() -> Vec<JobLookup> {
+    let accepted_job_ids = id_inventory::job_ids();
+
+    let mut jobs = ffxiv::JOBS
+        .iter()
+        .map(|(job_id, job)| JobLookup {
+            id: *job_id,
+            code: job.code().into(),
+            role_id: id_inventory::role_id_for_job_id(*job_id),
+            accepted_in_slots: accepted_job_ids.contains(job_id),
+        })
+        .collect::<Vec<_>>();
+
+    jobs.sort_unstable_by_key(|job| job.id);
+    jobs
+}
+
+pub(crate) fn roles() -> Vec<LabelLookup> {
+    ROLE_LABELS
+        .iter()
+        .map(|(role_id, name)| LabelLookup {
+            id: *role_id,
+            name: LocalisedLabel::from(name),
+        })
+        .collect()
+}
+
+pub(crate) fn objectives() -> Vec<LabelLookup> {
+    labelled_ids(&id_inventory::OBJECTIVE_IDS, &OBJECTIVE_LABELS)
+}
+
+pub(crate) fn conditions() -> Vec<LabelLookup> {
+    labelled_ids(&id_inventory::CONDITION_IDS, &CONDITION_LABELS)
+}
+
+pub(crate) fn loot_rules() -> Vec<LabelLookup> {
+    labelled_ids(&id_inventory::LOOT_RULE_IDS, &LOOT_RULE_LABELS)
+}
+
+pub(crate) fn objective_label(id: u32) -> Option<&'static LocalisedText> {
+    label_for(&id_inventory::OBJECTIVE_IDS, &OBJECTIVE_LABELS, id)
+}
+
+pub(crate) fn condition_label(id: u32) -> Option<&'static LocalisedText> {
+    label_for(&id_inventory::CONDITION_IDS, &CONDITION_LABELS, id)
+}
+
+pub(crate) fn loot_rule_label(id: u32) -> Option<&'static LocalisedText> {
+    label_for(&id_inventory::LOOT_RULE_IDS, &LOOT_RULE_LABELS, id)
+}
+
+fn label_for(ids: &[u32], labels: &'static [LocalisedText], id: u32) -> Option<&'static LocalisedText> {
+    ids.iter().position(|known| *known == id).and_then(|index| labels.get(index))
+}
+
+fn labelled_ids(ids: &[u32], labels: &[LocalisedText]) -> Vec<LabelLookup> {
+    ids.iter()
+        .zip(labels)
+        .map(|(id, name)| LabelLookup {
+            id: *id,
+            name: LocalisedLabel::from(name),
+        })
+        .collect()
+}
@@ -1,15 +1,33 @@
 use std::sync::Arc;
 
-use warp::{filters::BoxedFilter, Reply};
+use warp::{filters::BoxedFilter, Filter, Reply};
 
 use crate::web::State;
 
 pub mod contracts;
+pub mod duty_search;
+pub mod eligible;
 pub mod filters;
+pub mod fit;
+pub mod history;
 pub mod id_inventory;
 pub mod listings;
 pub mod lookups;
+pub mod stats;
+pub mod stream;
+pub mod subscriptions;
 
 pub fn routes(state: Arc<State>) -> BoxedFilter<(impl Reply,)> {
-    listings::routes(state)
+    listings::routes(Arc::clone(&state))
+        .or(history::routes(Arc::clone(&state)))
+        .unify()
+        .or(stats::routes(Arc::clone(&state)))
+        .unify()
+        .or(subscriptions::routes(state))
+        .unify()
+        .or(duty_search::route())
+        .unify()
+        .or(lookups::routes())
+        .unify()
+        .boxed()
 }
@@ -0,0 +1,232 @@
+use std::{convert::Infallible, sync::Arc};
+
+use serde_json::{Map, Value};
+use warp::{
+    filters::BoxedFilter,
+    http::{header::RETRY_AFTER, StatusCode},
+    reply::Response,
+    Filter, Reply,
+};
+
+use crate::{
+    stats::{self, DutyKey, FlagCount},
+    web::{stats::{get_duty_stats, is_known_duty}, State},
+};
+
+use super::contracts::{
+    DailyCount, DutyCount, DutyStatistics, DutyStatisticsResponse, DutyStatisticsWindow,
+    ErrorEnvelope, HostCount, HourCount, IdCount, MemberEnvelope, PlayerCount, Statistics,
+    StatisticsResponse, WeekdayCount,
+};
+
+/// What a client is told to wait while the first aggregation is still running.
+const WARMING_UP_RETRY_AFTER_SECONDS: u32 = 30;
+
+pub fn routes(state: Arc<State>) -> BoxedFilter<(Response,)> {
+    let all_time = {
+        let state = Arc::clone(&state);
+        warp::path!("api" / "v2" / "stats")
+            .and(warp::path::end())
+            .and(warp::get())
+            .and_then(move || statistics(Arc::clone(&state), false))
+    };
+    let seven_days = {
+        let state = Arc::clone(&state);
+        warp::path!("api" / "v2" / "stats" / "7days")
+            .and(warp::path::end())
+            .and(warp::get())
+            .and_then(move || statistics(Arc::clone(&state), true))
+    };
+    let duty = warp::path!("api" / "v2" / "stats" / "duties" / String / String / String)
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::any().map(move || state.clone()))
+        .and_then(duty);
+
+    all_time
+        .or(seven_days)
+        .unify()
+        .or(duty)
+        .unify()
+        .boxed()
+}
+
+async fn statistics(state: Arc<State>, seven_days: bool) -> Result<Response, Infallible> {
+    let cached = state.stats.read().await.clone();
+    Ok(match cached {
+        Some(cached) => {
+            let stats = if seven_days { &cached.seven_days } else { &cached.all_time };
+            warp::reply::json(&statistics_response(stats)).into_response()
+        }
+        None => warming_up_reply(),
+    })
+}
+
+/// Served until the background task has aggregated the statistics once.
+fn warming_up_reply() -> Response {
+    let mut response = error_reply(
+        ErrorEnvelope::new(
+            "warming_up",
+            "Statistics are still being generated; try again shortly",
+            Map::new(),
+        ),
+        StatusCode::SERVICE_UNAVAILABLE,
+    );
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, WARMING_UP_RETRY_AFTER_SECONDS.into());
+    response
+}
+
+pub(crate) fn statistics_response(stats: &stats::Statistics) -> StatisticsResponse {
+    MemberEnvelope {
+        data: Statistics {
+            listings: stats.num_listings(),
+            duties: stats
+                .duties
+                .iter()
+                .map(|info| DutyCount {
+                    duty_type_id: u32::from(info.info.0),
+                    category_id: info.info.1,
+                    duty_id: u32::from(info.info.2),
+                    count: info.count,
+                })
+                .collect(),
+            hosts: stats
+                .hosts
+                .iter()
+                .map(|host| HostCount {
+                    created_world_id: host.created_world,
+                    count: host.count,
+                    top_hosts: host
+                        .content_ids
+                        .iter()
+                        .map(|entry| {
+                            let alias = stats.aliases.get(&entry.content_id);
+                            PlayerCount {
+                                player_name: alias.map(|alias| alias.name.text()),
+                                home_world_id: alias.map(|alias| alias.home_world),
+                                count: entry.count,
+                            }
+                        })
+                        .collect(),
+                    other_count: host.num_other(),
+                })
+                .collect(),
+            hours: hour_counts(&stats.hours),
+            weekdays: weekday_counts(&stats.days),
+        },
+    }
+}
+
+fn hour_counts(hours: &[stats::HourInfo]) -> Vec<HourCount> {
+    hours
+        .iter()
+        .map(|info| HourCount {
+            hour: info.hour,
+            count: info.count,
+        })
+        .collect()
+}
+
+fn weekday_counts(days: &[stats::DayInfo]) -> Vec<WeekdayCount> {
+    days.iter()
+        .map(|info| WeekdayCount {
+            weekday: info.day,
+            count: info.count,
+        })
+        .collect()
+}
+
+async fn duty(
+    duty_type_id: String,
+    category_id: String,
+    duty_id: String,
+    state: Arc<State>,
+) -> Result<Response, Infallible> {
+    let key = match (duty_type_id.parse(), category_id.parse(), duty_id.parse()) {
+        (Ok(duty_type), Ok(category), Ok(duty)) => (duty_type, category, duty),
+        _ => return Ok(error_reply(
+            ErrorEnvelope::new("invalid_id", "Invalid duty ID format", Map::new()),
+            StatusCode::BAD_REQUEST,
+        )),
+    };
+
+    if !is_known_duty(key) {
+        let mut details = Map::new();
+        details.insert("duty_type_id".into(), Value::from(key.0));
+        details.insert("category_id".into(), Value::from(key.1));
+        return Ok(error_reply(
+            ErrorEnvelope::new("not_found", "Unknown duty type or category", details),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+
+    match get_duty_stats(&state, key).await {
+        Ok(cached) => Ok(warp::reply::json(&duty_statistics_response(
+            key,
+            &cached.all_time,
+            &cached.seven_days,
+        ))
+        .into_response()),
+        Err(error) => {
+            eprintln!("{error:#?}");
+            Ok(error_reply(
+                ErrorEnvelope::new("internal_error", "Failed to load API v2 statistics", Map::new()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+fn error_reply(error: ErrorEnvelope, status: StatusCode) -> Response {
+    warp::reply::with_status(warp::reply::json(&error), status).into_response()
+}
+
+pub(crate) fn duty_statistics_response(
+    (duty_type, category, duty): DutyKey,
+    all_time: &stats::DutyStatistics,
+    seven_days: &stats::DutyStatistics,
+) -> DutyStatisticsResponse {
+    MemberEnvelope {
+        data: DutyStatistics {
+            duty_type_id: u32::from(duty_type),
+            category_id: category,
+            duty_id: u32::from(duty),
+            all_time: duty_statistics_window(all_time),
+            seven_days: duty_statistics_window(seven_days),
+        },
+    }
+}
+
+fn duty_statistics_window(stats: &stats::DutyStatistics) -> DutyStatisticsWindow {
+    DutyStatisticsWindow {
+        listings: stats.num_listings(),
+        volume: stats
+            .volume
+            .iter()
+            .map(|info| DailyCount {
+                date: info.day.format("%Y-%m-%d").to_string(),
+                count: info.count,
+            })
+            .collect(),
+        hours: hour_counts(&stats.hours),
+        weekdays: weekday_counts(&stats.days),
+        median_min_item_level: stats.median_min_item_level(),
+        objectives: id_counts(stats.objective_counts()),
+        conditions: id_counts(stats.condition_counts()),
+        beginners_welcome_share: stats.beginners_welcome_share(),
+        filled: stats.num_filled(),
+        average_filled_lifetime_seconds: stats.average_filled_lifetime_seconds(),
+    }
+}
+
+fn id_counts(counts: Vec<FlagCount>) -> Vec<IdCount> {
+    counts
+        .into_iter()
+        .map(|count| IdCount {
+            id: count.flag,
+            count: count.count,
+        })
+        .collect()
+}
@@ -0,0 +1,216 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use warp::{filters::BoxedFilter, reply::Response, sse::Event, Filter, Reply};
+
+use crate::{
+    listing::{PartyFinderListing, SearchAreaFlags},
+    listing_container::QueriedListing,
+    store::{listing_identity, updated_minute, ListingIdentity, UpsertOutcome, ACTIVE_UPDATE_WINDOW},
+};
+
+use super::{
+    filters::parse_listings_query,
+    listings::{invalid_query_reply, project_event_summary},
+};
+
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingEventKind {
+    Created,
+    Updated,
+    Expired,
+}
+
+impl ListingEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ListingEvent {
+    pub kind: ListingEventKind,
+    pub listing: Arc<QueriedListing>,
+}
+
+struct TrackedListing {
+    listing: Arc<QueriedListing>,
+    expires_at: DateTime<Utc>,
+}
+
+/// In-process fan-out of listing upserts to `/api/v2/listings/stream` subscribers.
+///
+/// Listings are tracked by the same `(id, last_server_restart, created_world)` identity the
+/// upsert uses, so an `expired` event is emitted once a listing leaves the active window without
+/// being refreshed by another upload.
+pub struct ListingEvents {
+    sender: broadcast::Sender<ListingEvent>,
+    tracked: Mutex<HashMap<ListingIdentity, TrackedListing>>,
+}
+
+impl Default for ListingEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ListingEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
+        Self {
+            sender,
+            tracked: Default::default(),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ListingEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Tracks an upserted listing and broadcasts `created` or `updated`. An unchanged listing only
+    /// has its expiry pushed back; subscribers already have its current state. A refreshed listing
+    /// keeps the `created_at` it was first tracked with.
+    pub fn publish_upsert(&self, listing: PartyFinderListing, outcome: UpsertOutcome, now: DateTime<Utc>) {
+        let identity = listing_identity(&listing);
+        let remaining = chrono::Duration::seconds(i64::from(listing.seconds_remaining));
+        let mut tracked = self.tracked.lock().unwrap();
+        let created_at = match (outcome, tracked.get(&identity)) {
+            (UpsertOutcome::Inserted, _) | (_, None) => now,
+            (_, Some(previous)) => previous.listing.created_at,
+        };
+        let listing = Arc::new(QueriedListing {
+            created_at,
+            updated_at: now,
+            updated_minute: updated_minute(now),
+            time_left: f64::from(listing.seconds_remaining),
+            listing,
+        });
+
+        tracked.insert(
+            identity,
+            TrackedListing {
+                listing: Arc::clone(&listing),
+                expires_at: now + remaining.min(ACTIVE_UPDATE_WINDOW),
+            },
+        );
+        drop(tracked);
+
+        let kind = match outcome {
+            UpsertOutcome::Inserted => ListingEventKind::Created,
+            UpsertOutcome::Updated => ListingEventKind::Updated,
+            UpsertOutcome::Unchanged => return,
+        };
+        // no subscribers is not an error
+        let _ = self.sender.send(ListingEvent { kind, listing });
+    }
+
+    /// Tracked public listings still in their active window, by data centre of the created
+    /// world. Kept in memory, so it counts what was uploaded since startup without a store read.
+    pub fn active_by_data_centre(&self, now: DateTime<Utc>) -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for tracked in self.tracked.lock().unwrap().values() {
+            let listing = &tracked.listing.listing;
+            if tracked.expires_at <= now || listing.search_area.contains(SearchAreaFlags::PRIVATE) {
+                continue;
+            }
+
+            *counts.entry(listing.data_centre_name().unwrap_or("unknown")).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Emits `expired` for every tracked listing whose active window ended before `now`.
+    pub fn expire_stale(&self, now: DateTime<Utc>) -> usize {
+        let mut expired = Vec::new();
+        self.tracked.lock().unwrap().retain(|_, tracked| {
+            if tracked.expires_at > now {
+                return true;
+            }
+
+            expired.push(Arc::clone(&tracked.listing));
+            false
+        });
+
+        let count = expired.len();
+        for listing in expired {
+            let _ = self.sender.send(ListingEvent {
+                kind: ListingEventKind::Expired,
+                listing,
+            });
+        }
+
+        count
+    }
+}
+
+pub fn route(events: Arc<ListingEvents>) -> BoxedFilter<(Response,)> {
+    warp::path!("api" / "v2" / "listings" / "stream")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(
+            warp::query::<HashMap<String, String>>()
+                .or(warp::any().map(HashMap::new))
+                .unify(),
+        )
+        .and(warp::any().map(move || Arc::clone(&events)))
+        .and_then(stream)
+        .boxed()
+}
+
+#[cfg(test)]
+pub(crate) fn stream_route_for_tests() -> (Arc<ListingEvents>, BoxedFilter<(Response,)>) {
+    let events = Arc::new(ListingEvents::new());
+    (Arc::clone(&events), route(events))
+}
+
+async fn stream(
+    query: HashMap<String, String>,
+    events: Arc<ListingEvents>,
+) -> Result<Response, Infallible> {
+    let query = match parse_listings_query(&query) {
+        Ok(query) => query,
+        Err(error) => return Ok(invalid_query_reply(error).into_response()),
+    };
+
+    // subscribe before the response is returned so no upsert is missed between the two
+    let events = BroadcastStream::new(events.subscribe()).filter_map(move |event| {
+        let event = match event {
+            Ok(event) => event,
+            // the client fell behind the channel; tell it to refetch `/api/v2/listings`
+            Err(tokio_stream::wrappers::errors::BroadcastStreamRecvError::Lagged(skipped)) => {
+                return Some(Ok::<_, Infallible>(
+                    Event::default()
+                        .event("lagged")
+                        .data(skipped.to_string()),
+                ));
+            }
+        };
+
+        let mut summary = project_event_summary(&event.listing, &query)?;
+        if event.kind == ListingEventKind::Expired {
+            summary.time_left_seconds = 0;
+        }
+
+        Event::default()
+            .event(event.kind.as_str())
+            .id(summary.id.clone())
+            .json_data(&summary)
+            .ok()
+            .map(Ok)
+    });
+
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(events)).into_response())
+}
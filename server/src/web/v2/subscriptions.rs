@@ -0,0 +1,427 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    sync::Arc,
+};
+
+use chrono::Utc;
+use serde_json::{Map, Value};
+use warp::{
+    filters::BoxedFilter,
+    http::{header::WWW_AUTHENTICATE, StatusCode, Uri},
+    hyper::body::Bytes,
+    reply::Response,
+    Filter, Reply,
+};
+
+use crate::{
+    web::{
+        admin::{access, Access},
+        State,
+    },
+    webhooks::{Delivery, Subscription},
+};
+
+use super::{
+    contracts::{
+        DeliveryCollectionResponse, ErrorEnvelope, MemberEnvelope, SubscriptionCollectionResponse,
+        SubscriptionRequest, SubscriptionResponse, SubscriptionUpdate, WebhookAttempt,
+        WebhookDelivery, WebhookSubscription,
+    },
+    filters::{parse_listings_query, ListingsQuery},
+    lookups::lookup_collection,
+};
+
+const MAX_BODY_BYTES: u64 = 16 * 1024;
+const MIN_SECRET_CHARS: usize = 16;
+const MAX_SECRET_CHARS: usize = 256;
+/// Listing parameters that pick a page or an order, which mean nothing for new listings.
+const PAGING_FIELDS: [&str; 3] = ["page", "per_page", "sort"];
+/// How many deliveries `/api/v2/subscriptions/{id}/deliveries` shows.
+const DELIVERY_LOG_LIMIT: usize = 100;
+
+/// Operator-only, like `/admin`: every route needs the admin token and is not served without one.
+pub fn routes(state: Arc<State>) -> BoxedFilter<(Response,)> {
+    let authorization = warp::header::optional::<String>("authorization");
+    let with_state = warp::any().map(move || Arc::clone(&state));
+    let body = warp::body::content_length_limit(MAX_BODY_BYTES).and(warp::body::bytes());
+
+    let create = warp::path!("api" / "v2" / "subscriptions")
+        .and(warp::post())
+        .and(authorization)
+        .and(body)
+        .and(with_state.clone())
+        .and_then(create);
+    let list = warp::path!("api" / "v2" / "subscriptions")
+        .and(warp::get())
+        .and(authorization)
+        .and(with_state.clone())
+        .and_then(list);
+    let show = warp::path!("api" / "v2" / "subscriptions" / String)
+        .and(warp::get())
+        .and(authorization)
+        .and(with_state.clone())
+        .and_then(show);
+    let update = warp::path!("api" / "v2" / "subscriptions" / String)
+        .and(warp::patch())
+        .and(authorization)
+        .and(body)
+        .and(with_state.clone())
+        .and_then(update);
+    let delete = warp::path!("api" / "v2" / "subscriptions" / String)
+        .and(warp::delete())
+        .and(authorization)
+        .and(with_state.clone())
+        .and_then(delete);
+    let deliveries = warp::path!("api" / "v2" / "subscriptions" / String / "deliveries")
+        .and(warp::get())
+        .and(authorization)
+        .and(with_state)
+        .and_then(deliveries);
+
+    create
+        .or(list)
+        .unify()
+        .or(show)
+        .unify()
+        .or(update)
+        .unify()
+        .or(delete)
+        .unify()
+        .or(deliveries)
+        .unify()
+        .boxed()
+}
+
+async fn create(
+    authorization: Option<String>,
+    body: Bytes,
+    state: Arc<State>,
+) -> Result<Response, Infallible> {
+    if let Some(refusal) = refuse(&state, authorization.as_deref()) {
+        return Ok(refusal);
+    }
+    let (url, secret, filters, query) = match parse_subscription(&body) {
+        Ok(parsed) => parsed,
+        Err(error) => return Ok(error_reply(StatusCode::BAD_REQUEST, error)),
+    };
+
+    let subscription = Subscription {
+        id: mongodb::bson::oid::ObjectId::new().to_hex(),
+        url,
+        secret,
+        filters,
+        query,
+        enabled: true,
+        consecutive_failures: 0,
+        created_at: Utc::now(),
+    };
+    Ok(match state.store().insert_subscription(&subscription).await {
+        Ok(()) => {
+            let response: SubscriptionResponse = MemberEnvelope {
+                data: subscription_view(&subscription),
+            };
+            warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED).into_response()
+        }
+        Err(error) => store_error_reply(error),
+    })
+}
+
+async fn list(authorization: Option<String>, state: Arc<State>) -> Result<Response, Infallible> {
+    if let Some(refusal) = refuse(&state, authorization.as_deref()) {
+        return Ok(refusal);
+    }
+
+    Ok(match state.store().subscriptions().await {
+        Ok(subscriptions) => {
+            let response: SubscriptionCollectionResponse =
+                lookup_collection(subscriptions.iter().map(subscription_view).collect());
+            warp::reply::json(&response).into_response()
+        }
+        Err(error) => store_error_reply(error),
+    })
+}
+
+async fn show(
+    id: String,
+    authorization: Option<String>,
+    state: Arc<State>,
+) -> Result<Response, Infallible> {
+    if let Some(refusal) = refuse(&state, authorization.as_deref()) {
+        return Ok(refusal);
+    }
+
+    Ok(member_reply(&id, state.store().subscription(&id).await))
+}
+
+async fn update(
+    id: String,
+    authorization: Option<String>,
+    body: Bytes,
+    state: Arc<State>,
+) -> Result<Response, Infallible> {
+    if let Some(refusal) = refuse(&state, authorization.as_deref()) {
+        return Ok(refusal);
+    }
+    let update: SubscriptionUpdate = match serde_json::from_slice(&body) {
+        Ok(update) => update,
+        Err(error) => {
+            let error = ErrorEnvelope::invalid_body(
+                "body",
+                format!("body must be a subscription update: {error}"),
+            );
+            return Ok(error_reply(StatusCode::BAD_REQUEST, error));
+        }
+    };
+
+    Ok(member_reply(&id, state.store().set_subscription_enabled(&id, update.enabled).await))
+}
+
+async fn delete(
+    id: String,
+    authorization: Option<String>,
+    state: Arc<State>,
+) -> Result<Response, Infallible> {
+    if let Some(refusal) = refuse(&state, authorization.as_deref()) {
+        return Ok(refusal);
+    }
+
+    Ok(match state.store().delete_subscription(&id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => not_found_reply(&id),
+        Err(error) => store_error_reply(error),
+    })
+}
+
+async fn deliveries(
+    id: String,
+    authorization: Option<String>,
+    state: Arc<State>,
+) -> Result<Response, Infallible> {
+    if let Some(refusal) = refuse(&state, authorization.as_deref()) {
+        return Ok(refusal);
+    }
+
+    let store = state.store();
+    let deliveries = match store.subscription(&id).await {
+        Ok(Some(_)) => store.deliveries(&id, DELIVERY_LOG_LIMIT).await,
+        Ok(None) => return Ok(not_found_reply(&id)),
+        Err(error) => Err(error),
+    };
+    Ok(match deliveries {
+        Ok(deliveries) => {
+            let response: DeliveryCollectionResponse =
+                lookup_collection(deliveries.iter().map(delivery_view).collect());
+            warp::reply::json(&response).into_response()
+        }
+        Err(error) => store_error_reply(error),
+    })
+}
+
+/// Validates a `POST /api/v2/subscriptions` body into the URL, the secret, the filters as
+/// submitted and the query they parse to.
+pub fn parse_subscription(
+    body: &[u8],
+) -> Result<(String, String, BTreeMap<String, String>, ListingsQuery), ErrorEnvelope> {
+    let request: SubscriptionRequest = serde_json::from_slice(body).map_err(|error| {
+        ErrorEnvelope::invalid_body("body", format!("body must be a subscription: {error}"))
+    })?;
+
+    let valid_url = request.url.parse::<Uri>().is_ok_and(|uri| {
+        matches!(uri.scheme_str(), Some("http" | "https")) && uri.host().is_some()
+    });
+    if !valid_url {
+        return Err(ErrorEnvelope::invalid_body(
+            "url",
+            "url must be an absolute http or https URL",
+        ));
+    }
+
+    if !(MIN_SECRET_CHARS..=MAX_SECRET_CHARS).contains(&request.secret.chars().count()) {
+        return Err(ErrorEnvelope::invalid_body(
+            "secret",
+            format!("secret must be between {MIN_SECRET_CHARS} and {MAX_SECRET_CHARS} characters"),
+        ));
+    }
+
+    let (filters, query) = parse_filters(request.filters)?;
+
+    Ok((request.url, request.secret, filters, query))
+}
+
+/// Validates saved-search filters: the `GET /api/v2/listings` parameters with scalar values, minus
+/// paging and sorting. Errors name the field as `filters.<parameter>`.
+pub fn parse_filters(
+    submitted: BTreeMap<String, Value>,
+) -> Result<(BTreeMap<String, String>, ListingsQuery), ErrorEnvelope> {
+    let mut filters = BTreeMap::new();
+    for (field, value) in submitted {
+        if PAGING_FIELDS.contains(&field.as_str()) {
+            return Err(ErrorEnvelope::invalid_body(
+                format!("filters.{field}"),
+                format!("filters.{field} does not apply to saved searches"),
+            ));
+        }
+        let value = match value {
+            Value::String(value) => value,
+            Value::Number(value) => value.to_string(),
+            Value::Bool(value) => value.to_string(),
+            _ => {
+                return Err(ErrorEnvelope::invalid_body(
+                    format!("filters.{field}"),
+                    format!("filters.{field} must be a string, number or boolean"),
+                ))
+            }
+        };
+        filters.insert(field, value);
+    }
+
+    // the same parser as the query string, with fields reported under `filters`
+    let params = filters.clone().into_iter().collect::<HashMap<_, _>>();
+    let query = parse_listings_query(&params).map_err(|error| {
+        let field = error.error.details["field"].as_str().unwrap_or_default();
+        ErrorEnvelope::invalid_body(
+            format!("filters.{field}"),
+            format!("filters.{}", error.error.message),
+        )
+    })?;
+
+    Ok((filters, query))
+}
+
+fn subscription_view(subscription: &Subscription) -> WebhookSubscription {
+    WebhookSubscription {
+        id: subscription.id.clone(),
+        url: subscription.url.clone(),
+        filters: subscription.filters.clone(),
+        enabled: subscription.enabled,
+        consecutive_failures: subscription.consecutive_failures,
+        created_at: subscription.created_at.to_rfc3339(),
+    }
+}
+
+fn delivery_view(delivery: &Delivery) -> WebhookDelivery {
+    WebhookDelivery {
+        id: delivery.id.clone(),
+        listing_id: delivery.listing_id.clone(),
+        created_at: delivery.created_at.to_rfc3339(),
+        succeeded: delivery.succeeded,
+        attempts: delivery
+            .attempts
+            .iter()
+            .map(|attempt| WebhookAttempt {
+                started_at: attempt.started_at.to_rfc3339(),
+                status_code: attempt.status_code,
+                error: attempt.error.clone(),
+                duration_ms: attempt.duration_ms,
+            })
+            .collect(),
+    }
+}
+
+/// The reply for a request that may not use these routes, if it may not.
+fn refuse(state: &State, authorization: Option<&str>) -> Option<Response> {
+    match access(&state.admin, authorization) {
+        Access::Granted => None,
+        Access::Disabled => Some(error_reply(
+            StatusCode::NOT_FOUND,
+            ErrorEnvelope::new("not_found", "Not found", Map::new()),
+        )),
+        Access::Denied => {
+            let reply = error_reply(
+                StatusCode::UNAUTHORIZED,
+                ErrorEnvelope::new("unauthorized", "A valid admin token is required", Map::new()),
+            );
+            Some(warp::reply::with_header(reply, WWW_AUTHENTICATE, "Bearer").into_response())
+        }
+    }
+}
+
+fn member_reply(id: &str, subscription: anyhow::Result<Option<Subscription>>) -> Response {
+    match subscription {
+        Ok(Some(subscription)) => {
+            let response: SubscriptionResponse = MemberEnvelope {
+                data: subscription_view(&subscription),
+            };
+            warp::reply::json(&response).into_response()
+        }
+        Ok(None) => not_found_reply(id),
+        Err(error) => store_error_reply(error),
+    }
+}
+
+fn not_found_reply(id: &str) -> Response {
+    let mut details = Map::new();
+    details.insert("id".into(), Value::from(id));
+
+    error_reply(
+        StatusCode::NOT_FOUND,
+        ErrorEnvelope::new("not_found", "Subscription not found", details),
+    )
+}
+
+fn store_error_reply(error: anyhow::Error) -> Response {
+    eprintln!("{error:#?}");
+    error_reply(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorEnvelope::new(
+            "internal_error",
+            "Failed to access webhook subscriptions",
+            Map::new(),
+        ),
+    )
+}
+
+fn error_reply(status: StatusCode, error: ErrorEnvelope) -> Response {
+    warp::reply::with_status(warp::reply::json(&error), status).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(body: serde_json::Value) -> String {
+        let error = parse_subscription(body.to_string().as_bytes()).unwrap_err();
+        error.error.details["field"].as_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn filters_are_parsed_like_the_listings_query_string() {
+        let body = serde_json::json!({
+            "url": "https://example.com/hook",
+            "secret": "0123456789abcdef",
+            "filters": { "duty_id": 1006, "beginners_welcome": true, "job_ids": "19,24" },
+        });
+        let (_, _, filters, query) = parse_subscription(body.to_string().as_bytes()).unwrap();
+
+        assert_eq!(filters["duty_id"], "1006");
+        assert_eq!(query.duty_id, Some(1006));
+        assert_eq!(query.beginners_welcome, Some(true));
+        assert_eq!(query.job_ids, vec![19, 24]);
+    }
+
+    #[test]
+    fn rejects_bad_urls_secrets_and_filters() {
+        let body = |url: &str, secret: &str, filters: serde_json::Value| {
+            serde_json::json!({ "url": url, "secret": secret, "filters": filters })
+        };
+        let secret = "0123456789abcdef";
+
+        assert_eq!(field(body("ftp://example.com", secret, serde_json::json!({}))), "url");
+        assert_eq!(field(body("/hook", secret, serde_json::json!({}))), "url");
+        assert_eq!(field(body("http://example.com", "short", serde_json::json!({}))), "secret");
+        assert_eq!(
+            field(body("http://example.com", secret, serde_json::json!({ "page": 2 }))),
+            "filters.page",
+        );
+        assert_eq!(
+            field(body("http://example.com", secret, serde_json::json!({ "duty": "x" }))),
+            "filters.duty",
+        );
+        assert_eq!(
+            field(body("http://example.com", secret, serde_json::json!({ "job_ids": [19] }))),
+            "filters.job_ids",
+        );
+        assert_eq!(field(serde_json::json!({ "url": "http://example.com" })), "body");
+    }
+}
@@ -0,0 +1,446 @@
+//! Outbound webhooks for saved searches.
+//!
+//! A [`Subscription`] pairs a URL with a v2 filter set. When a listing is created that the
+//! filters match, the [`Dispatcher`] POSTs it to the URL as a [`WebhookPayload`] signed with the
+//! subscription's secret, retries failed attempts with [`Backoff`], and records every delivery so
+//! it can be inspected through `/api/v2/subscriptions/{id}/deliveries`. Subscriptions whose
+//! deliveries keep failing are disabled until they are re-enabled through the API.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use hyper_rustls::HttpsConnector;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::broadcast::{self, error::RecvError};
+use warp::{
+    http::{
+        header::{CONTENT_TYPE, USER_AGENT},
+        Request, StatusCode,
+    },
+    hyper::{client::HttpConnector, Body, Client},
+};
+
+use crate::{
+    config,
+    listing_container::QueriedListing,
+    store::ListingStore,
+    tasks::{jitter, Backoff},
+    web::v2::{
+        contracts::WebhookPayload,
+        filters::{parse_listings_query, ListingsQuery},
+        listings::{listing_summary, matches_query},
+        stream::{ListingEvent, ListingEventKind},
+    },
+};
+
+pub const SIGNATURE_HEADER: &str = "x-rpf-signature";
+pub const DELIVERY_HEADER: &str = "x-rpf-delivery";
+pub const EVENT_HEADER: &str = "x-rpf-event";
+pub const CREATED_EVENT: &str = "listing.created";
+
+/// How long the dispatcher matches against a loaded subscription list before reloading it.
+const SUBSCRIPTIONS_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A webhook registered through `POST /api/v2/subscriptions`, stored in `rpf.subscriptions`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(from = "StoredSubscription")]
+pub struct Subscription {
+    pub id: String,
+    pub url: String,
+    /// Key of the [`SIGNATURE_HEADER`] HMAC. Never returned by the API.
+    pub secret: String,
+    /// The filter parameters as they were submitted.
+    pub filters: BTreeMap<String, String>,
+    /// `filters` as parsed by `parse_listings_query`; listings are matched against this. Not
+    /// stored, so new filter fields do not break saved subscriptions; it is parsed again on load.
+    #[serde(skip)]
+    pub query: ListingsQuery,
+    pub enabled: bool,
+    /// Deliveries that failed since the last one that succeeded.
+    pub consecutive_failures: u32,
+    #[serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// A [`Subscription`] as stored, before its filters are parsed.
+#[derive(Deserialize)]
+struct StoredSubscription {
+    id: String,
+    url: String,
+    secret: String,
+    filters: BTreeMap<String, String>,
+    enabled: bool,
+    consecutive_failures: u32,
+    #[serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    created_at: DateTime<Utc>,
+}
+
+impl From<StoredSubscription> for Subscription {
+    /// Filters that no longer parse leave the subscription disabled rather than matching
+    /// everything; the stored row is untouched, so it can be fixed and re-enabled.
+    fn from(stored: StoredSubscription) -> Self {
+        let params = stored.filters.clone().into_iter().collect::<HashMap<_, _>>();
+        let (query, enabled) = match parse_listings_query(&params) {
+            Ok(query) => (query, stored.enabled),
+            Err(error) => {
+                eprintln!("subscription {} has invalid filters: {}", stored.id, error.error.message);
+                (ListingsQuery::default(), false)
+            }
+        };
+
+        Self {
+            id: stored.id,
+            url: stored.url,
+            secret: stored.secret,
+            filters: stored.filters,
+            query,
+            enabled,
+            consecutive_failures: stored.consecutive_failures,
+            created_at: stored.created_at,
+        }
+    }
+}
+
+/// One listing sent to one subscription, with every attempt it took. Stored in
+/// `rpf.webhook_deliveries`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Delivery {
+    pub id: String,
+    pub subscription_id: String,
+    /// The public id of the delivered listing.
+    pub listing_id: String,
+    #[serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    pub attempts: Vec<DeliveryAttempt>,
+    pub succeeded: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeliveryAttempt {
+    #[serde(with = "mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime")]
+    pub started_at: DateTime<Utc>,
+    /// Unset when no response arrived.
+    pub status_code: Option<u16>,
+    /// Why the attempt failed before a response arrived.
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+pub type HttpClient = Client<HttpsConnector<HttpConnector>>;
+
+/// A client for `http` and `https` URLs that trusts the bundled web PKI roots.
+pub fn http_client() -> HttpClient {
+    let connector = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_webpki_roots()
+        .https_or_http()
+        .enable_http1()
+        .build();
+    Client::builder().build(connector)
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of `body` keyed with `secret`.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Server errors, timeouts and rate limits may go away; other refusals will not.
+pub fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+pub struct Dispatcher {
+    store: Arc<dyn ListingStore>,
+    config: config::Webhooks,
+    client: HttpClient,
+    subscriptions: Mutex<Option<(Instant, Arc<Vec<Subscription>>)>>,
+}
+
+impl Dispatcher {
+    pub fn new(store: Arc<dyn ListingStore>, config: config::Webhooks) -> Self {
+        Self {
+            store,
+            config,
+            client: http_client(),
+            subscriptions: Default::default(),
+        }
+    }
+
+    /// Delivers every created listing to the enabled subscriptions it matches until `events`
+    /// closes. A store error ends the run, so the supervisor reports it and starts over.
+    pub async fn run(self: Arc<Self>, mut events: broadcast::Receiver<ListingEvent>) -> Result<()> {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    eprintln!("webhooks fell behind and skipped {skipped} listing events");
+                    continue;
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            };
+            if event.kind != ListingEventKind::Created {
+                continue;
+            }
+
+            for subscription in self.subscriptions().await?.iter() {
+                if subscription.enabled && matches_query(&event.listing, &subscription.query) {
+                    let dispatcher = Arc::clone(&self);
+                    let subscription = subscription.clone();
+                    let listing = Arc::clone(&event.listing);
+                    tokio::task::spawn(async move { dispatcher.deliver(subscription, &listing).await });
+                }
+            }
+        }
+    }
+
+    /// The stored subscriptions, reloaded at most once per [`SUBSCRIPTIONS_REFRESH_INTERVAL`] so
+    /// a burst of uploads does not query the store once per listing.
+    async fn subscriptions(&self) -> Result<Arc<Vec<Subscription>>> {
+        if let Some((loaded_at, subscriptions)) = &*self.subscriptions.lock().unwrap() {
+            if loaded_at.elapsed() < SUBSCRIPTIONS_REFRESH_INTERVAL {
+                return Ok(Arc::clone(subscriptions));
+            }
+        }
+
+        let subscriptions = Arc::new(self.store.subscriptions().await?);
+        *self.subscriptions.lock().unwrap() = Some((Instant::now(), Arc::clone(&subscriptions)));
+        Ok(subscriptions)
+    }
+
+    /// Sends `listing` to `subscription` and records the outcome, disabling the subscription once
+    /// too many deliveries in a row have failed.
+    pub async fn deliver(&self, subscription: Subscription, listing: &QueriedListing) -> Delivery {
+        let delivery = self.attempt_delivery(&subscription, listing).await;
+
+        match self
+            .store
+            .record_delivery(&delivery, self.config.disable_after_failures)
+            .await
+        {
+            Ok(Some(updated)) if subscription.enabled && !updated.enabled => eprintln!(
+                "disabled webhook subscription {} after {} failed deliveries",
+                updated.id, updated.consecutive_failures,
+            ),
+            Ok(_) => {}
+            Err(error) => eprintln!("could not record webhook delivery {}: {error:#?}", delivery.id),
+        }
+
+        delivery
+    }
+
+    /// POSTs the listing until it is accepted, refused for good, or out of attempts.
+    async fn attempt_delivery(&self, subscription: &Subscription, listing: &QueriedListing) -> Delivery {
+        let payload = WebhookPayload {
+            event: CREATED_EVENT.into(),
+            subscription_id: subscription.id.clone(),
+            delivery_id: mongodb::bson::oid::ObjectId::new().to_hex(),
+            listing: listing_summary(listing, &listing.listing),
+        };
+        let body = serde_json::to_vec(&payload).expect("webhook payloads always serialize");
+        let signature = signature(&subscription.secret, &body);
+        let backoff = Backoff {
+            base: Duration::from_millis(self.config.retry_base_ms),
+            max: Duration::from_millis(self.config.retry_max_ms),
+        };
+
+        let mut delivery = Delivery {
+            id: payload.delivery_id,
+            subscription_id: subscription.id.clone(),
+            listing_id: payload.listing.id,
+            created_at: Utc::now(),
+            attempts: Vec::new(),
+            succeeded: false,
+        };
+
+        for attempt in 1..=self.config.max_attempts.max(1) {
+            if attempt > 1 {
+                tokio::time::sleep(backoff.delay(attempt - 1, jitter())).await;
+            }
+
+            let started_at = Utc::now();
+            let started = Instant::now();
+            let result = self.post(&subscription.url, &body, &signature, &delivery.id).await;
+            let retry = result.as_ref().map_or(true, |status| is_retryable(*status));
+            delivery.succeeded = result.as_ref().is_ok_and(|status| status.is_success());
+            delivery.attempts.push(DeliveryAttempt {
+                started_at,
+                status_code: result.as_ref().ok().map(StatusCode::as_u16),
+                error: result.err().map(|error| format!("{error:#}")),
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+
+            if delivery.succeeded || !retry {
+                break;
+            }
+        }
+
+        delivery
+    }
+
+    async fn post(&self, url: &str, body: &[u8], signature: &str, delivery_id: &str) -> Result<StatusCode> {
+        let request = Request::post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(USER_AGENT, "remote-party-finder")
+            .header(SIGNATURE_HEADER, signature)
+            .header(DELIVERY_HEADER, delivery_id)
+            .header(EVENT_HEADER, CREATED_EVENT)
+            .body(Body::from(body.to_vec()))
+            .context("could not build webhook request")?;
+
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        let response = tokio::time::timeout(timeout, self.client.request(request))
+            .await
+            .context("webhook request timed out")?
+            .context("webhook request failed")?;
+        Ok(response.status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use tokio::sync::mpsc;
+    use warp::{hyper::body::Bytes, Filter};
+
+    use super::*;
+    use crate::store::MemoryStore;
+
+    /// A local HTTP server answering each request with the next of `statuses`, then 200.
+    fn stand_in(statuses: Vec<u16>) -> (SocketAddr, mpsc::UnboundedReceiver<(String, Bytes)>) {
+        let (sender, received) = mpsc::unbounded_channel();
+        let statuses = Arc::new(Mutex::new(statuses.into_iter()));
+        let route = warp::post()
+            .and(warp::header::<String>(SIGNATURE_HEADER))
+            .and(warp::body::bytes())
+            .map(move |signature: String, body: Bytes| {
+                sender.send((signature, body)).unwrap();
+                let status = statuses.lock().unwrap().next().unwrap_or(200);
+                warp::reply::with_status("", StatusCode::from_u16(status).unwrap())
+            });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::task::spawn(server);
+        (addr, received)
+    }
+
+    fn subscription(addr: SocketAddr) -> Subscription {
+        Subscription {
+            id: "sub".into(),
+            url: format!("http://{addr}/hook"),
+            secret: "0123456789abcdef".into(),
+            filters: Default::default(),
+            query: Default::default(),
+            enabled: true,
+            consecutive_failures: 0,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn listing() -> QueriedListing {
+        QueriedListing {
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            updated_minute: Utc::now(),
+            time_left: 1200.0,
+            listing: crate::test::listing(),
+        }
+    }
+
+    fn dispatcher(store: Arc<MemoryStore>, disable_after_failures: u32) -> Dispatcher {
+        Dispatcher::new(
+            store,
+            config::Webhooks {
+                max_attempts: 3,
+                retry_base_ms: 1,
+                retry_max_ms: 4,
+                timeout_ms: 5_000,
+                disable_after_failures,
+            },
+        )
+    }
+
+    #[test]
+    fn stored_subscriptions_keep_only_filters_and_reparse_them() {
+        let mut saved = subscription(SocketAddr::from(([127, 0, 0, 1], 0)));
+        saved.filters.insert("datacenter".into(), "陆行鸟".into());
+        saved.query.datacenter = Some("陆行鸟".into());
+
+        let mut document = mongodb::bson::to_document(&saved).unwrap();
+        assert!(!document.contains_key("query"));
+        let loaded = mongodb::bson::from_document::<Subscription>(document.clone()).unwrap();
+        assert_eq!((&loaded.filters, &loaded.query), (&saved.filters, &saved.query));
+        assert!(loaded.enabled);
+
+        // rows written with the old shape, or with filters the parser has since stopped accepting
+        document.insert("query", mongodb::bson::doc! { "page": 1 });
+        document.insert("filters", mongodb::bson::doc! { "duty": "55" });
+        let loaded = mongodb::bson::from_document::<Subscription>(document).unwrap();
+        assert!(!loaded.enabled);
+    }
+
+    #[test]
+    fn signature_is_hmac_sha256_of_the_body() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_attempts_are_retried_and_logged() {
+        let (addr, mut received) = stand_in(vec![503, 500]);
+        let store = Arc::new(MemoryStore::new());
+        let subscription = subscription(addr);
+        store.insert_subscription(&subscription).await.unwrap();
+
+        let delivery = dispatcher(Arc::clone(&store), 10)
+            .deliver(subscription.clone(), &listing())
+            .await;
+
+        assert!(delivery.succeeded);
+        let statuses = delivery.attempts.iter().map(|attempt| attempt.status_code).collect::<Vec<_>>();
+        assert_eq!(statuses, vec![Some(503), Some(500), Some(200)]);
+
+        let (header, body) = received.recv().await.unwrap();
+        assert_eq!(header, signature(&subscription.secret, &body));
+        let payload: WebhookPayload = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload.delivery_id, delivery.id);
+
+        assert_eq!(store.deliveries("sub", 10).await.unwrap(), vec![delivery]);
+    }
+
+    #[tokio::test]
+    async fn repeated_failures_disable_the_subscription() {
+        let (addr, _received) = stand_in(vec![500; 6]);
+        let store = Arc::new(MemoryStore::new());
+        store.insert_subscription(&subscription(addr)).await.unwrap();
+        let dispatcher = dispatcher(Arc::clone(&store), 2);
+
+        let first = dispatcher.deliver(subscription(addr), &listing()).await;
+        assert!(!first.succeeded);
+        assert_eq!(first.attempts.len(), 3);
+        assert!(store.subscription("sub").await.unwrap().unwrap().enabled);
+
+        dispatcher.deliver(subscription(addr), &listing()).await;
+        let stored = store.subscription("sub").await.unwrap().unwrap();
+        assert!(!stored.enabled);
+        assert_eq!(stored.consecutive_failures, 2);
+
+        // a refusal that will not go away is not retried
+        let (addr, _received) = stand_in(vec![404]);
+        let refused = dispatcher.deliver(subscription(addr), &listing()).await;
+        assert_eq!(refused.attempts.len(), 1);
+    }
+}